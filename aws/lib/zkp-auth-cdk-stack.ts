@@ -90,6 +90,10 @@ export class ZkpAuthCdkStack extends cdk.Stack {
     const serverHostname = `${serverServiceName}.${serverNamespaceName}`;
 
     clientContainer.addEnvironment("SERVER_HOST", serverHostname);
+    serverContainer.addEnvironment(
+      "ZKP_SERVER_IDENTITY",
+      `${serverHostname}:50051`
+    );
 
     const clientService = new ecs.FargateService(this, "ZkpAuthClientService", {
       cluster,
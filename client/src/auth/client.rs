@@ -1,50 +1,365 @@
-use std::collections::HashMap;
 use std::error::Error;
 
-use crypto::dl::prover::DlProver;
-use crypto::ec::prover::EcProver;
-use crypto::prover::Prover;
+#[cfg(feature = "openssl")]
+use crypto::ec::curves::NamedCurve;
+#[cfg(feature = "openssl")]
+use crypto::hybrid::HybridProver;
+#[cfg(feature = "openssl")]
+use crypto::okamoto::OkamotoProver;
+use crypto::prover::{ChaumPedersenProver, Prover};
+#[cfg(feature = "openssl")]
+use crypto::schnorr::SchnorrProver;
+#[cfg(feature = "openssl")]
+use crypto::slh_dsa::SlhDsaProver;
 use tonic::transport::Channel;
 
 use proto::zkp_auth::auth_client::AuthClient as ProtoAuthClient;
 use proto::zkp_auth::AuthAlgo;
+#[cfg(feature = "openssl")]
+use proto::zkp_auth::EcCurve;
 
-use util::params::{dl_params, ec_params};
+use util::params::ristretto_params;
+#[cfg(feature = "openssl")]
+use util::params::{dl_params, ec_params, EC_CURVE};
 
-use super::dl::DlAuthClient;
-use super::ec::EcAuthClient;
-use super::types::AuthClient;
+use super::identification::RistrettoAuthClient;
+#[cfg(feature = "openssl")]
+use super::identification::{
+    DlAuthClient, EcAuthClient, HybridDlAuthClient, HybridEcAuthClient, OkamotoDlAuthClient,
+    OkamotoEcAuthClient, SchnorrDlAuthClient, SchnorrEcAuthClient, SlhDsaAuthClient,
+};
+#[cfg(feature = "openssl")]
+use super::opaque::{OpaqueAuthClient, OpaqueSession};
+
+#[cfg(feature = "openssl")]
+fn proto_curve(curve: NamedCurve) -> EcCurve {
+    match curve {
+        NamedCurve::Secp256k1 => EcCurve::Secp256k1,
+        NamedCurve::P256 => EcCurve::P256,
+        NamedCurve::P384 => EcCurve::P384,
+        NamedCurve::P521 => EcCurve::P521,
+    }
+}
+
+// OPAQUE has no key to register, restore or prove with outside a password login
+#[cfg(feature = "openssl")]
+const OPAQUE_ONLY_WITH_PASSWORD: &str =
+    "OPAQUE registers with register_with_password and logs in with login_with_password";
+
+#[cfg(feature = "openssl")]
+const MEMBERSHIP_NEEDS_CHAUM_PEDERSEN: &str = "Membership logins need Chaum-Pedersen keys";
+
+// Every algo but Ristretto255 runs on OpenSSL's groups or primitives
+#[cfg(not(feature = "openssl"))]
+const NEEDS_OPENSSL: &str =
+    "This client was built without the openssl feature; only Ristretto255 is available";
 
 pub struct Client {
+    #[cfg(feature = "openssl")]
     ec_client: EcAuthClient,
+    #[cfg(feature = "openssl")]
     dl_client: DlAuthClient,
+    ristretto_client: RistrettoAuthClient,
+    #[cfg(feature = "openssl")]
+    schnorr_ec_client: SchnorrEcAuthClient,
+    #[cfg(feature = "openssl")]
+    schnorr_dl_client: SchnorrDlAuthClient,
+    #[cfg(feature = "openssl")]
+    okamoto_ec_client: OkamotoEcAuthClient,
+    #[cfg(feature = "openssl")]
+    okamoto_dl_client: OkamotoDlAuthClient,
+    #[cfg(feature = "openssl")]
+    opaque_client: OpaqueAuthClient,
+    #[cfg(feature = "openssl")]
+    slh_dsa_client: SlhDsaAuthClient,
+    #[cfg(feature = "openssl")]
+    hybrid_ec_client: HybridEcAuthClient,
+    #[cfg(feature = "openssl")]
+    hybrid_dl_client: HybridDlAuthClient,
 }
 
 impl Client {
+    #[cfg(feature = "openssl")]
+    pub async fn new(server_addr: String) -> Result<Self, Box<dyn Error>> {
+        Client::with_curve(server_addr, EC_CURVE).await
+    }
+
+    /// Connects a client for Ristretto255, the only algo without OpenSSL.
+    #[cfg(not(feature = "openssl"))]
     pub async fn new(server_addr: String) -> Result<Self, Box<dyn Error>> {
-        let channel = Channel::from_shared(server_addr)?.connect().await?;
+        Client::connect(server_addr).await
+    }
+
+    /// Connects a client whose EC registrations are made on `curve`.
+    #[cfg(feature = "openssl")]
+    pub async fn with_curve(
+        server_addr: String,
+        curve: NamedCurve,
+    ) -> Result<Self, Box<dyn Error>> {
+        Client::connect(server_addr, curve).await
+    }
+
+    async fn connect(
+        server_addr: String,
+        #[cfg(feature = "openssl")] curve: NamedCurve,
+    ) -> Result<Self, Box<dyn Error>> {
+        let endpoint = Channel::from_shared(server_addr)?;
+        // Logins are bound to the server as dialed, so a proof can't be
+        // relayed to another one
+        let server = endpoint
+            .uri()
+            .authority()
+            .ok_or("Server address has no host")?
+            .to_string();
+        let channel = endpoint.connect().await?;
         let client = ProtoAuthClient::new(channel.clone());
 
         Ok(Client {
-            ec_client: EcAuthClient {
-                client: client.clone(),
-                prover: EcProver::new(ec_params()?)?,
-                registrations: HashMap::new(),
-                authentication_states: HashMap::new(),
-            },
-            dl_client: DlAuthClient {
+            #[cfg(feature = "openssl")]
+            ec_client: EcAuthClient::new(
+                client.clone(),
+                server.clone(),
+                AuthAlgo::Ec,
+                Some(proto_curve(curve)),
+                ChaumPedersenProver::new(ec_params(curve)?)?,
+            ),
+            #[cfg(feature = "openssl")]
+            dl_client: DlAuthClient::new(
+                client.clone(),
+                server.clone(),
+                AuthAlgo::Dl,
+                None,
+                ChaumPedersenProver::new(dl_params()?)?,
+            ),
+            ristretto_client: RistrettoAuthClient::new(
+                client.clone(),
+                server.clone(),
+                AuthAlgo::Ristretto,
+                None,
+                ChaumPedersenProver::new(ristretto_params()?)?,
+            ),
+            #[cfg(feature = "openssl")]
+            schnorr_ec_client: SchnorrEcAuthClient::new(
+                client.clone(),
+                server.clone(),
+                AuthAlgo::SchnorrEc,
+                Some(proto_curve(curve)),
+                SchnorrProver::new(ec_params(curve)?),
+            ),
+            #[cfg(feature = "openssl")]
+            schnorr_dl_client: SchnorrDlAuthClient::new(
+                client.clone(),
+                server.clone(),
+                AuthAlgo::SchnorrDl,
+                None,
+                SchnorrProver::new(dl_params()?),
+            ),
+            #[cfg(feature = "openssl")]
+            okamoto_ec_client: OkamotoEcAuthClient::new(
+                client.clone(),
+                server.clone(),
+                AuthAlgo::OkamotoEc,
+                Some(proto_curve(curve)),
+                OkamotoProver::new(ec_params(curve)?),
+            ),
+            #[cfg(feature = "openssl")]
+            okamoto_dl_client: OkamotoDlAuthClient::new(
+                client.clone(),
+                server.clone(),
+                AuthAlgo::OkamotoDl,
+                None,
+                OkamotoProver::new(dl_params()?),
+            ),
+            #[cfg(feature = "openssl")]
+            opaque_client: OpaqueAuthClient::new(client.clone(), AuthAlgo::Opaque),
+            #[cfg(feature = "openssl")]
+            slh_dsa_client: SlhDsaAuthClient::new(
+                client.clone(),
+                server.clone(),
+                AuthAlgo::SlhDsa,
+                None,
+                SlhDsaProver::new(),
+            ),
+            #[cfg(feature = "openssl")]
+            hybrid_ec_client: HybridEcAuthClient::new(
+                client.clone(),
+                server.clone(),
+                AuthAlgo::HybridEc,
+                Some(proto_curve(curve)),
+                HybridProver {
+                    classical: ChaumPedersenProver::new(ec_params(curve)?)?,
+                    post_quantum: SlhDsaProver::new(),
+                },
+            ),
+            #[cfg(feature = "openssl")]
+            hybrid_dl_client: HybridDlAuthClient::new(
                 client,
-                prover: DlProver::new(dl_params()?)?,
-                registrations: HashMap::new(),
-                authentication_states: HashMap::new(),
-            },
+                server.clone(),
+                AuthAlgo::HybridDl,
+                None,
+                HybridProver {
+                    classical: ChaumPedersenProver::new(dl_params()?)?,
+                    post_quantum: SlhDsaProver::new(),
+                },
+            ),
         })
     }
 
     pub async fn register(&mut self, user: &str, algo: AuthAlgo) -> Result<(), Box<dyn Error>> {
         match algo {
+            #[cfg(feature = "openssl")]
             AuthAlgo::Ec => self.ec_client.register(user).await,
+            #[cfg(feature = "openssl")]
             AuthAlgo::Dl => self.dl_client.register(user).await,
+            AuthAlgo::Ristretto => self.ristretto_client.register(user).await,
+            #[cfg(feature = "openssl")]
+            AuthAlgo::SchnorrEc => self.schnorr_ec_client.register(user).await,
+            #[cfg(feature = "openssl")]
+            AuthAlgo::SchnorrDl => self.schnorr_dl_client.register(user).await,
+            #[cfg(feature = "openssl")]
+            AuthAlgo::OkamotoEc => self.okamoto_ec_client.register(user).await,
+            #[cfg(feature = "openssl")]
+            AuthAlgo::OkamotoDl => self.okamoto_dl_client.register(user).await,
+            #[cfg(feature = "openssl")]
+            AuthAlgo::SlhDsa => self.slh_dsa_client.register(user).await,
+            #[cfg(feature = "openssl")]
+            AuthAlgo::HybridDl => self.hybrid_dl_client.register(user).await,
+            #[cfg(feature = "openssl")]
+            AuthAlgo::HybridEc => self.hybrid_ec_client.register(user).await,
+            #[cfg(feature = "openssl")]
+            AuthAlgo::Opaque => Err(OPAQUE_ONLY_WITH_PASSWORD.into()),
+            #[cfg(not(feature = "openssl"))]
+            _ => Err(NEEDS_OPENSSL.into()),
+        }
+    }
+
+    pub async fn register_with_password(
+        &mut self,
+        user: &str,
+        password: &str,
+        algo: AuthAlgo,
+    ) -> Result<(), Box<dyn Error>> {
+        match algo {
+            #[cfg(feature = "openssl")]
+            AuthAlgo::Ec => self.ec_client.register_with_password(user, password).await,
+            #[cfg(feature = "openssl")]
+            AuthAlgo::Dl => self.dl_client.register_with_password(user, password).await,
+            AuthAlgo::Ristretto => {
+                self.ristretto_client
+                    .register_with_password(user, password)
+                    .await
+            }
+            #[cfg(feature = "openssl")]
+            AuthAlgo::SchnorrEc => {
+                self.schnorr_ec_client
+                    .register_with_password(user, password)
+                    .await
+            }
+            #[cfg(feature = "openssl")]
+            AuthAlgo::SchnorrDl => {
+                self.schnorr_dl_client
+                    .register_with_password(user, password)
+                    .await
+            }
+            #[cfg(feature = "openssl")]
+            AuthAlgo::OkamotoEc => {
+                self.okamoto_ec_client
+                    .register_with_password(user, password)
+                    .await
+            }
+            #[cfg(feature = "openssl")]
+            AuthAlgo::OkamotoDl => {
+                self.okamoto_dl_client
+                    .register_with_password(user, password)
+                    .await
+            }
+            #[cfg(feature = "openssl")]
+            AuthAlgo::SlhDsa => {
+                self.slh_dsa_client
+                    .register_with_password(user, password)
+                    .await
+            }
+            #[cfg(feature = "openssl")]
+            AuthAlgo::HybridDl => {
+                self.hybrid_dl_client
+                    .register_with_password(user, password)
+                    .await
+            }
+            #[cfg(feature = "openssl")]
+            AuthAlgo::HybridEc => {
+                self.hybrid_ec_client
+                    .register_with_password(user, password)
+                    .await
+            }
+            #[cfg(feature = "openssl")]
+            AuthAlgo::Opaque => self.opaque_client.register(user, password).await,
+            #[cfg(not(feature = "openssl"))]
+            _ => Err(NEEDS_OPENSSL.into()),
+        }
+    }
+
+    pub async fn restore_with_password(
+        &mut self,
+        user: &str,
+        password: &str,
+        algo: AuthAlgo,
+    ) -> Result<(), Box<dyn Error>> {
+        match algo {
+            #[cfg(feature = "openssl")]
+            AuthAlgo::Ec => self.ec_client.restore_with_password(user, password).await,
+            #[cfg(feature = "openssl")]
+            AuthAlgo::Dl => self.dl_client.restore_with_password(user, password).await,
+            AuthAlgo::Ristretto => {
+                self.ristretto_client
+                    .restore_with_password(user, password)
+                    .await
+            }
+            #[cfg(feature = "openssl")]
+            AuthAlgo::SchnorrEc => {
+                self.schnorr_ec_client
+                    .restore_with_password(user, password)
+                    .await
+            }
+            #[cfg(feature = "openssl")]
+            AuthAlgo::SchnorrDl => {
+                self.schnorr_dl_client
+                    .restore_with_password(user, password)
+                    .await
+            }
+            #[cfg(feature = "openssl")]
+            AuthAlgo::OkamotoEc => {
+                self.okamoto_ec_client
+                    .restore_with_password(user, password)
+                    .await
+            }
+            #[cfg(feature = "openssl")]
+            AuthAlgo::OkamotoDl => {
+                self.okamoto_dl_client
+                    .restore_with_password(user, password)
+                    .await
+            }
+            #[cfg(feature = "openssl")]
+            AuthAlgo::SlhDsa => {
+                self.slh_dsa_client
+                    .restore_with_password(user, password)
+                    .await
+            }
+            #[cfg(feature = "openssl")]
+            AuthAlgo::HybridDl => {
+                self.hybrid_dl_client
+                    .restore_with_password(user, password)
+                    .await
+            }
+            #[cfg(feature = "openssl")]
+            AuthAlgo::HybridEc => {
+                self.hybrid_ec_client
+                    .restore_with_password(user, password)
+                    .await
+            }
+            #[cfg(feature = "openssl")]
+            AuthAlgo::Opaque => Err(OPAQUE_ONLY_WITH_PASSWORD.into()),
+            #[cfg(not(feature = "openssl"))]
+            _ => Err(NEEDS_OPENSSL.into()),
         }
     }
 
@@ -54,8 +369,61 @@ impl Client {
         algo: AuthAlgo,
     ) -> Result<String, Box<dyn Error>> {
         match algo {
+            #[cfg(feature = "openssl")]
             AuthAlgo::Ec => self.ec_client.create_authentication_challenge(user).await,
+            #[cfg(feature = "openssl")]
             AuthAlgo::Dl => self.dl_client.create_authentication_challenge(user).await,
+            AuthAlgo::Ristretto => {
+                self.ristretto_client
+                    .create_authentication_challenge(user)
+                    .await
+            }
+            #[cfg(feature = "openssl")]
+            AuthAlgo::SchnorrEc => {
+                self.schnorr_ec_client
+                    .create_authentication_challenge(user)
+                    .await
+            }
+            #[cfg(feature = "openssl")]
+            AuthAlgo::SchnorrDl => {
+                self.schnorr_dl_client
+                    .create_authentication_challenge(user)
+                    .await
+            }
+            #[cfg(feature = "openssl")]
+            AuthAlgo::OkamotoEc => {
+                self.okamoto_ec_client
+                    .create_authentication_challenge(user)
+                    .await
+            }
+            #[cfg(feature = "openssl")]
+            AuthAlgo::OkamotoDl => {
+                self.okamoto_dl_client
+                    .create_authentication_challenge(user)
+                    .await
+            }
+            #[cfg(feature = "openssl")]
+            AuthAlgo::SlhDsa => {
+                self.slh_dsa_client
+                    .create_authentication_challenge(user)
+                    .await
+            }
+            #[cfg(feature = "openssl")]
+            AuthAlgo::HybridDl => {
+                self.hybrid_dl_client
+                    .create_authentication_challenge(user)
+                    .await
+            }
+            #[cfg(feature = "openssl")]
+            AuthAlgo::HybridEc => {
+                self.hybrid_ec_client
+                    .create_authentication_challenge(user)
+                    .await
+            }
+            #[cfg(feature = "openssl")]
+            AuthAlgo::Opaque => Err(OPAQUE_ONLY_WITH_PASSWORD.into()),
+            #[cfg(not(feature = "openssl"))]
+            _ => Err(NEEDS_OPENSSL.into()),
         }
     }
 
@@ -65,8 +433,112 @@ impl Client {
         algo: AuthAlgo,
     ) -> Result<String, Box<dyn Error>> {
         match algo {
+            #[cfg(feature = "openssl")]
             AuthAlgo::Ec => self.ec_client.verify_authentication(auth_id).await,
+            #[cfg(feature = "openssl")]
             AuthAlgo::Dl => self.dl_client.verify_authentication(auth_id).await,
+            AuthAlgo::Ristretto => self.ristretto_client.verify_authentication(auth_id).await,
+            #[cfg(feature = "openssl")]
+            AuthAlgo::SchnorrEc => self.schnorr_ec_client.verify_authentication(auth_id).await,
+            #[cfg(feature = "openssl")]
+            AuthAlgo::SchnorrDl => self.schnorr_dl_client.verify_authentication(auth_id).await,
+            #[cfg(feature = "openssl")]
+            AuthAlgo::OkamotoEc => self.okamoto_ec_client.verify_authentication(auth_id).await,
+            #[cfg(feature = "openssl")]
+            AuthAlgo::OkamotoDl => self.okamoto_dl_client.verify_authentication(auth_id).await,
+            #[cfg(feature = "openssl")]
+            AuthAlgo::SlhDsa => self.slh_dsa_client.verify_authentication(auth_id).await,
+            #[cfg(feature = "openssl")]
+            AuthAlgo::HybridDl => self.hybrid_dl_client.verify_authentication(auth_id).await,
+            #[cfg(feature = "openssl")]
+            AuthAlgo::HybridEc => self.hybrid_ec_client.verify_authentication(auth_id).await,
+            #[cfg(feature = "openssl")]
+            AuthAlgo::Opaque => Err(OPAQUE_ONLY_WITH_PASSWORD.into()),
+            #[cfg(not(feature = "openssl"))]
+            _ => Err(NEEDS_OPENSSL.into()),
         }
     }
+
+    pub async fn prove_membership(
+        &mut self,
+        user: &str,
+        group: &str,
+        algo: AuthAlgo,
+    ) -> Result<String, Box<dyn Error>> {
+        match algo {
+            #[cfg(feature = "openssl")]
+            AuthAlgo::Ec => self.ec_client.prove_membership(user, group).await,
+            #[cfg(feature = "openssl")]
+            AuthAlgo::Dl => self.dl_client.prove_membership(user, group).await,
+            AuthAlgo::Ristretto => self.ristretto_client.prove_membership(user, group).await,
+            #[cfg(feature = "openssl")]
+            AuthAlgo::SchnorrEc
+            | AuthAlgo::SchnorrDl
+            | AuthAlgo::OkamotoEc
+            | AuthAlgo::OkamotoDl
+            | AuthAlgo::SlhDsa => Err(MEMBERSHIP_NEEDS_CHAUM_PEDERSEN.into()),
+            #[cfg(feature = "openssl")]
+            AuthAlgo::HybridDl | AuthAlgo::HybridEc => {
+                Err("Membership logins would leave out the SLH-DSA key".into())
+            }
+            #[cfg(feature = "openssl")]
+            AuthAlgo::Opaque => Err(OPAQUE_ONLY_WITH_PASSWORD.into()),
+            #[cfg(not(feature = "openssl"))]
+            _ => Err(NEEDS_OPENSSL.into()),
+        }
+    }
+
+    pub async fn sign_action(
+        &mut self,
+        user: &str,
+        action: &[u8],
+        algo: AuthAlgo,
+    ) -> Result<String, Box<dyn Error>> {
+        match algo {
+            #[cfg(feature = "openssl")]
+            AuthAlgo::Ec => self.ec_client.sign_action(user, action).await,
+            #[cfg(feature = "openssl")]
+            AuthAlgo::Dl => self.dl_client.sign_action(user, action).await,
+            AuthAlgo::Ristretto => self.ristretto_client.sign_action(user, action).await,
+            #[cfg(feature = "openssl")]
+            AuthAlgo::SchnorrEc => self.schnorr_ec_client.sign_action(user, action).await,
+            #[cfg(feature = "openssl")]
+            AuthAlgo::SchnorrDl => self.schnorr_dl_client.sign_action(user, action).await,
+            #[cfg(feature = "openssl")]
+            AuthAlgo::OkamotoEc
+            | AuthAlgo::OkamotoDl
+            | AuthAlgo::SlhDsa
+            | AuthAlgo::HybridDl
+            | AuthAlgo::HybridEc => Err("Signed actions need a Schnorr key".into()),
+            #[cfg(feature = "openssl")]
+            AuthAlgo::Opaque => Err(OPAQUE_ONLY_WITH_PASSWORD.into()),
+            #[cfg(not(feature = "openssl"))]
+            _ => Err(NEEDS_OPENSSL.into()),
+        }
+    }
+
+    /// Logs in with `password`, returning the session id. OPAQUE runs its own
+    /// login; every other algo restores the password-derived secret and then
+    /// answers a challenge with it.
+    pub async fn login_with_password(
+        &mut self,
+        user: &str,
+        password: &str,
+        algo: AuthAlgo,
+    ) -> Result<String, Box<dyn Error>> {
+        #[cfg(feature = "openssl")]
+        if algo == AuthAlgo::Opaque {
+            return self.opaque_client.login(user, password).await;
+        }
+
+        self.restore_with_password(user, password, algo).await?;
+        let auth_id = self.create_authentication_challenge(user, algo).await?;
+        self.verify_authentication(&auth_id, algo).await
+    }
+
+    /// The keys the OPAQUE login that returned `session_id` ended with.
+    #[cfg(feature = "openssl")]
+    pub fn opaque_session(&self, session_id: &str) -> Option<&OpaqueSession> {
+        self.opaque_client.sessions.get(session_id)
+    }
 }
@@ -0,0 +1,211 @@
+use std::error::Error;
+
+use crypto::group::Group;
+#[cfg(feature = "openssl")]
+use crypto::hybrid::{HybridProver, HybridPublicKey, HybridResponse};
+use crypto::identification::IdentificationProver;
+use crypto::okamoto::{OkamotoProver, OkamotoResponse};
+use crypto::prover::{ChaumPedersenProver, ProverCommit, ProverPublicKeys};
+use crypto::schnorr::SchnorrProver;
+#[cfg(feature = "openssl")]
+use crypto::slh_dsa::{SlhDsaProver, VerifyingKey};
+
+use proto::zkp_auth::{
+    AuthenticationAnswerRequest, AuthenticationChallengeRequest, RegisterRequest,
+};
+
+/// Fills in the protocol's fields of the requests a login sends; the ones it
+/// has no use for are left empty.
+pub trait EncodeIdentification: IdentificationProver {
+    /// Reads the challenge scalar the server sent, when it picks one.
+    fn decode_challenge(&self, c: &[u8]) -> Result<Self::Scalar, Box<dyn Error>>;
+
+    fn encode_key(
+        &self,
+        key: &Self::PublicKey,
+        request: &mut RegisterRequest,
+    ) -> Result<(), Box<dyn Error>>;
+
+    fn encode_commitment(
+        &self,
+        commitment: &Self::Commitment,
+        request: &mut AuthenticationChallengeRequest,
+    ) -> Result<(), Box<dyn Error>>;
+
+    fn encode_response(
+        &self,
+        response: &Self::Response,
+        request: &mut AuthenticationAnswerRequest,
+    ) -> Result<(), Box<dyn Error>>;
+}
+
+impl<G: Group> EncodeIdentification for ChaumPedersenProver<G> {
+    fn decode_challenge(&self, c: &[u8]) -> Result<G::Scalar, Box<dyn Error>> {
+        Ok(self.params.decode_scalar(c)?)
+    }
+
+    fn encode_key(
+        &self,
+        key: &ProverPublicKeys<G::Element>,
+        request: &mut RegisterRequest,
+    ) -> Result<(), Box<dyn Error>> {
+        request.y1 = self.params.encode_element(&key.y1)?;
+        request.y2 = self.params.encode_element(&key.y2)?;
+        Ok(())
+    }
+
+    fn encode_commitment(
+        &self,
+        commit: &ProverCommit<G::Element>,
+        request: &mut AuthenticationChallengeRequest,
+    ) -> Result<(), Box<dyn Error>> {
+        request.r1 = self.params.encode_element(&commit.r1)?;
+        request.r2 = self.params.encode_element(&commit.r2)?;
+        Ok(())
+    }
+
+    fn encode_response(
+        &self,
+        s: &G::Scalar,
+        request: &mut AuthenticationAnswerRequest,
+    ) -> Result<(), Box<dyn Error>> {
+        request.s = self.params.encode_scalar(s)?;
+        Ok(())
+    }
+}
+
+impl<G: Group> EncodeIdentification for SchnorrProver<G> {
+    fn decode_challenge(&self, c: &[u8]) -> Result<G::Scalar, Box<dyn Error>> {
+        Ok(self.params.decode_scalar(c)?)
+    }
+
+    fn encode_key(
+        &self,
+        y1: &G::Element,
+        request: &mut RegisterRequest,
+    ) -> Result<(), Box<dyn Error>> {
+        request.y1 = self.params.encode_element(y1)?;
+        Ok(())
+    }
+
+    fn encode_commitment(
+        &self,
+        r1: &G::Element,
+        request: &mut AuthenticationChallengeRequest,
+    ) -> Result<(), Box<dyn Error>> {
+        request.r1 = self.params.encode_element(r1)?;
+        Ok(())
+    }
+
+    fn encode_response(
+        &self,
+        s: &G::Scalar,
+        request: &mut AuthenticationAnswerRequest,
+    ) -> Result<(), Box<dyn Error>> {
+        request.s = self.params.encode_scalar(s)?;
+        Ok(())
+    }
+}
+
+impl<G: Group> EncodeIdentification for OkamotoProver<G> {
+    fn decode_challenge(&self, c: &[u8]) -> Result<G::Scalar, Box<dyn Error>> {
+        Ok(self.params.decode_scalar(c)?)
+    }
+
+    fn encode_key(
+        &self,
+        y1: &G::Element,
+        request: &mut RegisterRequest,
+    ) -> Result<(), Box<dyn Error>> {
+        request.y1 = self.params.encode_element(y1)?;
+        Ok(())
+    }
+
+    fn encode_commitment(
+        &self,
+        r1: &G::Element,
+        request: &mut AuthenticationChallengeRequest,
+    ) -> Result<(), Box<dyn Error>> {
+        request.r1 = self.params.encode_element(r1)?;
+        Ok(())
+    }
+
+    fn encode_response(
+        &self,
+        response: &OkamotoResponse<G::Scalar>,
+        request: &mut AuthenticationAnswerRequest,
+    ) -> Result<(), Box<dyn Error>> {
+        request.s = self.params.encode_scalar(&response.s1)?;
+        request.s2 = self.params.encode_scalar(&response.s2)?;
+        Ok(())
+    }
+}
+
+#[cfg(feature = "openssl")]
+impl EncodeIdentification for SlhDsaProver {
+    // The server only ever sends a nonce to sign
+    fn decode_challenge(&self, _c: &[u8]) -> Result<(), Box<dyn Error>> {
+        Err("SLH-DSA challenges carry no scalar".into())
+    }
+
+    fn encode_key(
+        &self,
+        key: &VerifyingKey,
+        request: &mut RegisterRequest,
+    ) -> Result<(), Box<dyn Error>> {
+        request.pq_public_key = key.to_bytes().to_vec();
+        Ok(())
+    }
+
+    // SLH-DSA commits to nothing: the signature is made on the server's nonce
+    fn encode_commitment(
+        &self,
+        _commitment: &(),
+        _request: &mut AuthenticationChallengeRequest,
+    ) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+
+    fn encode_response(
+        &self,
+        signature: &Vec<u8>,
+        request: &mut AuthenticationAnswerRequest,
+    ) -> Result<(), Box<dyn Error>> {
+        request.signature = signature.clone();
+        Ok(())
+    }
+}
+
+#[cfg(feature = "openssl")]
+impl<G: Group> EncodeIdentification for HybridProver<G> {
+    fn decode_challenge(&self, c: &[u8]) -> Result<G::Scalar, Box<dyn Error>> {
+        self.classical.decode_challenge(c)
+    }
+
+    fn encode_key(
+        &self,
+        key: &HybridPublicKey<G::Element>,
+        request: &mut RegisterRequest,
+    ) -> Result<(), Box<dyn Error>> {
+        self.classical.encode_key(&key.keys, request)?;
+        self.post_quantum.encode_key(&key.pq_key, request)
+    }
+
+    fn encode_commitment(
+        &self,
+        commit: &ProverCommit<G::Element>,
+        request: &mut AuthenticationChallengeRequest,
+    ) -> Result<(), Box<dyn Error>> {
+        self.classical.encode_commitment(commit, request)
+    }
+
+    fn encode_response(
+        &self,
+        response: &HybridResponse<G::Scalar>,
+        request: &mut AuthenticationAnswerRequest,
+    ) -> Result<(), Box<dyn Error>> {
+        self.classical.encode_response(&response.s, request)?;
+        self.post_quantum
+            .encode_response(&response.signature, request)
+    }
+}
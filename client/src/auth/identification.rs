@@ -0,0 +1,400 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "openssl")]
+use crypto::dl::params::DlParams;
+#[cfg(feature = "openssl")]
+use crypto::ec::params::EcParams;
+use crypto::group::Group;
+#[cfg(feature = "openssl")]
+use crypto::hybrid::HybridProver;
+use crypto::identification::{Challenge, IdentificationProver};
+use crypto::membership::MembershipProver;
+#[cfg(feature = "openssl")]
+use crypto::okamoto::OkamotoProver;
+use crypto::password::{random_salt, PasswordKdf};
+use crypto::prover::{ChaumPedersenProver, ProverPublicKeys};
+use crypto::ristretto::params::RistrettoParams;
+#[cfg(feature = "openssl")]
+use crypto::schnorr::SchnorrProver;
+use crypto::schnorr::{SchnorrSecret, SchnorrSigner};
+#[cfg(feature = "openssl")]
+use crypto::slh_dsa::SlhDsaProver;
+use tonic::{transport::Channel, Request};
+
+use proto::zkp_auth::auth_client::AuthClient as ProtoAuthClient;
+use proto::zkp_auth::{
+    ActionChallengeRequest, AuthAlgo, AuthenticationAnswerRequest, AuthenticationChallengeRequest,
+    EcCurve, GetSaltRequest, MembershipAnswerRequest, MembershipChallengeRequest, RegisterRequest,
+    SignedActionRequest,
+};
+
+use util::transcript::{action_transcript, login_transcript, membership_transcript};
+
+use super::conversion::EncodeIdentification;
+use super::types::{AuthenticationState, Registration};
+
+/// Context a login's commitment nonce is hedged with. It must differ between
+/// runs: the same k answering two different challenges reveals x.
+pub fn session_context(user: &str, session: u64) -> Result<Vec<u8>, Box<dyn Error>> {
+    let time = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos();
+
+    Ok([user.as_bytes(), &session.to_be_bytes(), &time.to_be_bytes()].concat())
+}
+
+#[cfg(feature = "openssl")]
+pub type DlAuthClient = IdentificationClient<ChaumPedersenProver<DlParams>>;
+#[cfg(feature = "openssl")]
+pub type EcAuthClient = IdentificationClient<ChaumPedersenProver<EcParams>>;
+pub type RistrettoAuthClient = IdentificationClient<ChaumPedersenProver<RistrettoParams>>;
+#[cfg(feature = "openssl")]
+pub type SchnorrDlAuthClient = IdentificationClient<SchnorrProver<DlParams>>;
+#[cfg(feature = "openssl")]
+pub type SchnorrEcAuthClient = IdentificationClient<SchnorrProver<EcParams>>;
+#[cfg(feature = "openssl")]
+pub type OkamotoDlAuthClient = IdentificationClient<OkamotoProver<DlParams>>;
+#[cfg(feature = "openssl")]
+pub type OkamotoEcAuthClient = IdentificationClient<OkamotoProver<EcParams>>;
+#[cfg(feature = "openssl")]
+pub type SlhDsaAuthClient = IdentificationClient<SlhDsaProver>;
+#[cfg(feature = "openssl")]
+pub type HybridDlAuthClient = IdentificationClient<HybridProver<DlParams>>;
+#[cfg(feature = "openssl")]
+pub type HybridEcAuthClient = IdentificationClient<HybridProver<EcParams>>;
+
+/// Auth client for one identification protocol.
+pub struct IdentificationClient<P: IdentificationProver> {
+    pub client: ProtoAuthClient<Channel>,
+    /// The server's `host:port` as dialed, which its transcripts are bound to.
+    pub server: String,
+    pub auth_algo: AuthAlgo,
+    /// Curve the prover's params live on, for EC; every registration is made on it.
+    pub ec_curve: Option<EcCurve>,
+    pub prover: P,
+    /// Argon2id costs for password-derived secrets; must match across devices.
+    pub kdf: PasswordKdf,
+    pub registrations: HashMap<String, Registration<P::Secret, P::PublicKey>>,
+    pub authentication_states: HashMap<String, AuthenticationState<P::Nonce, P::Scalar>>,
+    /// Challenges requested so far, mixed into the nonce of each.
+    pub sessions: u64,
+}
+
+impl<P: EncodeIdentification> IdentificationClient<P> {
+    pub fn new(
+        client: ProtoAuthClient<Channel>,
+        server: String,
+        auth_algo: AuthAlgo,
+        ec_curve: Option<EcCurve>,
+        prover: P,
+    ) -> Self {
+        IdentificationClient {
+            client,
+            server,
+            auth_algo,
+            ec_curve,
+            prover,
+            kdf: PasswordKdf::default(),
+            registrations: HashMap::new(),
+            authentication_states: HashMap::new(),
+            sessions: 0,
+        }
+    }
+
+    fn ec_curve(&self) -> i32 {
+        self.ec_curve.map_or(0, |curve| curve as i32)
+    }
+
+    fn registration(
+        &self,
+        secret: P::Secret,
+    ) -> Result<Registration<P::Secret, P::PublicKey>, Box<dyn Error>> {
+        Ok(Registration {
+            key: self.prover.public_key(&secret)?,
+            secret,
+        })
+    }
+
+    async fn register_secret(
+        &mut self,
+        user: &str,
+        secret: P::Secret,
+        salt: Vec<u8>,
+    ) -> Result<(), Box<dyn Error>> {
+        let registration = self.registration(secret)?;
+
+        let mut request = RegisterRequest {
+            user: user.to_string(),
+            auth_algo: self.auth_algo as i32,
+            ec_curve: self.ec_curve(),
+            salt,
+            ..Default::default()
+        };
+        self.prover.encode_key(&registration.key, &mut request)?;
+
+        self.client.register(Request::new(request)).await?;
+        self.registrations.insert(user.to_string(), registration);
+        Ok(())
+    }
+
+    pub async fn register(&mut self, user: &str) -> Result<(), Box<dyn Error>> {
+        let secret = self.prover.generate_secret()?;
+        self.register_secret(user, secret, Vec::new()).await
+    }
+
+    /// Registers with a secret derived from `password` and a fresh salt the
+    /// server keeps.
+    pub async fn register_with_password(
+        &mut self,
+        user: &str,
+        password: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let salt = random_salt()?;
+        let secret =
+            self.prover
+                .secret_from_password(&self.kdf, user, password.as_bytes(), &salt)?;
+        self.register_secret(user, secret, salt).await
+    }
+
+    /// Re-derives the secret of a user registered with a password, e.g. on a
+    /// new device.
+    pub async fn restore_with_password(
+        &mut self,
+        user: &str,
+        password: &str,
+    ) -> Result<(), Box<dyn Error>> {
+        let request = GetSaltRequest {
+            user: user.to_string(),
+            auth_algo: self.auth_algo as i32,
+        };
+        let salt = self.client.get_salt(Request::new(request)).await?;
+
+        // A wrong password, or an unknown user's decoy salt, still gives a
+        // secret; it just won't match the registered key, so logins fail
+        let secret = self.prover.secret_from_password(
+            &self.kdf,
+            user,
+            password.as_bytes(),
+            &salt.into_inner().salt,
+        )?;
+        let registration = self.registration(secret)?;
+        self.registrations.insert(user.to_string(), registration);
+        Ok(())
+    }
+
+    pub async fn create_authentication_challenge(
+        &mut self,
+        user: &str,
+    ) -> Result<String, Box<dyn Error>> {
+        let registration = self.registrations.get(user).ok_or("User not registered")?;
+
+        self.sessions += 1;
+        let context = session_context(user, self.sessions)?;
+        let (k, commitment) = self.prover.commitment(&registration.secret, &context)?;
+
+        let mut request = AuthenticationChallengeRequest {
+            user: user.to_string(),
+            auth_algo: self.auth_algo as i32,
+            ec_curve: self.ec_curve(),
+            ..Default::default()
+        };
+        self.prover.encode_commitment(&commitment, &mut request)?;
+
+        let response = self
+            .client
+            .create_authentication_challenge(Request::new(request))
+            .await?;
+        let resp = response.into_inner();
+        let auth_id = resp.auth_id;
+        let challenge = Challenge {
+            c: match resp.c.is_empty() {
+                true => None,
+                false => Some(self.prover.decode_challenge(&resp.c)?),
+            },
+            nonce: resp.nonce,
+        };
+
+        // Only answer the challenge the transcript commits the server to:
+        // answering one it picked freely would sign whatever it hashed
+        let accepted = self.prover.accepts_challenge(
+            &registration.key,
+            &commitment,
+            &challenge,
+            &login_transcript(&self.server, user, &auth_id),
+        )?;
+        if !accepted {
+            return Err("Challenge does not match the login transcript".into());
+        }
+
+        self.authentication_states.insert(
+            auth_id.clone(),
+            AuthenticationState {
+                k,
+                challenge,
+                user: user.to_string(),
+            },
+        );
+        Ok(auth_id)
+    }
+
+    pub async fn verify_authentication(&mut self, auth_id: &str) -> Result<String, Box<dyn Error>> {
+        let state = self
+            .authentication_states
+            .get(auth_id)
+            .ok_or("Invalid auth_id")?;
+        let registration = self
+            .registrations
+            .get(&state.user)
+            .ok_or("User not registered")?;
+
+        let response = self.prover.respond(
+            &registration.secret,
+            &state.k,
+            &state.challenge,
+            &login_transcript(&self.server, &state.user, auth_id),
+        )?;
+
+        let mut request = AuthenticationAnswerRequest {
+            auth_id: auth_id.to_string(),
+            auth_algo: self.auth_algo as i32,
+            ..Default::default()
+        };
+        self.prover.encode_response(&response, &mut request)?;
+
+        let response = self
+            .client
+            .verify_authentication(Request::new(request))
+            .await?;
+        self.authentication_states.remove(auth_id);
+        Ok(response.into_inner().session_id)
+    }
+}
+
+impl<G: Group> IdentificationClient<ChaumPedersenProver<G>> {
+    // Decodes a public key received from the server and checks it is a group element
+    fn decode_element(
+        &self,
+        name: &'static str,
+        data: &[u8],
+    ) -> Result<G::Element, Box<dyn Error>> {
+        let element = self.prover.params.decode_element(data)?;
+        self.prover.params.check_element(name, &element)?;
+
+        Ok(element)
+    }
+
+    /// Logs in as one of the members of `group` registered with the same
+    /// parameters, without revealing which, and returns the session id.
+    pub async fn prove_membership(
+        &mut self,
+        user: &str,
+        group: &str,
+    ) -> Result<String, Box<dyn Error>> {
+        let registration = self.registrations.get(user).ok_or("User not registered")?;
+
+        let request = MembershipChallengeRequest {
+            auth_algo: self.auth_algo as i32,
+            ec_curve: self.ec_curve(),
+            group: group.to_string(),
+        };
+        let challenge = self
+            .client
+            .create_membership_challenge(Request::new(request))
+            .await?
+            .into_inner();
+
+        let own_y1 = self.prover.params.encode_element(&registration.key.y1)?;
+        let own_y2 = self.prover.params.encode_element(&registration.key.y2)?;
+        let index = challenge
+            .members
+            .iter()
+            .position(|member| member.y1 == own_y1 && member.y2 == own_y2)
+            .ok_or("User is not in the anonymity set")?;
+        let members = challenge
+            .members
+            .iter()
+            .map(|member| {
+                Ok(ProverPublicKeys {
+                    y1: self.decode_element("y1", &member.y1)?,
+                    y2: self.decode_element("y2", &member.y2)?,
+                })
+            })
+            .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+        let keys: Vec<_> = members
+            .iter()
+            .map(|member| ProverPublicKeys {
+                y1: &member.y1,
+                y2: &member.y2,
+            })
+            .collect();
+
+        let transcript = membership_transcript(&self.server, &challenge.auth_id);
+        let membership =
+            MembershipProver::with_rng(self.prover.params.clone(), self.prover.rng.clone());
+        let proof = membership.prove(&keys, index, &registration.secret, &transcript)?;
+
+        let (c, s) = proof
+            .responses
+            .iter()
+            .map(|response| {
+                Ok((
+                    self.prover.params.encode_scalar(&response.c)?,
+                    self.prover.params.encode_scalar(&response.s)?,
+                ))
+            })
+            .collect::<Result<Vec<_>, Box<dyn Error>>>()?
+            .into_iter()
+            .unzip();
+        let request = MembershipAnswerRequest {
+            auth_id: challenge.auth_id,
+            c,
+            s,
+            auth_algo: self.auth_algo as i32,
+        };
+
+        let response = self.client.verify_membership(Request::new(request)).await?;
+        Ok(response.into_inner().session_id)
+    }
+}
+
+impl<P: EncodeIdentification + SchnorrSecret> IdentificationClient<P> {
+    /// Signs `action` with the login secret under a fresh action challenge
+    /// from the server and submits it, returning the action id.
+    pub async fn sign_action(
+        &mut self,
+        user: &str,
+        action: &[u8],
+    ) -> Result<String, Box<dyn Error>> {
+        let registration = self.registrations.get(user).ok_or("User not registered")?;
+
+        let request = ActionChallengeRequest {
+            user: user.to_string(),
+            auth_algo: self.auth_algo as i32,
+            ec_curve: self.ec_curve(),
+        };
+        let action_id = self
+            .client
+            .create_action_challenge(Request::new(request))
+            .await?
+            .into_inner()
+            .action_id;
+
+        let transcript = action_transcript(&self.server, user, &action_id, action);
+        let group = self.prover.group();
+        let signer = SchnorrSigner::new(group.clone());
+        let signature = signer.sign(P::schnorr_secret(&registration.secret), &transcript)?;
+
+        let request = SignedActionRequest {
+            user: user.to_string(),
+            action: action.to_vec(),
+            c: group.encode_scalar(&signature.c)?,
+            s: group.encode_scalar(&signature.s)?,
+            auth_algo: self.auth_algo as i32,
+            ec_curve: self.ec_curve(),
+            action_id,
+        };
+
+        let response = self.client.signed_action(Request::new(request)).await?;
+        Ok(response.into_inner().action_id)
+    }
+}
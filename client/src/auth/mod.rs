@@ -1,4 +1,6 @@
 pub mod client;
-pub mod dl;
-pub mod ec;
+pub mod conversion;
+pub mod identification;
+#[cfg(feature = "openssl")]
+pub mod opaque;
 pub mod types;
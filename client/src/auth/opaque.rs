@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+use std::error::Error;
+
+use crypto::opaque::client::OpaqueClient;
+use crypto::opaque::messages::{Ke2, RegistrationResponse};
+use crypto::opaque::{ExportKey, SessionKey};
+use crypto::password::PasswordKdf;
+use tonic::{transport::Channel, Request};
+
+use proto::zkp_auth::auth_client::AuthClient as ProtoAuthClient;
+use proto::zkp_auth::{
+    AuthAlgo, OpaqueLoginFinishRequest, OpaqueLoginStartRequest, OpaqueRegistrationFinishRequest,
+    OpaqueRegistrationStartRequest,
+};
+
+/// The keys a finished OPAQUE login leaves the client with.
+pub struct OpaqueSession {
+    /// Shared with the server.
+    pub session_key: SessionKey,
+    /// Known only to the client, and the same on every login with the password.
+    pub export_key: ExportKey,
+}
+
+/// Auth client for OPAQUE. Nothing is kept between logins: every one starts
+/// from the password, and the server never sees it.
+pub struct OpaqueAuthClient {
+    pub client: ProtoAuthClient<Channel>,
+    pub auth_algo: AuthAlgo,
+    pub opaque: OpaqueClient,
+    pub sessions: HashMap<String, OpaqueSession>,
+}
+
+impl OpaqueAuthClient {
+    pub fn new(client: ProtoAuthClient<Channel>, auth_algo: AuthAlgo) -> Self {
+        OpaqueAuthClient {
+            client,
+            auth_algo,
+            opaque: OpaqueClient::new(PasswordKdf::default()),
+            sessions: HashMap::new(),
+        }
+    }
+
+    pub async fn register(&mut self, user: &str, password: &str) -> Result<(), Box<dyn Error>> {
+        let (registration_request, state) = self.opaque.registration_start(password.as_bytes())?;
+
+        let request = OpaqueRegistrationStartRequest {
+            user: user.to_string(),
+            registration_request: registration_request.serialize(),
+            auth_algo: self.auth_algo as i32,
+        };
+        let response = self
+            .client
+            .opaque_registration_start(Request::new(request))
+            .await?;
+        let response = response.into_inner();
+        let registration_id = response.registration_id;
+        let response = RegistrationResponse::deserialize(&response.registration_response)?;
+
+        let registration = self.opaque.registration_finish(state, &response)?;
+
+        let request = OpaqueRegistrationFinishRequest {
+            user: user.to_string(),
+            registration_record: registration.record.serialize(),
+            auth_algo: self.auth_algo as i32,
+            registration_id,
+        };
+        self.client
+            .opaque_registration_finish(Request::new(request))
+            .await?;
+        Ok(())
+    }
+
+    /// Logs in with `password`, returning the session id the keys are kept under.
+    pub async fn login(&mut self, user: &str, password: &str) -> Result<String, Box<dyn Error>> {
+        let (ke1, state) = self.opaque.login_start(password.as_bytes())?;
+
+        let request = OpaqueLoginStartRequest {
+            user: user.to_string(),
+            ke1: ke1.serialize(),
+            auth_algo: self.auth_algo as i32,
+        };
+        let response = self
+            .client
+            .opaque_login_start(Request::new(request))
+            .await?
+            .into_inner();
+        let ke2 = Ke2::deserialize(&response.ke2)?;
+
+        let login = self.opaque.login_finish(state, &ke2)?;
+
+        let request = OpaqueLoginFinishRequest {
+            auth_id: response.auth_id,
+            ke3: login.ke3.serialize(),
+            auth_algo: self.auth_algo as i32,
+        };
+        let response = self
+            .client
+            .opaque_login_finish(Request::new(request))
+            .await?;
+        let session_id = response.into_inner().session_id;
+
+        self.sessions.insert(
+            session_id.clone(),
+            OpaqueSession {
+                session_key: login.session_key,
+                export_key: login.export_key,
+            },
+        );
+        Ok(session_id)
+    }
+}
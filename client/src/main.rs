@@ -1,41 +1,109 @@
 use std::env;
 
 use client::auth::client::Client;
+#[cfg(feature = "openssl")]
+use crypto::ec::curves::NamedCurve;
 use proto::zkp_auth::AuthAlgo;
+#[cfg(feature = "openssl")]
+use util::params::EC_CURVE;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let args: Vec<String> = env::args().collect();
 
-    if args.len() != 5 {
+    if args.len() != 5 && args.len() != 6 {
         eprintln!(
-            "Usage: {} <username> <auth_algo> <server_host> <server_port>",
+            "Usage: {} <username> <auth_algo> <server_host> <server_port> [password]",
             args[0]
         );
-        eprintln!("  auth_algo: 'dl' for Discrete Logarithm or 'ec' for Elliptic Curve");
+        eprintln!("  auth_algo: 'dl' for Discrete Logarithm, 'ec' for Elliptic Curve or 'ristretto' for Ristretto255");
+        eprintln!(
+            "             'ec-<curve>' picks the curve: secp256k1 (default), p256, p384, p521"
+        );
+        eprintln!(
+            "             'schnorr-' or 'okamoto-' before 'dl' or 'ec' picks single-base Schnorr or Okamoto"
+        );
+        eprintln!("             'slh-dsa' for post-quantum SLH-DSA identification");
+        eprintln!(
+            "             'hybrid-' before 'dl' or 'ec' requires both Chaum-Pedersen and SLH-DSA"
+        );
+        eprintln!("             'opaque' for an OPAQUE password login, which needs a password");
+        eprintln!("  password:  derive the secret from it instead of generating a random one");
         std::process::exit(1);
     }
 
     let username = &args[1];
-    let auth_algo = match args[2].to_lowercase().as_str() {
-        "dl" => AuthAlgo::Dl,
-        "ec" => AuthAlgo::Ec,
+    let algo = args[2].to_lowercase();
+    // An optional protocol prefix, then the group, then for EC the curve
+    let (protocol, group) = match algo.split_once('-') {
+        Some((protocol @ ("schnorr" | "okamoto" | "hybrid"), group)) => (protocol, group),
+        _ => ("chaum-pedersen", algo.as_str()),
+    };
+    #[cfg(feature = "openssl")]
+    let (group, curve) = match group.split_once('-') {
+        Some(("ec", name)) => match NamedCurve::from_name(name) {
+            Some(curve) => ("ec", curve),
+            None => {
+                eprintln!("Invalid curve. Use one of secp256k1, p256, p384, p521");
+                std::process::exit(1);
+            }
+        },
+        _ => (group, EC_CURVE),
+    };
+    let auth_algo = match (protocol, group) {
+        ("chaum-pedersen", "dl") => AuthAlgo::Dl,
+        ("chaum-pedersen", "ec") => AuthAlgo::Ec,
+        ("chaum-pedersen", "ristretto") => AuthAlgo::Ristretto,
+        ("schnorr", "dl") => AuthAlgo::SchnorrDl,
+        ("schnorr", "ec") => AuthAlgo::SchnorrEc,
+        ("okamoto", "dl") => AuthAlgo::OkamotoDl,
+        ("okamoto", "ec") => AuthAlgo::OkamotoEc,
+        ("hybrid", "dl") => AuthAlgo::HybridDl,
+        ("hybrid", "ec") => AuthAlgo::HybridEc,
+        ("chaum-pedersen", "opaque") => AuthAlgo::Opaque,
+        ("chaum-pedersen", "slh-dsa") => AuthAlgo::SlhDsa,
         _ => {
             eprintln!(
-                "Invalid auth_algo. Use 'dl' for Discrete Logarithm or 'ec' for Elliptic Curve"
+                "Invalid auth_algo. Use one of dl, ec, ristretto, schnorr-dl, schnorr-ec, okamoto-dl, okamoto-ec, hybrid-dl, hybrid-ec, opaque, slh-dsa"
             );
             std::process::exit(1);
         }
     };
     let server_host = &args[3];
     let server_port = &args[4];
+    let password = args.get(5);
+    if auth_algo == AuthAlgo::Opaque && password.is_none() {
+        eprintln!("OPAQUE needs a password");
+        std::process::exit(1);
+    }
 
-    let mut client = Client::new(format!("http://{}:{}", server_host, server_port)).await?;
+    let server_addr = format!("http://{}:{}", server_host, server_port);
+    #[cfg(feature = "openssl")]
+    let mut client = Client::with_curve(server_addr, curve).await?;
+    #[cfg(not(feature = "openssl"))]
+    let mut client = Client::new(server_addr).await?;
 
     // Register
-    client.register(username, auth_algo).await?;
+    match password {
+        Some(password) => {
+            client
+                .register_with_password(username, password, auth_algo)
+                .await?
+        }
+        None => client.register(username, auth_algo).await?,
+    }
     println!("Registered user: {}", username);
 
+    // OPAQUE logs in with the password alone, and ends with a key shared with the server
+    if let (AuthAlgo::Opaque, Some(password)) = (auth_algo, password) {
+        let session_id = client
+            .login_with_password(username, password, auth_algo)
+            .await?;
+        println!("Logged in. Session ID: {}", session_id);
+        println!("Established a session key shared with the server");
+        return Ok(());
+    }
+
     // Create authentication challenge
     let auth_id = client
         .create_authentication_challenge(username, auth_algo)
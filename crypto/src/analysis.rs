@@ -0,0 +1,176 @@
+//! Tools that reason about transcripts instead of taking part in a run: the
+//! special-soundness extractor and the honest-verifier simulator. They back
+//! the soundness and zero-knowledge tests of every group, and show what a
+//! reused nonce gives away.
+
+use crate::error::{CryptoError, ExtractError};
+use crate::group::Group;
+use crate::prover::{ProverChallengeResponse, ProverCommit};
+use crate::random::RandomSource;
+use crate::secret::SecretScalar;
+use crate::util::random_scalar;
+use crate::verifier::ProofTranscript;
+
+/// Recovers `x` from two accepting transcripts that share the public keys and
+/// commitment but answer different challenges, as happens when a prover reuses
+/// `k`: from `s_a = k - c_a x` and `s_b = k - c_b x`,
+/// `x = (s_b - s_a) / (c_a - c_b)`.
+///
+/// The candidate is checked against `y1` and `y2`, so a transcript that does
+/// not verify gives `NotAccepting` rather than a wrong `x`.
+pub fn extract<G: Group>(
+    group: &G,
+    a: &ProofTranscript<G::Element, G::Scalar>,
+    b: &ProofTranscript<G::Element, G::Scalar>,
+) -> Result<SecretScalar<G::Scalar>, ExtractError> {
+    if !group.element_eq(a.y1, b.y1)? || !group.element_eq(a.y2, b.y2)? {
+        return Err(ExtractError::DifferentKeys);
+    }
+    if !group.element_eq(a.r1, b.r1)? || !group.element_eq(a.r2, b.r2)? {
+        return Err(ExtractError::DifferentCommitments);
+    }
+
+    let dc = group.scalar_sub(a.c, b.c)?;
+    let Some(dc_inv) = group.scalar_invert(&dc)? else {
+        return Err(ExtractError::SameChallenge);
+    };
+    let ds = group.scalar_sub(b.s, a.s)?;
+    let x = SecretScalar::new(group.scalar_mul(&ds, &dc_inv)?);
+
+    let y1 = group.exp_g(x.expose())?;
+    let y2 = group.exp_h(x.expose())?;
+    if !group.element_eq(&y1, a.y1)? || !group.element_eq(&y2, a.y2)? {
+        return Err(ExtractError::NotAccepting);
+    }
+
+    Ok(x)
+}
+
+/// Produces an accepting transcript for the challenge `c` without knowing `x`,
+/// by picking `s` first and solving for the commitment `(g^s y1^c, h^s y2^c)`.
+/// Its distribution is the same as an honest run's with that challenge, which
+/// is why an honest verifier learns nothing from the protocol. `s` is drawn
+/// from `source`.
+#[allow(clippy::type_complexity)]
+pub fn simulate<G: Group>(
+    source: &dyn RandomSource,
+    group: &G,
+    y1: &G::Element,
+    y2: &G::Element,
+    c: &G::Scalar,
+) -> Result<(ProverCommit<G::Element>, ProverChallengeResponse<G::Scalar>), CryptoError> {
+    let s = random_scalar(source, group)?;
+
+    let r1 = group.exp_g_mul(&s, y1, c)?;
+    let r2 = group.exp_h_mul(&s, y2, c)?;
+
+    Ok((ProverCommit { r1, r2 }, ProverChallengeResponse { s }))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::CryptoError;
+    #[cfg(feature = "openssl")]
+    use openssl::nid::Nid;
+
+    use super::*;
+    #[cfg(feature = "openssl")]
+    use crate::dl::{groups::NamedGroup, params::DlParams};
+    #[cfg(feature = "openssl")]
+    use crate::ec::params::EcParams;
+    use crate::prover::{ChaumPedersenProver, Prover, ProverPublicKeys};
+    use crate::ristretto::params::RistrettoParams;
+    use crate::verifier::{ChaumPedersenVerifier, Verifier};
+
+    const ROUNDS: usize = 8;
+
+    // Soundness: two answers to one commitment always give x back
+    fn check_extract<G: Group>(group: G) -> Result<(), ExtractError> {
+        let prover = ChaumPedersenProver::new(group.clone())?;
+        let verifier = ChaumPedersenVerifier::new(group.clone())?;
+
+        for _ in 0..ROUNDS {
+            let x = prover.random()?;
+            let ProverPublicKeys { y1, y2 } = prover.public_keys(&x)?;
+            let k = prover.random()?;
+            let ProverCommit { r1, r2 } = prover.commit(&k)?;
+            let (c_a, c_b) = (verifier.random()?, verifier.random()?);
+            let s_a = prover.challenge_response(&k, &c_a, &x)?.s;
+            let s_b = prover.challenge_response(&k, &c_b, &x)?.s;
+
+            let a = ProofTranscript {
+                y1: &y1,
+                y2: &y2,
+                r1: &r1,
+                r2: &r2,
+                c: &c_a,
+                s: &s_a,
+            };
+            let b = ProofTranscript {
+                c: &c_b,
+                s: &s_b,
+                ..a
+            };
+            assert_eq!(extract(&group, &a, &b)?.expose(), x.expose());
+
+            assert!(matches!(
+                extract(&group, &a, &a),
+                Err(ExtractError::SameChallenge)
+            ));
+            let other = prover.commit(&prover.random()?)?;
+            let moved = ProofTranscript { r1: &other.r1, ..b };
+            assert!(matches!(
+                extract(&group, &a, &moved),
+                Err(ExtractError::DifferentCommitments)
+            ));
+            let wrong_s = group.scalar_sub(&s_b, &group.reduce_scalar(&[1])?)?;
+            let forged = ProofTranscript { s: &wrong_s, ..b };
+            assert!(matches!(
+                extract(&group, &a, &forged),
+                Err(ExtractError::NotAccepting)
+            ));
+        }
+
+        Ok(())
+    }
+
+    // Zero knowledge: simulated transcripts verify, without x
+    fn check_simulate<G: Group>(group: G) -> Result<(), CryptoError> {
+        let prover = ChaumPedersenProver::new(group.clone())?;
+        let verifier = ChaumPedersenVerifier::new(group.clone())?;
+        let ProverPublicKeys { y1, y2 } = prover.public_keys(&prover.random()?)?;
+
+        for _ in 0..ROUNDS {
+            let c = verifier.random()?;
+            let (ProverCommit { r1, r2 }, ProverChallengeResponse { s }) =
+                simulate(&*verifier.rng, &group, &y1, &y2, &c)?;
+
+            assert!(
+                verifier.check(&y1, &y2, &r1, &r2, &c, &s)?,
+                "simulated transcript does not verify"
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract() -> Result<(), ExtractError> {
+        #[cfg(feature = "openssl")]
+        {
+            check_extract(DlParams::from_named_group(NamedGroup::Ffdhe2048, b"seed")?)?;
+            check_extract(EcParams::new(Nid::X9_62_PRIME256V1)?)?;
+        }
+        check_extract(RistrettoParams::new()?)
+    }
+
+    #[test]
+    fn test_simulate() -> Result<(), CryptoError> {
+        #[cfg(feature = "openssl")]
+        {
+            check_simulate(DlParams::from_named_group(NamedGroup::Ffdhe2048, b"seed")?)?;
+            check_simulate(EcParams::new(Nid::X9_62_PRIME256V1)?)?;
+        }
+        check_simulate(RistrettoParams::new()?)
+    }
+}
@@ -2,12 +2,18 @@ use openssl::bn::BigNumContext;
 use std::cell::RefCell;
 
 thread_local! {
+    // BN_CTX_new only fails when allocation does, and Rust aborts on that anyway
     pub static BN_CTX: RefCell<BigNumContext> = RefCell::new(BigNumContext::new().unwrap());
 }
 
+/// Runs `f` with this thread's `BN_CTX`. The `Group` methods borrow it
+/// themselves, so a call nested inside another gets a context of its own.
 pub fn with_bn_ctx<F, R>(f: F) -> R
 where
     F: FnOnce(&mut BigNumContext) -> R,
 {
-    BN_CTX.with(|ctx| f(&mut ctx.borrow_mut()))
+    BN_CTX.with(|ctx| match ctx.try_borrow_mut() {
+        Ok(mut ctx) => f(&mut ctx),
+        Err(_) => f(&mut BigNumContext::new().unwrap()),
+    })
 }
@@ -0,0 +1,93 @@
+use openssl::bn::BigNum;
+use openssl::error::ErrorStack;
+
+/// Standardised safe-prime groups (p = 2q + 1) of production size. The
+/// generator 2 has order q = (p - 1) / 2 in each of them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NamedGroup {
+    /// RFC 3526 2048-bit MODP group (group 14)
+    Modp2048,
+    /// RFC 3526 3072-bit MODP group (group 15)
+    Modp3072,
+    /// RFC 3526 4096-bit MODP group (group 16)
+    Modp4096,
+    /// RFC 7919 ffdhe2048
+    Ffdhe2048,
+    /// RFC 7919 ffdhe3072
+    Ffdhe3072,
+    /// RFC 7919 ffdhe4096
+    Ffdhe4096,
+}
+
+impl NamedGroup {
+    pub fn name(&self) -> &'static str {
+        match self {
+            NamedGroup::Modp2048 => "modp2048",
+            NamedGroup::Modp3072 => "modp3072",
+            NamedGroup::Modp4096 => "modp4096",
+            NamedGroup::Ffdhe2048 => "ffdhe2048",
+            NamedGroup::Ffdhe3072 => "ffdhe3072",
+            NamedGroup::Ffdhe4096 => "ffdhe4096",
+        }
+    }
+
+    pub fn prime(&self) -> Result<BigNum, ErrorStack> {
+        match self {
+            NamedGroup::Modp2048 => BigNum::get_rfc3526_prime_2048(),
+            NamedGroup::Modp3072 => BigNum::get_rfc3526_prime_3072(),
+            NamedGroup::Modp4096 => BigNum::get_rfc3526_prime_4096(),
+            NamedGroup::Ffdhe2048 => BigNum::from_hex_str(FFDHE2048_P),
+            NamedGroup::Ffdhe3072 => BigNum::from_hex_str(FFDHE3072_P),
+            NamedGroup::Ffdhe4096 => BigNum::from_hex_str(FFDHE4096_P),
+        }
+    }
+
+    pub fn generator(&self) -> Result<BigNum, ErrorStack> {
+        BigNum::from_u32(2)
+    }
+}
+
+const FFDHE2048_P: &str = concat!(
+    "FFFFFFFFFFFFFFFFADF85458A2BB4A9AAFDC5620273D3CF1D8B9C583CE2D3695",
+    "A9E13641146433FBCC939DCE249B3EF97D2FE363630C75D8F681B202AEC4617A",
+    "D3DF1ED5D5FD65612433F51F5F066ED0856365553DED1AF3B557135E7F57C935",
+    "984F0C70E0E68B77E2A689DAF3EFE8721DF158A136ADE73530ACCA4F483A797A",
+    "BC0AB182B324FB61D108A94BB2C8E3FBB96ADAB760D7F4681D4F42A3DE394DF4",
+    "AE56EDE76372BB190B07A7C8EE0A6D709E02FCE1CDF7E2ECC03404CD28342F61",
+    "9172FE9CE98583FF8E4F1232EEF28183C3FE3B1B4C6FAD733BB5FCBC2EC22005",
+    "C58EF1837D1683B2C6F34A26C1B2EFFA886B423861285C97FFFFFFFFFFFFFFFF",
+);
+
+const FFDHE3072_P: &str = concat!(
+    "FFFFFFFFFFFFFFFFADF85458A2BB4A9AAFDC5620273D3CF1D8B9C583CE2D3695",
+    "A9E13641146433FBCC939DCE249B3EF97D2FE363630C75D8F681B202AEC4617A",
+    "D3DF1ED5D5FD65612433F51F5F066ED0856365553DED1AF3B557135E7F57C935",
+    "984F0C70E0E68B77E2A689DAF3EFE8721DF158A136ADE73530ACCA4F483A797A",
+    "BC0AB182B324FB61D108A94BB2C8E3FBB96ADAB760D7F4681D4F42A3DE394DF4",
+    "AE56EDE76372BB190B07A7C8EE0A6D709E02FCE1CDF7E2ECC03404CD28342F61",
+    "9172FE9CE98583FF8E4F1232EEF28183C3FE3B1B4C6FAD733BB5FCBC2EC22005",
+    "C58EF1837D1683B2C6F34A26C1B2EFFA886B4238611FCFDCDE355B3B6519035B",
+    "BC34F4DEF99C023861B46FC9D6E6C9077AD91D2691F7F7EE598CB0FAC186D91C",
+    "AEFE130985139270B4130C93BC437944F4FD4452E2D74DD364F2E21E71F54BFF",
+    "5CAE82AB9C9DF69EE86D2BC522363A0DABC521979B0DEADA1DBF9A42D5C4484E",
+    "0ABCD06BFA53DDEF3C1B20EE3FD59D7C25E41D2B66C62E37FFFFFFFFFFFFFFFF",
+);
+
+const FFDHE4096_P: &str = concat!(
+    "FFFFFFFFFFFFFFFFADF85458A2BB4A9AAFDC5620273D3CF1D8B9C583CE2D3695",
+    "A9E13641146433FBCC939DCE249B3EF97D2FE363630C75D8F681B202AEC4617A",
+    "D3DF1ED5D5FD65612433F51F5F066ED0856365553DED1AF3B557135E7F57C935",
+    "984F0C70E0E68B77E2A689DAF3EFE8721DF158A136ADE73530ACCA4F483A797A",
+    "BC0AB182B324FB61D108A94BB2C8E3FBB96ADAB760D7F4681D4F42A3DE394DF4",
+    "AE56EDE76372BB190B07A7C8EE0A6D709E02FCE1CDF7E2ECC03404CD28342F61",
+    "9172FE9CE98583FF8E4F1232EEF28183C3FE3B1B4C6FAD733BB5FCBC2EC22005",
+    "C58EF1837D1683B2C6F34A26C1B2EFFA886B4238611FCFDCDE355B3B6519035B",
+    "BC34F4DEF99C023861B46FC9D6E6C9077AD91D2691F7F7EE598CB0FAC186D91C",
+    "AEFE130985139270B4130C93BC437944F4FD4452E2D74DD364F2E21E71F54BFF",
+    "5CAE82AB9C9DF69EE86D2BC522363A0DABC521979B0DEADA1DBF9A42D5C4484E",
+    "0ABCD06BFA53DDEF3C1B20EE3FD59D7C25E41D2B669E1EF16E6F52C3164DF4FB",
+    "7930E9E4E58857B6AC7D5F42D69F6D187763CF1D5503400487F55BA57E31CC7A",
+    "7135C886EFB4318AED6A1E012D9E6832A907600A918130C46DC778F971AD0038",
+    "092999A333CB8B7A1A1DB93D7140003C2A4ECEA9F98D0ACC0A8291CDCEC97DCF",
+    "8EC9B55A7F88A46B4DB5A851F44182E1C68A007E5E655F6AFFFFFFFFFFFFFFFF",
+);
@@ -1,19 +1,24 @@
+pub mod groups;
 pub mod params;
+pub mod precompute;
 pub mod prover;
 pub mod verifier;
 
 #[cfg(test)]
 mod tests {
-    use openssl::error::ErrorStack;
+    use crate::error::CryptoError;
+    use openssl::bn::BigNum;
 
     use crate::{
-        dl::{params::DlParams, prover::DlProver, verifier::DlVerifier},
+        dl::{groups::NamedGroup, params::DlParams, prover::DlProver, verifier::DlVerifier},
+        fiat_shamir,
         prover::{Prover, ProverChallengeResponse, ProverCommit, ProverPublicKeys},
-        verifier::Verifier,
+        transcript::Transcript,
+        verifier::{ProofTranscript, Verifier},
     };
 
     #[test]
-    fn test_dl_chaum_pedersen_protocol() -> Result<(), ErrorStack> {
+    fn test_dl_chaum_pedersen_protocol() -> Result<(), CryptoError> {
         let params = DlParams::new()?;
         let prover = DlProver::new(params.clone())?;
         let verifier = DlVerifier::new(params.clone())?;
@@ -49,7 +54,7 @@ mod tests {
     }
 
     #[test]
-    fn test_dl_incorrect_prover_secret() -> Result<(), ErrorStack> {
+    fn test_dl_incorrect_prover_secret() -> Result<(), CryptoError> {
         let params = DlParams::new()?;
         let prover = DlProver::new(params.clone())?;
         let verifier = DlVerifier::new(params.clone())?;
@@ -71,7 +76,7 @@ mod tests {
     }
 
     #[test]
-    fn test_dl_tampered_public_keys_y1() -> Result<(), ErrorStack> {
+    fn test_dl_tampered_public_keys_y1() -> Result<(), CryptoError> {
         let params = DlParams::new()?;
         let prover = DlProver::new(params.clone())?;
         let verifier = DlVerifier::new(params.clone())?;
@@ -93,7 +98,7 @@ mod tests {
     }
 
     #[test]
-    fn test_dl_tampered_public_keys_y2() -> Result<(), ErrorStack> {
+    fn test_dl_tampered_public_keys_y2() -> Result<(), CryptoError> {
         let params = DlParams::new()?;
         let prover = DlProver::new(params.clone())?;
         let verifier = DlVerifier::new(params.clone())?;
@@ -115,7 +120,7 @@ mod tests {
     }
 
     #[test]
-    fn test_dl_incorrect_commitment_r1() -> Result<(), ErrorStack> {
+    fn test_dl_incorrect_commitment_r1() -> Result<(), CryptoError> {
         let params = DlParams::new()?;
         let prover = DlProver::new(params.clone())?;
         let verifier = DlVerifier::new(params.clone())?;
@@ -137,7 +142,7 @@ mod tests {
     }
 
     #[test]
-    fn test_dl_incorrect_commitment_r2() -> Result<(), ErrorStack> {
+    fn test_dl_incorrect_commitment_r2() -> Result<(), CryptoError> {
         let params = DlParams::new()?;
         let prover = DlProver::new(params.clone())?;
         let verifier = DlVerifier::new(params.clone())?;
@@ -159,7 +164,7 @@ mod tests {
     }
 
     #[test]
-    fn test_dl_incorrect_challenge_response() -> Result<(), ErrorStack> {
+    fn test_dl_incorrect_challenge_response() -> Result<(), CryptoError> {
         let params = DlParams::new()?;
         let prover = DlProver::new(params.clone())?;
         let verifier = DlVerifier::new(params.clone())?;
@@ -184,7 +189,7 @@ mod tests {
     }
 
     #[test]
-    fn test_dl_mismatched_parameters() -> Result<(), ErrorStack> {
+    fn test_dl_mismatched_parameters() -> Result<(), CryptoError> {
         let params1 = DlParams::new()?;
         let params2 = DlParams::new()?;
         let prover = DlProver::new(params1.clone())?;
@@ -205,4 +210,183 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_dl_non_interactive_proof() -> Result<(), CryptoError> {
+        let params = DlParams::new()?;
+        let prover = DlProver::new(params.clone())?;
+        let verifier = DlVerifier::new(params.clone())?;
+
+        let x = prover.random()?;
+        let ProverPublicKeys { y1, y2 } = prover.public_keys(&x)?;
+        let proof = prover.prove(&x, &Transcript::new(b"batch-job-42"))?;
+
+        let valid = verifier.verify(&y1, &y2, &proof, &Transcript::new(b"batch-job-42"))?;
+        assert!(valid, "Non-interactive proof verification failed");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dl_non_interactive_proof_wrong_context() -> Result<(), CryptoError> {
+        let params = DlParams::new()?;
+        let prover = DlProver::new(params.clone())?;
+        let verifier = DlVerifier::new(params.clone())?;
+
+        let x = prover.random()?;
+        let ProverPublicKeys { y1, y2 } = prover.public_keys(&x)?;
+        let proof = prover.prove(&x, &Transcript::new(b"batch-job-42"))?;
+
+        let valid = verifier.verify(&y1, &y2, &proof, &Transcript::new(b"batch-job-43"))?;
+        assert!(!valid, "Verification should fail with a different context");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dl_non_interactive_proof_tampered_response() -> Result<(), CryptoError> {
+        let params = DlParams::new()?;
+        let prover = DlProver::new(params.clone())?;
+        let verifier = DlVerifier::new(params.clone())?;
+
+        let x = prover.random()?;
+        let ProverPublicKeys { y1, y2 } = prover.public_keys(&x)?;
+        let mut proof = prover.prove(&x, &Transcript::new(b"batch-job-42"))?;
+
+        // Tamper with s
+        proof.s.add_word(1)?;
+
+        let valid = verifier.verify(&y1, &y2, &proof, &Transcript::new(b"batch-job-42"))?;
+        assert!(!valid, "Verification should fail with tampered proof");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dl_proof_bound_to_transcript() -> Result<(), CryptoError> {
+        let params = DlParams::from_named_group(NamedGroup::Ffdhe2048, b"seed")?;
+        let prover = DlProver::new(params.clone())?;
+        let verifier = DlVerifier::new(params.clone())?;
+        let session = |user: &[u8]| {
+            let mut transcript = Transcript::new(b"login");
+            transcript.append_message(b"user", user);
+            transcript.append_message(b"auth_id", b"42");
+            transcript
+        };
+
+        let x = prover.random()?;
+        let ProverPublicKeys { y1, y2 } = prover.public_keys(&x)?;
+
+        // Not replayable for another user, nor under other parameters
+        let proof = prover.prove(&x, &session(b"alice"))?;
+        assert!(verifier.verify(&y1, &y2, &proof, &session(b"alice"))?);
+        assert!(!verifier.verify(&y1, &y2, &proof, &session(b"bob"))?);
+        let other = DlVerifier::new(DlParams::from_named_group(NamedGroup::Ffdhe2048, b"other")?)?;
+        assert!(!other.verify(&y1, &y2, &proof, &session(b"alice"))?);
+
+        // Interactive challenges are fresh each run, and the prover can re-derive them
+        let k = prover.random()?;
+        let ProverCommit { r1, r2 } = prover.commit(&k)?;
+        let nonce = verifier.challenge_nonce()?;
+        let c = verifier.challenge(&y1, &y2, &r1, &r2, &nonce, &session(b"alice"))?;
+        let other_nonce = verifier.challenge_nonce()?;
+        assert_ne!(
+            c,
+            verifier.challenge(&y1, &y2, &r1, &r2, &other_nonce, &session(b"alice"))?
+        );
+        let rederived = fiat_shamir::interactive_challenge(
+            &params,
+            &ProverPublicKeys { y1: &y1, y2: &y2 },
+            &ProverCommit { r1: &r1, r2: &r2 },
+            &nonce,
+            &session(b"alice"),
+        )?;
+        assert_eq!(c, rederived);
+        let ProverChallengeResponse { s } = prover.challenge_response(&k, &c, &x)?;
+        assert!(verifier.check(&y1, &y2, &r1, &r2, &c, &s)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_dl_protocol_over_standard_groups() -> Result<(), CryptoError> {
+        let mut precomputed = DlParams::from_named_group(NamedGroup::Ffdhe2048, b"seed")?;
+        precomputed.precompute()?;
+
+        for params in [
+            DlParams::from_named_group(NamedGroup::Ffdhe2048, b"seed")?,
+            DlParams::from_named_group(NamedGroup::Modp3072, b"seed")?,
+            DlParams::schnorr(2048, 256)?,
+            precomputed,
+        ] {
+            let prover = DlProver::new(params.clone())?;
+            let verifier = DlVerifier::new(params.clone())?;
+
+            let x = prover.random()?;
+            let ProverPublicKeys { y1, y2 } = prover.public_keys(&x)?;
+            let k = prover.random()?;
+            let ProverCommit { r1, r2 } = prover.commit(&k)?;
+            let c = verifier.random()?;
+            let ProverChallengeResponse { s } = prover.challenge_response(&k, &c, &x)?;
+
+            let valid = verifier.check(&y1, &y2, &r1, &r2, &c, &s)?;
+            assert!(
+                valid,
+                "Chaum-Pedersen verification failed over a large group"
+            );
+        }
+
+        Ok(())
+    }
+
+    // (y1, y2, r1, r2, c, s) of one honest run
+    type Run = (BigNum, BigNum, BigNum, BigNum, BigNum, BigNum);
+
+    fn transcripts(runs: &[Run]) -> Vec<ProofTranscript<'_, BigNum, BigNum>> {
+        runs.iter()
+            .map(|(y1, y2, r1, r2, c, s)| ProofTranscript {
+                y1,
+                y2,
+                r1,
+                r2,
+                c,
+                s,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_dl_check_batch() -> Result<(), CryptoError> {
+        let params = DlParams::new()?;
+        let prover = DlProver::new(params.clone())?;
+        let verifier = DlVerifier::new(params.clone())?;
+
+        let mut runs = Vec::new();
+        for _ in 0..8 {
+            let x = prover.random()?;
+            let ProverPublicKeys { y1, y2 } = prover.public_keys(&x)?;
+            let k = prover.random()?;
+            let ProverCommit { r1, r2 } = prover.commit(&k)?;
+            let c = verifier.random()?;
+            let ProverChallengeResponse { s } = prover.challenge_response(&k, &c, &x)?;
+            runs.push((y1, y2, r1, r2, c, s));
+        }
+
+        let verdicts = verifier.check_batch(&transcripts(&runs))?;
+        assert_eq!(verdicts, vec![true; 8], "Honest batch failed verification");
+
+        // Tamper with s of run 2 and c of run 5
+        runs[2].5.add_word(1)?;
+        runs[5].4.add_word(1)?;
+
+        let verdicts = verifier.check_batch(&transcripts(&runs))?;
+        assert_eq!(
+            verdicts,
+            vec![true, true, false, true, true, false, true, true],
+            "Batch verification did not isolate the tampered runs"
+        );
+        assert!(verifier.check_batch(&[])?.is_empty());
+
+        Ok(())
+    }
 }
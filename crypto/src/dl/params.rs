@@ -1,5 +1,26 @@
-use openssl::bn::{BigNum, BigNumContext};
+use std::sync::Arc;
+
+use openssl::bn::{BigNum, BigNumContext, BigNumContextRef, BigNumRef, MsbOption};
 use openssl::error::ErrorStack;
+use openssl::rand::rand_bytes;
+
+use super::groups::NamedGroup;
+use super::precompute::DlTables;
+
+use crate::context::with_bn_ctx;
+use crate::error::{CryptoError, ParamsError};
+use crate::group::Group;
+use crate::multiexp::mod_exp2;
+use crate::util::{
+    check_scalar, decode_mod, encode_mod, hash_to_range, invert_mod, mod_add, mod_mul, mod_sub,
+    reduce_mod,
+};
+
+const HASH_TO_SUBGROUP_DOMAIN: &[u8] = b"zkp_auth/dl/hash-to-subgroup/v1";
+
+// Miller-Rabin rounds for externally supplied p and q, 2^-128 error on adversarial input
+const PRIMALITY_CHECKS: i32 = 64;
+
 pub fn is_generator(
     g: &BigNum,
     q: &BigNum,
@@ -12,34 +33,73 @@ pub fn is_generator(
     Ok(exp == BigNum::from_u32(1)? && *g != BigNum::from_u32(1)?)
 }
 
+// (p - 1) / q, the exponent that maps Z_p^* onto the order-q subgroup
+fn cofactor(q: &BigNum, p: &BigNum, ctx: &mut BigNumContext) -> Result<BigNum, ErrorStack> {
+    let one = BigNum::from_u32(1)?;
+    let mut p_minus_one = BigNum::new()?;
+    p_minus_one.checked_sub(p, &one)?;
+    let mut cofactor = BigNum::new()?;
+    cofactor.checked_div(&p_minus_one, q, ctx)?;
+
+    Ok(cofactor)
+}
+
 fn find_generator(q: &BigNum, p: &BigNum, ctx: &mut BigNumContext) -> Result<BigNum, ErrorStack> {
+    let cofactor = cofactor(q, p, ctx)?;
     let mut x = BigNum::new()?;
     loop {
         p.rand_range(&mut x)?;
         let mut y = BigNum::new()?;
-        y.mod_exp(&x, &BigNum::from_u32(2).unwrap(), p, ctx)?;
+        y.mod_exp(&x, &cofactor, p, ctx)?;
         if is_generator(&y, q, p, ctx)? {
             return Ok(y);
         }
     }
 }
 
-impl Clone for DlParams {
-    fn clone(&self) -> Self {
-        DlParams {
-            q: self.q.to_owned().unwrap(),
-            p: self.p.to_owned().unwrap(),
-            g: self.g.to_owned().unwrap(),
-            h: self.h.to_owned().unwrap(),
+/// Maps `seed` to an element of the order-q subgroup of Z_p^* whose discrete log
+/// to any base is unknown: hash to [0, p), then clear the cofactor (p-1)/q.
+pub fn hash_to_subgroup(
+    q: &BigNum,
+    p: &BigNum,
+    seed: &[u8],
+    ctx: &mut BigNumContext,
+) -> Result<BigNum, ErrorStack> {
+    let one = BigNum::from_u32(1)?;
+    let cofactor = cofactor(q, p, ctx)?;
+
+    let (p_bytes, q_bytes) = (p.to_vec(), q.to_vec());
+    let mut counter: u32 = 0;
+    loop {
+        let u = hash_to_range(
+            HASH_TO_SUBGROUP_DOMAIN,
+            &[&p_bytes, &q_bytes, seed, &counter.to_be_bytes()],
+            p,
+            ctx,
+        )?;
+        let mut h = BigNum::new()?;
+        h.mod_exp(&u, &cofactor, p, ctx)?;
+        if h > one {
+            return Ok(h);
         }
+        counter += 1;
     }
 }
 
+type TableLookup =
+    fn(&DlTables, &BigNumRef, &mut BigNumContextRef) -> Result<Option<BigNum>, ErrorStack>;
+
+/// The numbers are shared behind `Arc`s, so cloning cannot fail.
+#[derive(Clone)]
 pub struct DlParams {
-    pub q: BigNum,
-    pub p: BigNum,
-    pub g: BigNum,
-    pub h: BigNum,
+    pub q: Arc<BigNum>,
+    pub p: Arc<BigNum>,
+    pub g: Arc<BigNum>,
+    pub h: Arc<BigNum>,
+    /// Seed `h` was derived from with `hash_to_subgroup`, if known.
+    pub seed: Option<Vec<u8>>,
+    /// Fixed-base tables for `g` and `h`, built by `precompute`.
+    pub tables: Option<Arc<DlTables>>,
 }
 
 impl DlParams {
@@ -53,34 +113,348 @@ impl DlParams {
 
         // Calculate q = (p-1)/2
         let mut q = BigNum::new()?;
-        q.checked_sub(&p, &BigNum::from_u32(1).unwrap())?;
+        q.checked_sub(&p, BigNum::from_u32(1)?.as_ref())?;
         q.div_word(2)?;
 
-        // Find generators
+        // Find generator g, and derive h from a fresh seed so nobody knows log_g(h)
         let g = find_generator(&q, &p, &mut ctx)?;
-        let mut h = find_generator(&q, &p, &mut ctx)?;
+        let mut seed = [0u8; 32];
+        rand_bytes(&mut seed)?;
+
+        DlParams::from_seed(q, p, g, &seed)
+    }
+
+    /// Schnorr group: a `p_bits` prime p = m * q + 1 with a `q_bits` prime-order subgroup.
+    pub fn schnorr(p_bits: i32, q_bits: i32) -> Result<DlParams, ErrorStack> {
+        let mut ctx = BigNumContext::new()?;
 
-        // Ensure h is different from g
-        while h == g {
-            h = find_generator(&q, &p, &mut ctx)?;
+        let mut q = BigNum::new()?;
+        q.generate_prime(q_bits, false, None, None)?;
+
+        // Search for an even multiplier m such that p = m * q + 1 is a prime of the right size
+        let mut p = BigNum::new()?;
+        loop {
+            let mut m = BigNum::new()?;
+            m.rand(p_bits - q_bits, MsbOption::ONE, false)?;
+            m.clear_bit(0)?;
+
+            p.checked_mul(&m, &q, &mut ctx)?;
+            p.add_word(1)?;
+            if p.num_bits() == p_bits && p.is_prime_fasttest(PRIMALITY_CHECKS, &mut ctx, true)? {
+                break;
+            }
         }
 
-        Ok(DlParams { p, q, g, h })
+        let g = find_generator(&q, &p, &mut ctx)?;
+        let mut seed = [0u8; 32];
+        rand_bytes(&mut seed)?;
+
+        DlParams::from_seed(q, p, g, &seed)
+    }
+
+    /// One of the standardised MODP / FFDHE groups, with h derived from `seed`.
+    pub fn from_named_group(group: NamedGroup, seed: &[u8]) -> Result<DlParams, ErrorStack> {
+        let p = group.prime()?;
+        let mut q = BigNum::new()?;
+        q.rshift1(&p)?;
+
+        DlParams::from_seed(q, p, group.generator()?, seed)
     }
 
     pub fn with_params(q: BigNum, p: BigNum, g: BigNum, h: BigNum) -> Result<DlParams, ErrorStack> {
-        Ok(DlParams { p, q, g, h })
+        Ok(DlParams {
+            p: Arc::new(p),
+            q: Arc::new(q),
+            g: Arc::new(g),
+            h: Arc::new(h),
+            seed: None,
+            tables: None,
+        })
+    }
+
+    /// Builds the params with `h` derived deterministically from `seed`.
+    pub fn from_seed(q: BigNum, p: BigNum, g: BigNum, seed: &[u8]) -> Result<DlParams, ErrorStack> {
+        let mut ctx = BigNumContext::new()?;
+        let h = hash_to_subgroup(&q, &p, seed, &mut ctx)?;
+
+        Ok(DlParams {
+            p: Arc::new(p),
+            q: Arc::new(q),
+            g: Arc::new(g),
+            h: Arc::new(h),
+            seed: Some(seed.to_vec()),
+            tables: None,
+        })
+    }
+
+    /// The subgroup order q.
+    pub fn order(&self) -> &BigNum {
+        &self.q
+    }
+
+    /// Builds the fixed-base tables for `g` and `h`. Clones share them.
+    pub fn precompute(&mut self) -> Result<(), ErrorStack> {
+        let mut ctx = BigNumContext::new()?;
+        let tables = DlTables::new(&self.q, &self.p, &self.g, &self.h, &mut ctx)?;
+        self.tables = Some(Arc::new(tables));
+
+        Ok(())
+    }
+
+    /// `g^e mod p`, from the table if there is one.
+    pub fn pow_g(&self, e: &BigNum, ctx: &mut BigNumContext) -> Result<BigNum, ErrorStack> {
+        self.pow(&self.g, DlTables::pow_g, e, ctx)
+    }
+
+    /// `h^e mod p`, from the table if there is one.
+    pub fn pow_h(&self, e: &BigNum, ctx: &mut BigNumContext) -> Result<BigNum, ErrorStack> {
+        self.pow(&self.h, DlTables::pow_h, e, ctx)
+    }
+
+    fn pow(
+        &self,
+        base: &BigNum,
+        from_table: TableLookup,
+        e: &BigNum,
+        ctx: &mut BigNumContext,
+    ) -> Result<BigNum, ErrorStack> {
+        if let Some(tables) = &self.tables {
+            if let Some(result) = from_table(tables, e, ctx)? {
+                return Ok(result);
+            }
+        }
+
+        let mut result = BigNum::new()?;
+        result.mod_exp(base, e, &self.p, ctx)?;
+        Ok(result)
+    }
+
+    /// Re-derives `h` from `seed` and checks it matches the one in use.
+    pub fn verify_generation(&self, seed: &[u8]) -> Result<bool, ErrorStack> {
+        let mut ctx = BigNumContext::new()?;
+        let h = hash_to_subgroup(&self.q, &self.p, seed, &mut ctx)?;
+
+        Ok(h == *self.h)
+    }
+
+    /// Checks that p and q are prime, q divides p - 1, and g and h are distinct
+    /// non-identity elements of the order-q subgroup.
+    pub fn validate(&self) -> Result<(), ParamsError> {
+        let mut ctx = BigNumContext::new()?;
+
+        if !self.p.is_prime(PRIMALITY_CHECKS, &mut ctx)? {
+            return Err(ParamsError::NotPrime("p"));
+        }
+        if !self.q.is_prime(PRIMALITY_CHECKS, &mut ctx)? {
+            return Err(ParamsError::NotPrime("q"));
+        }
+
+        let one = BigNum::from_u32(1)?;
+        let mut p_minus_one = BigNum::new()?;
+        p_minus_one.checked_sub(&self.p, &one)?;
+        let mut rem = BigNum::new()?;
+        rem.nnmod(&p_minus_one, &self.q, &mut ctx)?;
+        if rem.num_bits() != 0 {
+            return Err(ParamsError::OrderDoesNotDivide);
+        }
+
+        self.check_element("g", &self.g)?;
+        self.check_element("h", &self.h)?;
+
+        if self.g == self.h {
+            return Err(ParamsError::SameGenerators);
+        }
+
+        Ok(())
+    }
+
+    /// Checks that `element` is in the order-q subgroup and is not the identity.
+    pub fn check_element(&self, name: &'static str, element: &BigNum) -> Result<(), ParamsError> {
+        if element.is_negative() || element.num_bits() == 0 || *element >= *self.p {
+            return Err(ParamsError::OutOfRange(name));
+        }
+        if *element == BigNum::from_u32(1)? {
+            return Err(ParamsError::Identity(name));
+        }
+        if !with_bn_ctx(|ctx| is_generator(element, &self.q, &self.p, ctx))? {
+            return Err(ParamsError::WrongOrder(name));
+        }
+
+        Ok(())
+    }
+}
+
+// acc = acc * a1^p1 * a2^p2 mod p
+fn mul_pow2(
+    acc: &mut BigNum,
+    (a1, p1): (&BigNum, &BigNum),
+    (a2, p2): (&BigNum, &BigNum),
+    p: &BigNum,
+    ctx: &mut BigNumContext,
+) -> Result<(), ErrorStack> {
+    let pow = mod_exp2(a1, p1, a2, p2, p, ctx)?;
+    *acc = mod_mul(acc, &pow, p, ctx)?;
+
+    Ok(())
+}
+
+impl Group for DlParams {
+    type Element = BigNum;
+    type Scalar = BigNum;
+
+    fn g(&self) -> &BigNum {
+        &self.g
+    }
+
+    fn h(&self) -> &BigNum {
+        &self.h
+    }
+
+    fn identity(&self) -> Result<BigNum, CryptoError> {
+        Ok(BigNum::from_u32(1)?)
+    }
+
+    fn exp(&self, base: &BigNum, e: &BigNum) -> Result<BigNum, CryptoError> {
+        let mut result = BigNum::new()?;
+        with_bn_ctx(|ctx| result.mod_exp(base, e, &self.p, ctx))?;
+        Ok(result)
+    }
+
+    // Pairs of bases share their squarings in one simultaneous exponentiation
+    fn multi_exp(&self, bases: &[&BigNum], exponents: &[&BigNum]) -> Result<BigNum, CryptoError> {
+        let mut acc = self.identity()?;
+        for (bases, exponents) in bases.chunks(2).zip(exponents.chunks(2)) {
+            match (bases, exponents) {
+                ([a1, a2], [p1, p2]) => {
+                    with_bn_ctx(|ctx| mul_pow2(&mut acc, (a1, p1), (a2, p2), &self.p, ctx))?
+                }
+                _ => {
+                    let pow = self.exp(bases[0], exponents[0])?;
+                    acc = with_bn_ctx(|ctx| mod_mul(&acc, &pow, &self.p, ctx))?;
+                }
+            }
+        }
+
+        Ok(acc)
+    }
+
+    fn element_eq(&self, a: &BigNum, b: &BigNum) -> Result<bool, CryptoError> {
+        Ok(a == b)
+    }
+
+    fn encode_element(&self, element: &BigNum) -> Result<Vec<u8>, CryptoError> {
+        Ok(element.to_vec())
+    }
+
+    // Any bytes are a number; check_element decides whether it is an element
+    fn decode_element(&self, data: &[u8]) -> Result<BigNum, CryptoError> {
+        Ok(BigNum::from_slice(data)?)
+    }
+
+    fn check_element(&self, name: &'static str, element: &BigNum) -> Result<(), ParamsError> {
+        DlParams::check_element(self, name, element)
+    }
+
+    fn description(&self) -> Result<Vec<Vec<u8>>, CryptoError> {
+        Ok(vec![
+            self.p.to_vec(),
+            self.q.to_vec(),
+            self.g.to_vec(),
+            self.h.to_vec(),
+        ])
+    }
+
+    fn scalar_len(&self) -> usize {
+        self.q.num_bytes() as usize
+    }
+
+    fn reduce_scalar(&self, bytes: &[u8]) -> Result<BigNum, CryptoError> {
+        reduce_mod(bytes, &self.q)
+    }
+
+    fn encode_scalar(&self, scalar: &BigNum) -> Result<Vec<u8>, CryptoError> {
+        encode_mod(scalar, &self.q)
+    }
+
+    fn decode_scalar(&self, data: &[u8]) -> Result<BigNum, CryptoError> {
+        decode_mod(data, &self.q)
+    }
+
+    fn scalar_add(&self, a: &BigNum, b: &BigNum) -> Result<BigNum, CryptoError> {
+        Ok(with_bn_ctx(|ctx| mod_add(a, b, &self.q, ctx))?)
+    }
+
+    fn scalar_sub(&self, a: &BigNum, b: &BigNum) -> Result<BigNum, CryptoError> {
+        Ok(with_bn_ctx(|ctx| mod_sub(a, b, &self.q, ctx))?)
+    }
+
+    fn scalar_mul(&self, a: &BigNum, b: &BigNum) -> Result<BigNum, CryptoError> {
+        Ok(with_bn_ctx(|ctx| mod_mul(a, b, &self.q, ctx))?)
+    }
+
+    fn scalar_invert(&self, a: &BigNum) -> Result<Option<BigNum>, CryptoError> {
+        invert_mod(a, &self.q)
+    }
+
+    // A BigNum can hold any integer, not only the ones decode_scalar lets through
+    fn check_scalar(&self, name: &'static str, value: &BigNum) -> Result<(), ParamsError> {
+        check_scalar(name, value, &self.q)
+    }
+
+    fn exp_g(&self, e: &BigNum) -> Result<BigNum, CryptoError> {
+        Ok(with_bn_ctx(|ctx| self.pow_g(e, ctx))?)
+    }
+
+    fn exp_h(&self, e: &BigNum) -> Result<BigNum, CryptoError> {
+        Ok(with_bn_ctx(|ctx| self.pow_h(e, ctx))?)
+    }
+
+    // The fixed-base tables don't help here: y^m needs the full run of squarings,
+    // which the joint exponentiation shares with g^n for free.
+    fn exp_g_mul(&self, n: &BigNum, y: &BigNum, m: &BigNum) -> Result<BigNum, CryptoError> {
+        Ok(with_bn_ctx(|ctx| mod_exp2(&self.g, n, y, m, &self.p, ctx))?)
+    }
+
+    fn exp_h_mul(&self, n: &BigNum, y: &BigNum, m: &BigNum) -> Result<BigNum, CryptoError> {
+        Ok(with_bn_ctx(|ctx| mod_exp2(&self.h, n, y, m, &self.p, ctx))?)
+    }
+
+    // With tables, g and h are cheaper on their own than jointly
+    fn multi_exp_gh(
+        &self,
+        a: &BigNum,
+        b: &BigNum,
+        bases: &[&BigNum],
+        exponents: &[&BigNum],
+    ) -> Result<BigNum, CryptoError> {
+        if self.tables.is_none() {
+            let bases: Vec<&BigNum> = [&*self.g, &*self.h]
+                .into_iter()
+                .chain(bases.iter().copied())
+                .collect();
+            let exponents: Vec<_> = [a, b]
+                .into_iter()
+                .chain(exponents.iter().copied())
+                .collect();
+            return self.multi_exp(&bases, &exponents);
+        }
+
+        let rest = self.multi_exp(bases, exponents)?;
+        let gh =
+            with_bn_ctx(|ctx| mod_mul(&self.pow_g(a, ctx)?, &self.pow_h(b, ctx)?, &self.p, ctx))?;
+        Ok(with_bn_ctx(|ctx| mod_mul(&rest, &gh, &self.p, ctx))?)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::CryptoError;
 
     #[test]
-    fn test_new_params() -> Result<(), ErrorStack> {
+    fn test_new_params() -> Result<(), CryptoError> {
         let params = DlParams::new()?;
-        let DlParams { p, q, g, h } = params;
+        let DlParams { p, q, g, h, .. } = params.clone();
         let mut ctx = BigNumContext::new()?;
 
         // Test 1: Verify q is prime
@@ -107,6 +481,10 @@ mod tests {
         // Test 6: Verify q has the correct bit length
         assert!(q.num_bits() == 256, "q does not have 256 bits");
 
+        // Test 7: Verify h was derived from the recorded seed
+        let seed = params.seed.as_deref().expect("h should be seed-derived");
+        assert!(params.verify_generation(seed)?, "h does not match its seed");
+
         // let values = [
         //     ("h", &h),
         //     ("g", &g),
@@ -125,7 +503,7 @@ mod tests {
     }
 
     #[test]
-    fn test_is_generator() -> Result<(), ErrorStack> {
+    fn test_is_generator() -> Result<(), CryptoError> {
         let mut ctx = BigNumContext::new()?;
 
         // Create a small prime q and corresponding p = 2q + 1
@@ -178,4 +556,185 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_hash_to_subgroup() -> Result<(), CryptoError> {
+        let params = DlParams::new()?;
+        let mut ctx = BigNumContext::new()?;
+
+        let h1 = hash_to_subgroup(&params.q, &params.p, b"seed", &mut ctx)?;
+        let h2 = hash_to_subgroup(&params.q, &params.p, b"seed", &mut ctx)?;
+        let h3 = hash_to_subgroup(&params.q, &params.p, b"other seed", &mut ctx)?;
+
+        assert!(h1 == h2, "hash_to_subgroup is not deterministic");
+        assert!(h1 != h3, "different seeds gave the same element");
+        assert!(
+            is_generator(&h1, &params.q, &params.p, &mut ctx)?,
+            "derived element is not of order q"
+        );
+        assert!(
+            !params.verify_generation(b"other seed")?,
+            "verify_generation accepted the wrong seed"
+        );
+
+        Ok(())
+    }
+
+    fn small_params(g: u32, h: u32) -> Result<DlParams, ErrorStack> {
+        // q = 11, p = 2q + 1 = 23
+        DlParams::with_params(
+            BigNum::from_u32(11)?,
+            BigNum::from_u32(23)?,
+            BigNum::from_u32(g)?,
+            BigNum::from_u32(h)?,
+        )
+    }
+
+    #[test]
+    fn test_validate() -> Result<(), CryptoError> {
+        assert!(DlParams::new()?.validate().is_ok());
+        assert!(small_params(2, 3)?.validate().is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_group() -> Result<(), CryptoError> {
+        let mut params = small_params(2, 3)?;
+        params.p = Arc::new(BigNum::from_u32(25)?);
+        assert!(matches!(params.validate(), Err(ParamsError::NotPrime("p"))));
+
+        let mut params = small_params(2, 3)?;
+        params.q = Arc::new(BigNum::from_u32(9)?);
+        assert!(matches!(params.validate(), Err(ParamsError::NotPrime("q"))));
+
+        let mut params = small_params(2, 3)?;
+        params.q = Arc::new(BigNum::from_u32(7)?);
+        assert!(matches!(
+            params.validate(),
+            Err(ParamsError::OrderDoesNotDivide)
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate_rejects_bad_generators() -> Result<(), CryptoError> {
+        assert!(matches!(
+            small_params(0, 3)?.validate(),
+            Err(ParamsError::OutOfRange("g"))
+        ));
+        assert!(matches!(
+            small_params(2, 23)?.validate(),
+            Err(ParamsError::OutOfRange("h"))
+        ));
+        assert!(matches!(
+            small_params(1, 3)?.validate(),
+            Err(ParamsError::Identity("g"))
+        ));
+        // 22 = p - 1 has order 2, 5 has order 22
+        assert!(matches!(
+            small_params(22, 3)?.validate(),
+            Err(ParamsError::WrongOrder("g"))
+        ));
+        assert!(matches!(
+            small_params(2, 5)?.validate(),
+            Err(ParamsError::WrongOrder("h"))
+        ));
+        assert!(matches!(
+            small_params(2, 2)?.validate(),
+            Err(ParamsError::SameGenerators)
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_element() -> Result<(), CryptoError> {
+        let params = small_params(2, 3)?;
+        let check = |value: u32| params.check_element("y1", &BigNum::from_u32(value).unwrap());
+
+        assert!(check(4).is_ok());
+        assert!(matches!(check(0), Err(ParamsError::OutOfRange("y1"))));
+        assert!(matches!(check(23), Err(ParamsError::OutOfRange("y1"))));
+        assert!(matches!(check(1), Err(ParamsError::Identity("y1"))));
+        // p - 1 has order 2
+        assert!(matches!(check(22), Err(ParamsError::WrongOrder("y1"))));
+
+        let check = |value: u32| params.check_scalar("c", &BigNum::from_u32(value).unwrap());
+        assert!(check(10).is_ok());
+        assert!(matches!(check(0), Err(ParamsError::ScalarOutOfRange("c"))));
+        assert!(matches!(check(11), Err(ParamsError::ScalarOutOfRange("c"))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_precompute() -> Result<(), CryptoError> {
+        let mut ctx = BigNumContext::new()?;
+        let plain = DlParams::from_named_group(NamedGroup::Ffdhe2048, b"seed")?;
+        let mut params = plain.clone();
+        params.precompute()?;
+
+        let one = BigNum::from_u32(1)?;
+        let mut q_minus_one = BigNum::new()?;
+        q_minus_one.checked_sub(&params.q, &one)?;
+        let mut random = BigNum::new()?;
+        params.q.rand_range(&mut random)?;
+        let mut secret = random.to_owned()?;
+        secret.set_const_time();
+        let mut too_large = BigNum::new()?;
+        too_large.lshift1(&params.q)?;
+
+        for e in [BigNum::new()?, q_minus_one, random, secret, too_large] {
+            assert_eq!(params.pow_g(&e, &mut ctx)?, plain.pow_g(&e, &mut ctx)?);
+            assert_eq!(params.pow_h(&e, &mut ctx)?, plain.pow_h(&e, &mut ctx)?);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_schnorr_params() -> Result<(), CryptoError> {
+        let params = DlParams::schnorr(1024, 256)?;
+
+        assert!(params.p.num_bits() == 1024, "p does not have 1024 bits");
+        assert!(params.q.num_bits() == 256, "q does not have 256 bits");
+        assert!(params.validate().is_ok(), "Schnorr group is invalid");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_named_groups() -> Result<(), CryptoError> {
+        for group in [
+            NamedGroup::Modp2048,
+            NamedGroup::Modp3072,
+            NamedGroup::Modp4096,
+            NamedGroup::Ffdhe2048,
+            NamedGroup::Ffdhe3072,
+            NamedGroup::Ffdhe4096,
+        ] {
+            let params = DlParams::from_named_group(group, b"seed")?;
+            let mut ctx = BigNumContext::new()?;
+
+            assert!(
+                params.p.num_bits() == params.q.num_bits() + 1,
+                "{} is not a safe-prime group",
+                group.name()
+            );
+            assert!(
+                is_generator(&params.g, &params.q, &params.p, &mut ctx)?,
+                "{} generator does not have order q",
+                group.name()
+            );
+            assert!(
+                is_generator(&params.h, &params.q, &params.p, &mut ctx)?,
+                "{} h does not have order q",
+                group.name()
+            );
+        }
+
+        Ok(())
+    }
 }
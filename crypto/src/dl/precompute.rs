@@ -0,0 +1,302 @@
+//! Fixed-base window tables for `g` and `h`.
+//!
+//! For a base `b`, window `i` holds `b^(j * 2^(4i))` for every 4-bit digit `j`,
+//! so `b^e` is one Montgomery multiplication per digit of `e` and no squarings.
+//! A table for a 2048-bit group takes about 2 MiB.
+
+use std::os::raw::c_int;
+
+use foreign_types::{ForeignType, ForeignTypeRef};
+use openssl::bn::{BigNum, BigNumContextRef, BigNumRef};
+use openssl::error::ErrorStack;
+use openssl_sys::{BIGNUM, BN_CTX, BN_MONT_CTX};
+
+const WINDOW_BITS: i32 = 4;
+const WINDOW_SIZE: usize = 1 << WINDOW_BITS;
+
+extern "C" {
+    fn BN_MONT_CTX_new() -> *mut BN_MONT_CTX;
+    fn BN_MONT_CTX_free(mont: *mut BN_MONT_CTX);
+    fn BN_MONT_CTX_set(mont: *mut BN_MONT_CTX, m: *const BIGNUM, ctx: *mut BN_CTX) -> c_int;
+    fn BN_to_montgomery(
+        r: *mut BIGNUM,
+        a: *const BIGNUM,
+        mont: *mut BN_MONT_CTX,
+        ctx: *mut BN_CTX,
+    ) -> c_int;
+    fn BN_from_montgomery(
+        r: *mut BIGNUM,
+        a: *const BIGNUM,
+        mont: *mut BN_MONT_CTX,
+        ctx: *mut BN_CTX,
+    ) -> c_int;
+    fn BN_mod_mul_montgomery(
+        r: *mut BIGNUM,
+        a: *const BIGNUM,
+        b: *const BIGNUM,
+        mont: *mut BN_MONT_CTX,
+        ctx: *mut BN_CTX,
+    ) -> c_int;
+}
+
+fn cvt(result: c_int) -> Result<(), ErrorStack> {
+    if result <= 0 {
+        Err(ErrorStack::get())
+    } else {
+        Ok(())
+    }
+}
+
+// Montgomery context for the modulus p. The pointer is owned: it is never
+// copied out, and `Drop` frees it.
+struct Montgomery(*mut BN_MONT_CTX);
+
+// SAFETY: the context is owned and holds no thread-local state, so it can be
+// freed on any thread.
+unsafe impl Send for Montgomery {}
+
+// SAFETY: `BN_MONT_CTX_set` in `new` is the only call that writes to the
+// context, and it runs before the `Montgomery` is returned. After that, only
+// `to_mont`, `to_plain` and `mul` use it, through `BN_to_montgomery`,
+// `BN_from_montgomery` and `BN_mod_mul_montgomery`. Those take a non-const
+// pointer but only read `N`, `RR` and `n0` from it; their scratch space comes
+// from the caller's `BN_CTX`. OpenSSL's RSA code relies on the same property
+// to share one cached context between threads. Any method that has to write
+// to the context must take `&mut self`.
+unsafe impl Sync for Montgomery {}
+
+impl Montgomery {
+    fn new(p: &BigNumRef, ctx: &mut BigNumContextRef) -> Result<Montgomery, ErrorStack> {
+        unsafe {
+            let mont = BN_MONT_CTX_new();
+            if mont.is_null() {
+                return Err(ErrorStack::get());
+            }
+            let mont = Montgomery(mont);
+            cvt(BN_MONT_CTX_set(mont.0, p.as_ptr(), ctx.as_ptr()))?;
+
+            Ok(mont)
+        }
+    }
+
+    fn to_mont(&self, a: &BigNumRef, ctx: &mut BigNumContextRef) -> Result<BigNum, ErrorStack> {
+        let r = BigNum::new()?;
+        unsafe {
+            cvt(BN_to_montgomery(
+                r.as_ptr(),
+                a.as_ptr(),
+                self.0,
+                ctx.as_ptr(),
+            ))?
+        };
+        Ok(r)
+    }
+
+    fn to_plain(&self, a: &BigNumRef, ctx: &mut BigNumContextRef) -> Result<BigNum, ErrorStack> {
+        let r = BigNum::new()?;
+        unsafe {
+            cvt(BN_from_montgomery(
+                r.as_ptr(),
+                a.as_ptr(),
+                self.0,
+                ctx.as_ptr(),
+            ))?
+        };
+        Ok(r)
+    }
+
+    fn mul(
+        &self,
+        a: &BigNumRef,
+        b: &BigNumRef,
+        ctx: &mut BigNumContextRef,
+    ) -> Result<BigNum, ErrorStack> {
+        let r = BigNum::new()?;
+        unsafe {
+            cvt(BN_mod_mul_montgomery(
+                r.as_ptr(),
+                a.as_ptr(),
+                b.as_ptr(),
+                self.0,
+                ctx.as_ptr(),
+            ))?
+        };
+        Ok(r)
+    }
+}
+
+impl Drop for Montgomery {
+    fn drop(&mut self) {
+        unsafe { BN_MONT_CTX_free(self.0) }
+    }
+}
+
+// Window table of one base. Entries are stored as big-endian u64 words of
+// their Montgomery form so a secret digit can be looked up by a full scan.
+struct FixedBaseTable {
+    windows: usize,
+    words: usize,
+    entries: Vec<u64>,
+}
+
+impl FixedBaseTable {
+    fn new(
+        base: &BigNumRef,
+        exponent_bits: i32,
+        p: &BigNumRef,
+        mont: &Montgomery,
+        ctx: &mut BigNumContextRef,
+    ) -> Result<FixedBaseTable, ErrorStack> {
+        let windows = ((exponent_bits + WINDOW_BITS - 1) / WINDOW_BITS) as usize;
+        let words = (p.num_bytes() as usize).div_ceil(8);
+        let mut entries = Vec::with_capacity(windows * WINDOW_SIZE * words);
+
+        let one = BigNum::from_u32(1)?;
+        let one = mont.to_mont(&one, ctx)?;
+        // base^(2^(4i)) in Montgomery form
+        let mut window_base = mont.to_mont(base, ctx)?;
+        for _ in 0..windows {
+            let mut entry = one.to_owned()?;
+            for _ in 0..WINDOW_SIZE {
+                let bytes = entry.to_vec_padded(words as i32 * 8)?;
+                entries.extend(
+                    bytes
+                        .chunks_exact(8)
+                        .map(|word| u64::from_be_bytes(word.try_into().unwrap())),
+                );
+                entry = mont.mul(&entry, &window_base, ctx)?;
+            }
+            // entry is now window_base^16
+            window_base = entry;
+        }
+
+        Ok(FixedBaseTable {
+            windows,
+            words,
+            entries,
+        })
+    }
+
+    fn entry(&self, window: usize, digit: usize) -> &[u64] {
+        let start = (window * WINDOW_SIZE + digit) * self.words;
+        &self.entries[start..start + self.words]
+    }
+
+    // Reads every entry of the window and keeps the one for `digit` with a
+    // mask, so the memory access pattern does not depend on it
+    fn select_const_time(&self, window: usize, digit: usize, out: &mut [u64]) {
+        out.fill(0);
+        for candidate in 0..WINDOW_SIZE {
+            let mask = ((candidate ^ digit) as u64).wrapping_sub(1) >> 63;
+            let mask = mask.wrapping_neg();
+            for (o, w) in out.iter_mut().zip(self.entry(window, candidate)) {
+                *o |= w & mask;
+            }
+        }
+    }
+
+    fn pow(
+        &self,
+        e: &BigNumRef,
+        mont: &Montgomery,
+        ctx: &mut BigNumContextRef,
+    ) -> Result<BigNum, ErrorStack> {
+        let secret = e.is_const_time();
+        let mut selected = vec![0u64; self.words];
+        let mut bytes = vec![0u8; self.words * 8];
+
+        let mut acc: Option<BigNum> = None;
+        for window in 0..self.windows {
+            let mut digit = 0;
+            for bit in 0..WINDOW_BITS {
+                let set = e.is_bit_set(window as i32 * WINDOW_BITS + bit) as usize;
+                digit |= set << bit;
+            }
+
+            let entry = if secret {
+                self.select_const_time(window, digit, &mut selected);
+                &selected[..]
+            } else {
+                self.entry(window, digit)
+            };
+            for (chunk, word) in bytes.chunks_exact_mut(8).zip(entry) {
+                chunk.copy_from_slice(&word.to_be_bytes());
+            }
+            let mut entry = BigNum::from_slice(&bytes)?;
+            if secret {
+                entry.set_const_time();
+            }
+
+            acc = Some(match acc {
+                None => entry,
+                Some(acc) => mont.mul(&acc, &entry, ctx)?,
+            });
+        }
+
+        match acc {
+            Some(acc) => mont.to_plain(&acc, ctx),
+            None => BigNum::from_u32(1),
+        }
+    }
+}
+
+/// Precomputed tables for `g` and `h`, for exponents below `2^|q|`.
+pub struct DlTables {
+    exponent_bits: i32,
+    mont: Montgomery,
+    g: FixedBaseTable,
+    h: FixedBaseTable,
+}
+
+impl DlTables {
+    pub fn new(
+        q: &BigNumRef,
+        p: &BigNumRef,
+        g: &BigNumRef,
+        h: &BigNumRef,
+        ctx: &mut BigNumContextRef,
+    ) -> Result<DlTables, ErrorStack> {
+        let exponent_bits = q.num_bits();
+        let mont = Montgomery::new(p, ctx)?;
+        let g = FixedBaseTable::new(g, exponent_bits, p, &mont, ctx)?;
+        let h = FixedBaseTable::new(h, exponent_bits, p, &mont, ctx)?;
+
+        Ok(DlTables {
+            exponent_bits,
+            mont,
+            g,
+            h,
+        })
+    }
+
+    /// `g^e mod p`, or `None` if `e` is too large or negative for the table.
+    pub fn pow_g(
+        &self,
+        e: &BigNumRef,
+        ctx: &mut BigNumContextRef,
+    ) -> Result<Option<BigNum>, ErrorStack> {
+        self.pow(&self.g, e, ctx)
+    }
+
+    /// `h^e mod p`, or `None` if `e` is too large or negative for the table.
+    pub fn pow_h(
+        &self,
+        e: &BigNumRef,
+        ctx: &mut BigNumContextRef,
+    ) -> Result<Option<BigNum>, ErrorStack> {
+        self.pow(&self.h, e, ctx)
+    }
+
+    fn pow(
+        &self,
+        table: &FixedBaseTable,
+        e: &BigNumRef,
+        ctx: &mut BigNumContextRef,
+    ) -> Result<Option<BigNum>, ErrorStack> {
+        if e.is_negative() || e.num_bits() > self.exponent_bits {
+            return Ok(None);
+        }
+
+        table.pow(e, &self.mont, ctx).map(Some)
+    }
+}
@@ -0,0 +1,52 @@
+use openssl::nid::Nid;
+
+/// Curves the EC variant of the protocol can run on. All of them have
+/// cofactor 1, so the standard generator spans the whole group.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum NamedCurve {
+    /// SEC 2 secp256k1
+    Secp256k1,
+    /// NIST P-256 (secp256r1 / prime256v1)
+    P256,
+    /// NIST P-384 (secp384r1)
+    P384,
+    /// NIST P-521 (secp521r1)
+    P521,
+}
+
+impl NamedCurve {
+    pub const ALL: [NamedCurve; 4] = [
+        NamedCurve::Secp256k1,
+        NamedCurve::P256,
+        NamedCurve::P384,
+        NamedCurve::P521,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            NamedCurve::Secp256k1 => "secp256k1",
+            NamedCurve::P256 => "p256",
+            NamedCurve::P384 => "p384",
+            NamedCurve::P521 => "p521",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<NamedCurve> {
+        NamedCurve::ALL
+            .into_iter()
+            .find(|curve| curve.name() == name)
+    }
+
+    pub fn nid(&self) -> Nid {
+        match self {
+            NamedCurve::Secp256k1 => Nid::SECP256K1,
+            NamedCurve::P256 => Nid::X9_62_PRIME256V1,
+            NamedCurve::P384 => Nid::SECP384R1,
+            NamedCurve::P521 => Nid::SECP521R1,
+        }
+    }
+
+    pub fn from_nid(nid: Nid) -> Option<NamedCurve> {
+        NamedCurve::ALL.into_iter().find(|curve| curve.nid() == nid)
+    }
+}
@@ -1,10 +1,14 @@
+pub mod curves;
 pub mod params;
+pub mod precompute;
 pub mod prover;
 pub mod verifier;
 
 #[cfg(test)]
 mod tests {
+    use crate::error::CryptoError;
     use openssl::{
+        bn::BigNum,
         ec::{EcGroup, EcPoint},
         error::ErrorStack,
         nid::Nid,
@@ -14,11 +18,12 @@ mod tests {
         context::with_bn_ctx,
         ec::{params::EcParams, prover::EcProver, verifier::EcVerifier},
         prover::{Prover, ProverChallengeResponse, ProverCommit, ProverPublicKeys},
-        verifier::Verifier,
+        transcript::Transcript,
+        verifier::{ProofTranscript, Verifier},
     };
 
     #[test]
-    fn test_ec_chaum_pedersen_protocol() -> Result<(), ErrorStack> {
+    fn test_ec_chaum_pedersen_protocol() -> Result<(), CryptoError> {
         let params = EcParams::new(Nid::SECP256K1)?;
         let prover = EcProver::new(params.clone())?;
         let verifier = EcVerifier::new(params.clone())?;
@@ -74,13 +79,13 @@ mod tests {
     fn tamper_point(point: &EcPoint, group: &EcGroup) -> Result<EcPoint, ErrorStack> {
         with_bn_ctx(|ctx| {
             let mut tampered = EcPoint::new(group)?;
-            tampered.add(group, point, group.generator(), ctx)?;
+            tampered.add(group, point, group.generator_opt().unwrap(), ctx)?;
             Ok(tampered)
         })
     }
 
     #[test]
-    fn test_ec_incorrect_prover_secret() -> Result<(), ErrorStack> {
+    fn test_ec_incorrect_prover_secret() -> Result<(), CryptoError> {
         let params = EcParams::new(Nid::SECP256K1)?;
         let prover = EcProver::new(params.clone())?;
         let verifier = EcVerifier::new(params.clone())?;
@@ -102,7 +107,7 @@ mod tests {
     }
 
     #[test]
-    fn test_ec_tampered_public_keys_y1() -> Result<(), ErrorStack> {
+    fn test_ec_tampered_public_keys_y1() -> Result<(), CryptoError> {
         let params = EcParams::new(Nid::SECP256K1)?;
         let prover = EcProver::new(params.clone())?;
         let verifier = EcVerifier::new(params.clone())?;
@@ -127,7 +132,7 @@ mod tests {
     }
 
     #[test]
-    fn test_ec_tampered_public_keys_y2() -> Result<(), ErrorStack> {
+    fn test_ec_tampered_public_keys_y2() -> Result<(), CryptoError> {
         let params = EcParams::new(Nid::SECP256K1)?;
         let prover = EcProver::new(params.clone())?;
         let verifier = EcVerifier::new(params.clone())?;
@@ -152,7 +157,7 @@ mod tests {
     }
 
     #[test]
-    fn test_ec_incorrect_commitment_r1() -> Result<(), ErrorStack> {
+    fn test_ec_incorrect_commitment_r1() -> Result<(), CryptoError> {
         let params = EcParams::new(Nid::SECP256K1)?;
         let prover = EcProver::new(params.clone())?;
         let verifier = EcVerifier::new(params.clone())?;
@@ -177,7 +182,7 @@ mod tests {
     }
 
     #[test]
-    fn test_ec_incorrect_commitment_r2() -> Result<(), ErrorStack> {
+    fn test_ec_incorrect_commitment_r2() -> Result<(), CryptoError> {
         let params = EcParams::new(Nid::SECP256K1)?;
         let prover = EcProver::new(params.clone())?;
         let verifier = EcVerifier::new(params.clone())?;
@@ -202,7 +207,7 @@ mod tests {
     }
 
     #[test]
-    fn test_ec_incorrect_challenge_response() -> Result<(), ErrorStack> {
+    fn test_ec_incorrect_challenge_response() -> Result<(), CryptoError> {
         let params = EcParams::new(Nid::SECP256K1)?;
         let prover = EcProver::new(params.clone())?;
         let verifier = EcVerifier::new(params.clone())?;
@@ -227,7 +232,7 @@ mod tests {
     }
 
     #[test]
-    fn test_ec_mismatched_parameters() -> Result<(), ErrorStack> {
+    fn test_ec_mismatched_parameters() -> Result<(), CryptoError> {
         let params1 = EcParams::new(Nid::SECP256K1)?;
         let params2 = EcParams::new(Nid::SECP256K1)?;
         let prover = EcProver::new(params1.clone())?;
@@ -248,4 +253,110 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_ec_non_interactive_proof() -> Result<(), CryptoError> {
+        let params = EcParams::new(Nid::SECP256K1)?;
+        let prover = EcProver::new(params.clone())?;
+        let verifier = EcVerifier::new(params.clone())?;
+
+        let x = prover.random()?;
+        let ProverPublicKeys { y1, y2 } = prover.public_keys(&x)?;
+        let proof = prover.prove(&x, &Transcript::new(b"batch-job-42"))?;
+
+        let valid = verifier.verify(&y1, &y2, &proof, &Transcript::new(b"batch-job-42"))?;
+        assert!(valid, "Non-interactive proof verification failed");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ec_non_interactive_proof_wrong_context() -> Result<(), CryptoError> {
+        let params = EcParams::new(Nid::SECP256K1)?;
+        let prover = EcProver::new(params.clone())?;
+        let verifier = EcVerifier::new(params.clone())?;
+
+        let x = prover.random()?;
+        let ProverPublicKeys { y1, y2 } = prover.public_keys(&x)?;
+        let proof = prover.prove(&x, &Transcript::new(b"batch-job-42"))?;
+
+        let valid = verifier.verify(&y1, &y2, &proof, &Transcript::new(b"batch-job-43"))?;
+        assert!(!valid, "Verification should fail with a different context");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ec_non_interactive_proof_tampered_public_key() -> Result<(), CryptoError> {
+        let params = EcParams::new(Nid::SECP256K1)?;
+        let prover = EcProver::new(params.clone())?;
+        let verifier = EcVerifier::new(params.clone())?;
+
+        let x = prover.random()?;
+        let ProverPublicKeys { y1, y2 } = prover.public_keys(&x)?;
+        let proof = prover.prove(&x, &Transcript::new(b"batch-job-42"))?;
+
+        // Tamper with y1
+        let tampered_y1 = tamper_point(&y1, &params.group)?;
+
+        let valid =
+            verifier.verify(&tampered_y1, &y2, &proof, &Transcript::new(b"batch-job-42"))?;
+        assert!(
+            !valid,
+            "Verification should fail with tampered public key y1"
+        );
+
+        Ok(())
+    }
+
+    // (y1, y2, r1, r2, c, s) of one honest run
+    type Run = (EcPoint, EcPoint, EcPoint, EcPoint, BigNum, BigNum);
+
+    fn transcripts(runs: &[Run]) -> Vec<ProofTranscript<'_, EcPoint, BigNum>> {
+        runs.iter()
+            .map(|(y1, y2, r1, r2, c, s)| ProofTranscript {
+                y1,
+                y2,
+                r1,
+                r2,
+                c,
+                s,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ec_check_batch() -> Result<(), CryptoError> {
+        let params = EcParams::new(Nid::SECP256K1)?;
+        let prover = EcProver::new(params.clone())?;
+        let verifier = EcVerifier::new(params.clone())?;
+
+        let mut runs = Vec::new();
+        for _ in 0..8 {
+            let x = prover.random()?;
+            let ProverPublicKeys { y1, y2 } = prover.public_keys(&x)?;
+            let k = prover.random()?;
+            let ProverCommit { r1, r2 } = prover.commit(&k)?;
+            let c = verifier.random()?;
+            let ProverChallengeResponse { s } = prover.challenge_response(&k, &c, &x)?;
+            runs.push((y1, y2, r1, r2, c, s));
+        }
+
+        let verdicts = verifier.check_batch(&transcripts(&runs))?;
+        assert_eq!(verdicts, vec![true; 8], "Honest batch failed verification");
+
+        // Tamper with s of run 2 and c of run 5
+        runs[2].5.add_word(1)?;
+        runs[5].4.add_word(1)?;
+
+        let verdicts = verifier.check_batch(&transcripts(&runs))?;
+        assert_eq!(
+            verdicts,
+            vec![true, true, false, true, true, false, true, true],
+            "Batch verification did not isolate the tampered runs"
+        );
+        assert!(verifier.check_batch(&[])?.is_empty());
+
+        Ok(())
+    }
 }
@@ -1,7 +1,26 @@
-use openssl::bn::{BigNum, BigNumContext};
-use openssl::ec::{EcGroup, EcPoint};
+use std::sync::Arc;
+
+use openssl::bn::{BigNum, BigNumContext, BigNumRef};
+use openssl::ec::{EcGroup, EcPoint, EcPointRef, PointConversionForm};
 use openssl::error::ErrorStack;
 use openssl::nid::Nid;
+use openssl::rand::rand_bytes;
+
+use crate::context::with_bn_ctx;
+use crate::ec::curves::NamedCurve;
+use crate::ec::precompute::EcTables;
+use crate::error::{CryptoError, ParamsError};
+use crate::group::Group;
+use crate::multiexp::ec_mul_sum;
+use crate::util::{
+    check_scalar, decode_mod, encode_mod, hash_to_range, invert_mod, mod_add, mod_mul, mod_sub,
+    reduce_mod,
+};
+
+const HASH_TO_CURVE_DOMAIN: &[u8] = b"zkp_auth/ec/hash-to-curve/v1";
+
+// Miller-Rabin rounds for the subgroup order, 2^-128 error on adversarial input
+const PRIMALITY_CHECKS: i32 = 64;
 
 fn find_generator(
     group: &EcGroup,
@@ -11,29 +30,61 @@ fn find_generator(
     let mut point = EcPoint::new(group)?;
     let mut rand = BigNum::new()?;
     order.rand_range(&mut rand)?;
-    point.mul_generator(group, &rand, ctx)?;
+    point.mul_generator2(group, &rand, ctx)?;
 
     Ok(point)
 }
 
-impl Clone for EcParams {
-    fn clone(&self) -> Self {
-        let group = EcGroup::from_curve_name(self.group.curve_name().unwrap()).unwrap();
+/// Maps `seed` to a point of the prime-order subgroup whose discrete log to any
+/// base is unknown, by try-and-increment on the x-coordinate.
+pub fn hash_to_curve(
+    group: &EcGroup,
+    seed: &[u8],
+    ctx: &mut BigNumContext,
+) -> Result<EcPoint, ErrorStack> {
+    let (mut field, mut a, mut b) = (BigNum::new()?, BigNum::new()?, BigNum::new()?);
+    group.components_gfp(&mut field, &mut a, &mut b, ctx)?;
+    let mut cofactor = BigNum::new()?;
+    group.cofactor(&mut cofactor, ctx)?;
+
+    let curve = group.curve_name().map_or(0, |nid| nid.as_raw());
+    let field_len = field.num_bytes();
+    let mut counter: u32 = 0;
+    loop {
+        let x = hash_to_range(
+            HASH_TO_CURVE_DOMAIN,
+            &[&curve.to_be_bytes(), seed, &counter.to_be_bytes()],
+            &field,
+            ctx,
+        )?;
+        counter += 1;
 
-        EcParams {
-            group,
-            g: self.g.to_owned(&self.group).unwrap(),
-            h: self.h.to_owned(&self.group).unwrap(),
-            order: self.order.to_owned().unwrap(),
+        // Not every x is on the curve; decoding fails for those and we try the next one
+        let mut encoded = vec![0x02];
+        encoded.extend(x.to_vec_padded(field_len)?);
+        let Ok(candidate) = EcPoint::from_bytes(group, &encoded, ctx) else {
+            continue;
+        };
+
+        let mut point = EcPoint::new(group)?;
+        point.mul2(group, &candidate, &cofactor, ctx)?;
+        if !point.is_infinity(group) {
+            return Ok(point);
         }
     }
 }
 
+/// The curve, points and order are shared behind `Arc`s, so cloning cannot fail.
+#[derive(Clone)]
 pub struct EcParams {
-    pub group: EcGroup,
-    pub g: EcPoint,
-    pub h: EcPoint,
-    pub order: BigNum,
+    pub group: Arc<EcGroup>,
+    pub g: Arc<EcPoint>,
+    pub h: Arc<EcPoint>,
+    pub order: Arc<BigNum>,
+    /// Seed `h` was derived from with `hash_to_curve`, if known.
+    pub seed: Option<Vec<u8>>,
+    /// Fixed-base tables for `g` and `h`, built by `precompute`.
+    pub tables: Option<Arc<EcTables>>,
 }
 
 impl EcParams {
@@ -44,16 +95,12 @@ impl EcParams {
         let mut order = BigNum::new()?;
         group.order(&mut order, &mut ctx)?;
 
-        // Find generators g and h
+        // Find generator g, and derive h from a fresh seed so nobody knows log_g(h)
         let g = find_generator(&group, &order, &mut ctx)?;
-        let mut h = find_generator(&group, &order, &mut ctx)?;
+        let mut seed = [0u8; 32];
+        rand_bytes(&mut seed)?;
 
-        // Ensure h is different from g
-        while h.eq(&group, &g, &mut ctx)? {
-            h = find_generator(&group, &order, &mut ctx)?;
-        }
-
-        Ok(EcParams { group, g, h, order })
+        EcParams::from_seed(nid, g, &seed)
     }
 
     pub fn with_params(curve_nid: Nid, g: EcPoint, h: EcPoint) -> Result<EcParams, ErrorStack> {
@@ -63,6 +110,420 @@ impl EcParams {
         let mut order = BigNum::new()?;
         group.order(&mut order, &mut ctx)?;
 
-        Ok(EcParams { group, g, h, order })
+        Ok(EcParams {
+            group: Arc::new(group),
+            g: Arc::new(g),
+            h: Arc::new(h),
+            order: Arc::new(order),
+            seed: None,
+            tables: None,
+        })
+    }
+
+    /// Builds the params with `h` derived deterministically from `seed`.
+    pub fn from_seed(curve_nid: Nid, g: EcPoint, seed: &[u8]) -> Result<EcParams, ErrorStack> {
+        let mut ctx = BigNumContext::new()?;
+        let group = EcGroup::from_curve_name(curve_nid)?;
+        let h = hash_to_curve(&group, seed, &mut ctx)?;
+
+        let mut params = EcParams::with_params(curve_nid, g, h)?;
+        params.seed = Some(seed.to_vec());
+
+        Ok(params)
+    }
+
+    /// Builds the params for a named curve: `g` is the standard generator and
+    /// `h` is derived from `seed`.
+    pub fn from_named_curve(curve: NamedCurve, seed: &[u8]) -> Result<EcParams, ErrorStack> {
+        let group = EcGroup::from_curve_name(curve.nid())?;
+        // Named curves always come with a generator
+        let g = group
+            .generator_opt()
+            .ok_or_else(ErrorStack::get)?
+            .to_owned(&group)?;
+
+        EcParams::from_seed(curve.nid(), g, seed)
+    }
+
+    /// The named curve these params live on, if it is one we support.
+    pub fn curve(&self) -> Option<NamedCurve> {
+        self.group.curve_name().and_then(NamedCurve::from_nid)
+    }
+
+    /// The order of the prime-order subgroup.
+    pub fn order(&self) -> &BigNum {
+        &self.order
+    }
+
+    /// Builds the fixed-base tables for `g` and `h`. Clones share them.
+    pub fn precompute(&mut self) -> Result<(), ErrorStack> {
+        let mut ctx = BigNumContext::new()?;
+        let tables = EcTables::new(&self.group, &self.g, &self.h, &mut ctx)?;
+        self.tables = Some(Arc::new(tables));
+
+        Ok(())
+    }
+
+    /// `n * g`, from the table if there is one.
+    pub fn mul_g(&self, n: &BigNum, ctx: &mut BigNumContext) -> Result<EcPoint, ErrorStack> {
+        match &self.tables {
+            Some(tables) => tables.mul_g(n, ctx),
+            None => self.mul(&self.g, n, ctx),
+        }
+    }
+
+    /// `n * h`, from the table if there is one.
+    pub fn mul_h(&self, n: &BigNum, ctx: &mut BigNumContext) -> Result<EcPoint, ErrorStack> {
+        match &self.tables {
+            Some(tables) => tables.mul_h(n, ctx),
+            None => self.mul(&self.h, n, ctx),
+        }
+    }
+
+    /// `n * g + m * point` as one joint multiplication.
+    pub fn mul_g_add(
+        &self,
+        n: &BigNum,
+        point: &EcPointRef,
+        m: &BigNum,
+        ctx: &mut BigNumContext,
+    ) -> Result<EcPoint, ErrorStack> {
+        match &self.tables {
+            Some(tables) => tables.mul_g_add(n, point, m, ctx),
+            None => ec_mul_sum(&self.group, &[(&self.g, n), (point, m)], ctx),
+        }
+    }
+
+    /// `n * h + m * point` as one joint multiplication.
+    pub fn mul_h_add(
+        &self,
+        n: &BigNum,
+        point: &EcPointRef,
+        m: &BigNum,
+        ctx: &mut BigNumContext,
+    ) -> Result<EcPoint, ErrorStack> {
+        match &self.tables {
+            Some(tables) => tables.mul_h_add(n, point, m, ctx),
+            None => ec_mul_sum(&self.group, &[(&self.h, n), (point, m)], ctx),
+        }
+    }
+
+    fn mul(
+        &self,
+        point: &EcPointRef,
+        n: &BigNum,
+        ctx: &mut BigNumContext,
+    ) -> Result<EcPoint, ErrorStack> {
+        let mut result = EcPoint::new(&self.group)?;
+        result.mul2(&self.group, point, n, ctx)?;
+        Ok(result)
+    }
+
+    /// Re-derives `h` from `seed` and checks it matches the one in use.
+    pub fn verify_generation(&self, seed: &[u8]) -> Result<bool, ErrorStack> {
+        let mut ctx = BigNumContext::new()?;
+        let h = hash_to_curve(&self.group, seed, &mut ctx)?;
+
+        h.eq(&self.group, &self.h, &mut ctx)
+    }
+
+    /// Checks that g and h are distinct points on the curve, not the identity,
+    /// and in the prime-order subgroup.
+    pub fn validate(&self) -> Result<(), ParamsError> {
+        let mut ctx = BigNumContext::new()?;
+
+        if !self.order.is_prime(PRIMALITY_CHECKS, &mut ctx)? {
+            return Err(ParamsError::NotPrime("order"));
+        }
+
+        self.check_element("g", &self.g)?;
+        self.check_element("h", &self.h)?;
+
+        if self.g.eq(&self.group, &self.h, &mut ctx)? {
+            return Err(ParamsError::SameGenerators);
+        }
+
+        Ok(())
+    }
+
+    /// Checks that `point` is on the curve, in the prime-order subgroup and is
+    /// not the point at infinity.
+    pub fn check_element(&self, name: &'static str, point: &EcPointRef) -> Result<(), ParamsError> {
+        if point.is_infinity(&self.group) {
+            return Err(ParamsError::Identity(name));
+        }
+
+        with_bn_ctx(|ctx| {
+            if !point.is_on_curve(&self.group, ctx)? {
+                return Err(ParamsError::NotOnCurve(name));
+            }
+            let mut check = EcPoint::new(&self.group)?;
+            check.mul2(&self.group, point, &self.order, ctx)?;
+            if !check.is_infinity(&self.group) {
+                return Err(ParamsError::WrongOrder(name));
+            }
+
+            Ok(())
+        })
+    }
+}
+
+impl Group for EcParams {
+    type Element = EcPoint;
+    type Scalar = BigNum;
+
+    fn g(&self) -> &EcPoint {
+        &self.g
+    }
+
+    fn h(&self) -> &EcPoint {
+        &self.h
+    }
+
+    fn identity(&self) -> Result<EcPoint, CryptoError> {
+        Ok(EcPoint::new(&self.group)?)
+    }
+
+    fn exp(&self, base: &EcPoint, e: &BigNum) -> Result<EcPoint, CryptoError> {
+        Ok(with_bn_ctx(|ctx| self.mul(base, e, ctx))?)
+    }
+
+    fn multi_exp(&self, bases: &[&EcPoint], exponents: &[&BigNum]) -> Result<EcPoint, CryptoError> {
+        let terms: Vec<(&EcPointRef, &BigNumRef)> = bases
+            .iter()
+            .zip(exponents)
+            .map(|(point, n)| (&***point, &***n))
+            .collect();
+
+        Ok(with_bn_ctx(|ctx| ec_mul_sum(&self.group, &terms, ctx))?)
+    }
+
+    fn element_eq(&self, a: &EcPoint, b: &EcPoint) -> Result<bool, CryptoError> {
+        Ok(with_bn_ctx(|ctx| a.eq(&self.group, b, ctx))?)
+    }
+
+    fn encode_element(&self, element: &EcPoint) -> Result<Vec<u8>, CryptoError> {
+        Ok(with_bn_ctx(|ctx| {
+            element.to_bytes(&self.group, PointConversionForm::COMPRESSED, ctx)
+        })?)
+    }
+
+    fn decode_element(&self, data: &[u8]) -> Result<EcPoint, CryptoError> {
+        with_bn_ctx(|ctx| EcPoint::from_bytes(&self.group, data, ctx))
+            .map_err(|_| CryptoError::InvalidEncoding)
+    }
+
+    fn check_element(&self, name: &'static str, element: &EcPoint) -> Result<(), ParamsError> {
+        EcParams::check_element(self, name, element)
+    }
+
+    fn description(&self) -> Result<Vec<Vec<u8>>, CryptoError> {
+        let curve = self.group.curve_name().map_or(0, |nid| nid.as_raw());
+
+        Ok(vec![
+            curve.to_be_bytes().to_vec(),
+            self.encode_element(&self.g)?,
+            self.encode_element(&self.h)?,
+        ])
+    }
+
+    fn scalar_len(&self) -> usize {
+        self.order.num_bytes() as usize
+    }
+
+    fn reduce_scalar(&self, bytes: &[u8]) -> Result<BigNum, CryptoError> {
+        reduce_mod(bytes, &self.order)
+    }
+
+    fn encode_scalar(&self, scalar: &BigNum) -> Result<Vec<u8>, CryptoError> {
+        encode_mod(scalar, &self.order)
+    }
+
+    fn decode_scalar(&self, data: &[u8]) -> Result<BigNum, CryptoError> {
+        decode_mod(data, &self.order)
+    }
+
+    fn scalar_add(&self, a: &BigNum, b: &BigNum) -> Result<BigNum, CryptoError> {
+        Ok(with_bn_ctx(|ctx| mod_add(a, b, &self.order, ctx))?)
+    }
+
+    fn scalar_sub(&self, a: &BigNum, b: &BigNum) -> Result<BigNum, CryptoError> {
+        Ok(with_bn_ctx(|ctx| mod_sub(a, b, &self.order, ctx))?)
+    }
+
+    fn scalar_mul(&self, a: &BigNum, b: &BigNum) -> Result<BigNum, CryptoError> {
+        Ok(with_bn_ctx(|ctx| mod_mul(a, b, &self.order, ctx))?)
+    }
+
+    fn scalar_invert(&self, a: &BigNum) -> Result<Option<BigNum>, CryptoError> {
+        invert_mod(a, &self.order)
+    }
+
+    // A BigNum can hold any integer, not only the ones decode_scalar lets through
+    fn check_scalar(&self, name: &'static str, value: &BigNum) -> Result<(), ParamsError> {
+        check_scalar(name, value, &self.order)
+    }
+
+    fn exp_g(&self, e: &BigNum) -> Result<EcPoint, CryptoError> {
+        Ok(with_bn_ctx(|ctx| self.mul_g(e, ctx))?)
+    }
+
+    fn exp_h(&self, e: &BigNum) -> Result<EcPoint, CryptoError> {
+        Ok(with_bn_ctx(|ctx| self.mul_h(e, ctx))?)
+    }
+
+    fn exp_g_mul(&self, n: &BigNum, y: &EcPoint, m: &BigNum) -> Result<EcPoint, CryptoError> {
+        Ok(with_bn_ctx(|ctx| self.mul_g_add(n, y, m, ctx))?)
+    }
+
+    fn exp_h_mul(&self, n: &BigNum, y: &EcPoint, m: &BigNum) -> Result<EcPoint, CryptoError> {
+        Ok(with_bn_ctx(|ctx| self.mul_h_add(n, y, m, ctx))?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::CryptoError;
+
+    #[test]
+    fn test_new_params() -> Result<(), CryptoError> {
+        let params = EcParams::new(Nid::SECP256K1)?;
+
+        let seed = params.seed.as_deref().expect("h should be seed-derived");
+        assert!(params.verify_generation(seed)?, "h does not match its seed");
+        assert!(
+            !params.verify_generation(b"other seed")?,
+            "verify_generation accepted the wrong seed"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hash_to_curve() -> Result<(), CryptoError> {
+        let mut ctx = BigNumContext::new()?;
+
+        for nid in [Nid::SECP256K1, Nid::X9_62_PRIME256V1, Nid::SECP384R1] {
+            let group = EcGroup::from_curve_name(nid)?;
+            let h1 = hash_to_curve(&group, b"seed", &mut ctx)?;
+            let h2 = hash_to_curve(&group, b"seed", &mut ctx)?;
+            let h3 = hash_to_curve(&group, b"other seed", &mut ctx)?;
+
+            assert!(
+                h1.eq(&group, &h2, &mut ctx)?,
+                "hash_to_curve is not deterministic"
+            );
+            assert!(
+                !h1.eq(&group, &h3, &mut ctx)?,
+                "different seeds gave the same point"
+            );
+            assert!(h1.is_on_curve(&group, &mut ctx)?, "point is not on curve");
+            assert!(!h1.is_infinity(&group), "point is the identity");
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_named_curves() -> Result<(), ParamsError> {
+        for curve in NamedCurve::ALL {
+            let params = EcParams::from_named_curve(curve, b"seed")?;
+            params.validate()?;
+            assert_eq!(params.curve(), Some(curve));
+            assert!(
+                params.verify_generation(b"seed")?,
+                "h does not match its seed"
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_precompute() -> Result<(), CryptoError> {
+        let mut ctx = BigNumContext::new()?;
+
+        for curve in NamedCurve::ALL {
+            let plain = EcParams::from_named_curve(curve, b"seed")?;
+            let mut params = plain.clone();
+            params.precompute()?;
+            let group = &params.group;
+
+            let mut n = BigNum::new()?;
+            params.order.rand_range(&mut n)?;
+            let mut m = BigNum::new()?;
+            params.order.rand_range(&mut m)?;
+            let y = plain.mul_h(&m, &mut ctx)?;
+
+            let pairs = [
+                (params.mul_g(&n, &mut ctx)?, plain.mul_g(&n, &mut ctx)?),
+                (params.mul_h(&n, &mut ctx)?, plain.mul_h(&n, &mut ctx)?),
+                (
+                    params.mul_g_add(&n, &y, &m, &mut ctx)?,
+                    plain.mul_g_add(&n, &y, &m, &mut ctx)?,
+                ),
+                (
+                    params.mul_h_add(&n, &y, &m, &mut ctx)?,
+                    plain.mul_h_add(&n, &y, &m, &mut ctx)?,
+                ),
+            ];
+            for (with_table, without) in pairs {
+                assert!(
+                    with_table.eq(group, &without, &mut ctx)?,
+                    "table result differs on {}",
+                    curve.name()
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_element() -> Result<(), ParamsError> {
+        let params = EcParams::from_named_curve(NamedCurve::P256, b"seed")?;
+
+        params.check_element("y1", &params.h)?;
+        let identity = EcPoint::new(&params.group)?;
+        assert!(matches!(
+            params.check_element("y1", &identity),
+            Err(ParamsError::Identity("y1"))
+        ));
+
+        assert!(matches!(
+            params.check_scalar("c", &BigNum::new()?),
+            Err(ParamsError::ScalarOutOfRange("c"))
+        ));
+        assert!(matches!(
+            params.check_scalar("c", &params.order),
+            Err(ParamsError::ScalarOutOfRange("c"))
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_validate() -> Result<(), CryptoError> {
+        let params = EcParams::new(Nid::SECP256K1)?;
+        assert!(params.validate().is_ok());
+
+        let identity = EcPoint::new(&params.group)?;
+        let h = EcPointRef::to_owned(&params.h, &params.group)?;
+        let invalid = EcParams::with_params(Nid::SECP256K1, identity, h)?;
+        assert!(matches!(
+            invalid.validate(),
+            Err(ParamsError::Identity("g"))
+        ));
+
+        let g = EcPointRef::to_owned(&params.g, &params.group)?;
+        let h = EcPointRef::to_owned(&params.g, &params.group)?;
+        let invalid = EcParams::with_params(Nid::SECP256K1, g, h)?;
+        assert!(matches!(
+            invalid.validate(),
+            Err(ParamsError::SameGenerators)
+        ));
+
+        Ok(())
     }
 }
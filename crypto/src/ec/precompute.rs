@@ -0,0 +1,112 @@
+//! Fixed-base tables for `g` and `h`, using OpenSSL's own generator
+//! precomputation: each base gets a copy of the curve with that base set as
+//! its generator.
+//!
+//! Single multiplications by a base keep OpenSSL's constant-time code paths.
+//! Where the curve has an optimised implementation (P-256) these use the
+//! table directly; on the generic curves the table speeds up the base term
+//! of `g^s * y^c`.
+
+use std::os::raw::c_int;
+
+use foreign_types::ForeignTypeRef;
+use openssl::bn::{BigNumContextRef, BigNumRef};
+use openssl::ec::{EcGroup, EcGroupRef, EcPoint, EcPointRef};
+use openssl::error::ErrorStack;
+use openssl_sys::{BN_CTX, EC_GROUP};
+
+use crate::multiexp::with_generator;
+
+extern "C" {
+    fn EC_GROUP_precompute_mult(group: *mut EC_GROUP, ctx: *mut BN_CTX) -> c_int;
+}
+
+// The curve of `group` with `base` as its generator, with precomputed multiples
+fn group_with_generator(
+    group: &EcGroupRef,
+    base: &EcPointRef,
+    ctx: &mut BigNumContextRef,
+) -> Result<EcGroup, ErrorStack> {
+    let table = with_generator(group, base, ctx)?;
+    unsafe {
+        if EC_GROUP_precompute_mult(table.as_ptr(), ctx.as_ptr()) <= 0 {
+            return Err(ErrorStack::get());
+        }
+    }
+
+    Ok(table)
+}
+
+/// Precomputed tables for `g` and `h`.
+pub struct EcTables {
+    g: EcGroup,
+    h: EcGroup,
+}
+
+impl EcTables {
+    pub fn new(
+        group: &EcGroupRef,
+        g: &EcPointRef,
+        h: &EcPointRef,
+        ctx: &mut BigNumContextRef,
+    ) -> Result<EcTables, ErrorStack> {
+        Ok(EcTables {
+            g: group_with_generator(group, g, ctx)?,
+            h: group_with_generator(group, h, ctx)?,
+        })
+    }
+
+    /// `n * g`
+    pub fn mul_g(&self, n: &BigNumRef, ctx: &mut BigNumContextRef) -> Result<EcPoint, ErrorStack> {
+        mul_generator(&self.g, n, ctx)
+    }
+
+    /// `n * h`
+    pub fn mul_h(&self, n: &BigNumRef, ctx: &mut BigNumContextRef) -> Result<EcPoint, ErrorStack> {
+        mul_generator(&self.h, n, ctx)
+    }
+
+    /// `n * g + m * point`
+    pub fn mul_g_add(
+        &self,
+        n: &BigNumRef,
+        point: &EcPointRef,
+        m: &BigNumRef,
+        ctx: &mut BigNumContextRef,
+    ) -> Result<EcPoint, ErrorStack> {
+        mul_full(&self.g, n, point, m, ctx)
+    }
+
+    /// `n * h + m * point`
+    pub fn mul_h_add(
+        &self,
+        n: &BigNumRef,
+        point: &EcPointRef,
+        m: &BigNumRef,
+        ctx: &mut BigNumContextRef,
+    ) -> Result<EcPoint, ErrorStack> {
+        mul_full(&self.h, n, point, m, ctx)
+    }
+}
+
+fn mul_generator(
+    table: &EcGroupRef,
+    n: &BigNumRef,
+    ctx: &mut BigNumContextRef,
+) -> Result<EcPoint, ErrorStack> {
+    let mut result = EcPoint::new(table)?;
+    result.mul_generator2(table, n, ctx)?;
+    Ok(result)
+}
+
+fn mul_full(
+    table: &EcGroupRef,
+    n: &BigNumRef,
+    point: &EcPointRef,
+    m: &BigNumRef,
+    ctx: &mut BigNumContextRef,
+) -> Result<EcPoint, ErrorStack> {
+    let mut result = EcPoint::new(table)?;
+    result.mul_full(table, n, point, m, ctx)?;
+    Ok(result)
+}
@@ -0,0 +1,415 @@
+use std::fmt;
+
+#[cfg(feature = "openssl")]
+use openssl::error::ErrorStack;
+
+/// Reason a set of group parameters, or an element received for them, was
+/// rejected.
+#[derive(Debug)]
+pub enum ParamsError {
+    /// The named modulus or order is not prime.
+    NotPrime(&'static str),
+    /// The subgroup order q does not divide p - 1.
+    OrderDoesNotDivide,
+    /// The named element is not a residue in `[1, p)`.
+    OutOfRange(&'static str),
+    /// The named element is the group identity.
+    Identity(&'static str),
+    /// The named element does not have order q.
+    WrongOrder(&'static str),
+    /// The named point does not lie on the curve.
+    NotOnCurve(&'static str),
+    /// The named scalar is not in `[1, q)`.
+    ScalarOutOfRange(&'static str),
+    /// g and h are the same element.
+    SameGenerators,
+    /// The underlying OpenSSL call failed.
+    #[cfg(feature = "openssl")]
+    Backend(ErrorStack),
+}
+
+impl fmt::Display for ParamsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamsError::NotPrime(name) => write!(f, "{} is not prime", name),
+            ParamsError::OrderDoesNotDivide => write!(f, "q does not divide p - 1"),
+            ParamsError::OutOfRange(name) => write!(f, "{} is not in the range [1, p)", name),
+            ParamsError::Identity(name) => write!(f, "{} is the identity element", name),
+            ParamsError::WrongOrder(name) => write!(f, "{} does not have order q", name),
+            ParamsError::NotOnCurve(name) => write!(f, "{} is not on the curve", name),
+            ParamsError::ScalarOutOfRange(name) => {
+                write!(f, "{} is not in the range [1, q)", name)
+            }
+            ParamsError::SameGenerators => write!(f, "g and h must be different"),
+            #[cfg(feature = "openssl")]
+            ParamsError::Backend(err) => write!(f, "backend error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for ParamsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            #[cfg(feature = "openssl")]
+            ParamsError::Backend(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "openssl")]
+impl From<ErrorStack> for ParamsError {
+    fn from(err: ErrorStack) -> Self {
+        ParamsError::Backend(err)
+    }
+}
+
+/// Error from the crypto crate's protocol APIs, separating what the caller
+/// sent from what went wrong locally.
+#[derive(Debug)]
+pub enum CryptoError {
+    /// Bytes that do not encode a group element.
+    InvalidEncoding,
+    /// A well-formed input that must be rejected, such as the identity as a
+    /// public key or a challenge outside `[1, q)`.
+    InvalidInput(ParamsError),
+    /// The group parameters failed validation.
+    InvalidParams(ParamsError),
+    /// OpenSSL's random number generator failed.
+    #[cfg(feature = "openssl")]
+    Rng(ErrorStack),
+    /// The operating system's entropy source failed.
+    Entropy(getrandom::Error),
+    /// Any other OpenSSL call failed.
+    #[cfg(feature = "openssl")]
+    Backend(ErrorStack),
+}
+
+impl fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CryptoError::InvalidEncoding => write!(f, "invalid encoding"),
+            CryptoError::InvalidInput(err) => write!(f, "{}", err),
+            CryptoError::InvalidParams(err) => write!(f, "invalid parameters: {}", err),
+            #[cfg(feature = "openssl")]
+            CryptoError::Rng(err) => write!(f, "random number generator failed: {}", err),
+            CryptoError::Entropy(err) => write!(f, "entropy source failed: {}", err),
+            #[cfg(feature = "openssl")]
+            CryptoError::Backend(err) => write!(f, "backend error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for CryptoError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CryptoError::InvalidEncoding => None,
+            CryptoError::InvalidInput(err) | CryptoError::InvalidParams(err) => Some(err),
+            #[cfg(feature = "openssl")]
+            CryptoError::Rng(err) | CryptoError::Backend(err) => Some(err),
+            CryptoError::Entropy(err) => Some(err),
+        }
+    }
+}
+
+#[cfg(feature = "openssl")]
+impl From<ErrorStack> for CryptoError {
+    fn from(err: ErrorStack) -> Self {
+        CryptoError::Backend(err)
+    }
+}
+
+/// Element and scalar checks are about the input; the rest are about the
+/// parameters themselves.
+impl From<ParamsError> for CryptoError {
+    fn from(err: ParamsError) -> Self {
+        match err {
+            #[cfg(feature = "openssl")]
+            ParamsError::Backend(err) => CryptoError::Backend(err),
+            ParamsError::NotPrime(_)
+            | ParamsError::OrderDoesNotDivide
+            | ParamsError::SameGenerators => CryptoError::InvalidParams(err),
+            err => CryptoError::InvalidInput(err),
+        }
+    }
+}
+
+/// Reason deriving a secret from a password failed.
+#[derive(Debug)]
+pub enum PasswordError {
+    /// Argon2 rejected the cost parameters or the salt.
+    Kdf(argon2::Error),
+    /// The derived secret reduced to zero; astronomically unlikely.
+    ZeroSecret,
+    /// A lower-level failure, such as the RNG or OpenSSL.
+    Crypto(CryptoError),
+}
+
+impl fmt::Display for PasswordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PasswordError::Kdf(err) => write!(f, "password hashing failed: {}", err),
+            PasswordError::ZeroSecret => write!(f, "derived secret is zero"),
+            PasswordError::Crypto(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for PasswordError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PasswordError::Crypto(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<argon2::Error> for PasswordError {
+    fn from(err: argon2::Error) -> Self {
+        PasswordError::Kdf(err)
+    }
+}
+
+impl From<CryptoError> for PasswordError {
+    fn from(err: CryptoError) -> Self {
+        PasswordError::Crypto(err)
+    }
+}
+
+#[cfg(feature = "openssl")]
+impl From<ErrorStack> for PasswordError {
+    fn from(err: ErrorStack) -> Self {
+        PasswordError::Crypto(CryptoError::Backend(err))
+    }
+}
+
+/// Reason `x` could not be extracted from a pair of transcripts.
+#[derive(Debug)]
+pub enum ExtractError {
+    /// The transcripts are for different public keys.
+    DifferentKeys,
+    /// The transcripts start from different commitments.
+    DifferentCommitments,
+    /// Both transcripts answer the same challenge, so they reveal nothing.
+    SameChallenge,
+    /// At least one transcript does not verify.
+    NotAccepting,
+    /// A lower-level failure, such as the RNG or OpenSSL.
+    Crypto(CryptoError),
+}
+
+impl fmt::Display for ExtractError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExtractError::DifferentKeys => write!(f, "transcripts are for different public keys"),
+            ExtractError::DifferentCommitments => {
+                write!(f, "transcripts have different commitments")
+            }
+            ExtractError::SameChallenge => write!(f, "transcripts have the same challenge"),
+            ExtractError::NotAccepting => write!(f, "a transcript does not verify"),
+            ExtractError::Crypto(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for ExtractError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ExtractError::Crypto(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<CryptoError> for ExtractError {
+    fn from(err: CryptoError) -> Self {
+        ExtractError::Crypto(err)
+    }
+}
+
+#[cfg(feature = "openssl")]
+impl From<ErrorStack> for ExtractError {
+    fn from(err: ErrorStack) -> Self {
+        ExtractError::Crypto(CryptoError::Backend(err))
+    }
+}
+
+/// Reason a sigma-protocol proof could not be made or read.
+#[derive(Debug)]
+pub enum SigmaError {
+    /// The witness does not have one value per secret scalar of the relation.
+    WitnessLength,
+    /// The witness does not satisfy the equation with this index.
+    Unsatisfied(usize),
+    /// The proof has the wrong number of responses, or a value out of range.
+    MalformedProof,
+    /// A lower-level failure, such as the RNG or OpenSSL.
+    Crypto(CryptoError),
+}
+
+impl fmt::Display for SigmaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SigmaError::WitnessLength => write!(f, "witness has the wrong number of scalars"),
+            SigmaError::Unsatisfied(index) => {
+                write!(f, "witness does not satisfy equation {}", index)
+            }
+            SigmaError::MalformedProof => write!(f, "malformed proof"),
+            SigmaError::Crypto(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for SigmaError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SigmaError::Crypto(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<CryptoError> for SigmaError {
+    fn from(err: CryptoError) -> Self {
+        SigmaError::Crypto(err)
+    }
+}
+
+#[cfg(feature = "openssl")]
+impl From<ErrorStack> for SigmaError {
+    fn from(err: ErrorStack) -> Self {
+        SigmaError::Crypto(CryptoError::Backend(err))
+    }
+}
+
+/// Reason a membership proof could not be made.
+#[derive(Debug)]
+pub enum MembershipError {
+    /// The secret does not belong to the key at the given index.
+    NotAMember,
+    /// A lower-level failure, such as the RNG or OpenSSL.
+    Crypto(CryptoError),
+}
+
+impl fmt::Display for MembershipError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MembershipError::NotAMember => write!(f, "secret does not match any key in the set"),
+            MembershipError::Crypto(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for MembershipError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            MembershipError::Crypto(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<CryptoError> for MembershipError {
+    fn from(err: CryptoError) -> Self {
+        MembershipError::Crypto(err)
+    }
+}
+
+#[cfg(feature = "openssl")]
+impl From<ErrorStack> for MembershipError {
+    fn from(err: ErrorStack) -> Self {
+        MembershipError::Crypto(CryptoError::Backend(err))
+    }
+}
+
+/// Reason an OPAQUE registration or login failed.
+#[derive(Debug)]
+pub enum OpaqueError {
+    /// A message has the wrong length or holds an invalid or identity element.
+    InvalidMessage,
+    /// The password hashed to the identity; astronomically unlikely.
+    InvalidInput,
+    /// No key could be derived from a seed; astronomically unlikely.
+    DeriveKeyPair,
+    /// The envelope did not open: wrong password, or a response or record
+    /// that was tampered with or belongs to somebody else.
+    EnvelopeRecovery,
+    /// The server's MAC did not verify, so it does not hold the record.
+    ServerAuthentication,
+    /// The client's MAC did not verify, so it does not know the password.
+    ClientAuthentication,
+    /// Stretching the OPRF output with Argon2id failed.
+    Password(PasswordError),
+    /// A lower-level failure, such as the RNG or OpenSSL.
+    Crypto(CryptoError),
+}
+
+impl fmt::Display for OpaqueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OpaqueError::InvalidMessage => write!(f, "invalid OPAQUE message"),
+            OpaqueError::InvalidInput => write!(f, "password hashes to the identity"),
+            OpaqueError::DeriveKeyPair => write!(f, "no key pair could be derived"),
+            OpaqueError::EnvelopeRecovery => write!(f, "envelope could not be opened"),
+            OpaqueError::ServerAuthentication => write!(f, "server failed to authenticate"),
+            OpaqueError::ClientAuthentication => write!(f, "client failed to authenticate"),
+            OpaqueError::Password(err) => write!(f, "{}", err),
+            OpaqueError::Crypto(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for OpaqueError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            OpaqueError::Password(err) => Some(err),
+            OpaqueError::Crypto(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<PasswordError> for OpaqueError {
+    fn from(err: PasswordError) -> Self {
+        OpaqueError::Password(err)
+    }
+}
+
+impl From<CryptoError> for OpaqueError {
+    fn from(err: CryptoError) -> Self {
+        OpaqueError::Crypto(err)
+    }
+}
+
+#[cfg(feature = "openssl")]
+impl From<ErrorStack> for OpaqueError {
+    fn from(err: ErrorStack) -> Self {
+        OpaqueError::Crypto(CryptoError::Backend(err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_params_error_classification() {
+        assert!(matches!(
+            CryptoError::from(ParamsError::Identity("y1")),
+            CryptoError::InvalidInput(ParamsError::Identity("y1"))
+        ));
+        assert!(matches!(
+            CryptoError::from(ParamsError::ScalarOutOfRange("c")),
+            CryptoError::InvalidInput(_)
+        ));
+        assert!(matches!(
+            CryptoError::from(ParamsError::SameGenerators),
+            CryptoError::InvalidParams(_)
+        ));
+        #[cfg(feature = "openssl")]
+        assert!(matches!(
+            CryptoError::from(ParamsError::Backend(ErrorStack::get())),
+            CryptoError::Backend(_)
+        ));
+    }
+}
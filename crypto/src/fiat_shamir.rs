@@ -0,0 +1,61 @@
+use crate::error::CryptoError;
+use crate::group::Group;
+use crate::prover::{ProverCommit, ProverPublicKeys};
+use crate::transcript::Transcript;
+
+const PROTOCOL: &[u8] = b"chaum-pedersen/v1";
+
+pub struct NonInteractiveProof<S> {
+    pub c: S,
+    pub s: S,
+}
+
+/// Appends one run of the protocol to `transcript`: the protocol version, the
+/// parameters fingerprint, the public keys and the commitment.
+pub fn append_run<G: Group>(
+    transcript: &mut Transcript,
+    group: &G,
+    keys: &ProverPublicKeys<&G::Element>,
+    commit: &ProverCommit<&G::Element>,
+) -> Result<(), CryptoError> {
+    transcript.append_message(b"protocol", PROTOCOL);
+    transcript.append_params(group)?;
+    transcript.append_element(group, b"y1", keys.y1)?;
+    transcript.append_element(group, b"y2", keys.y2)?;
+    transcript.append_element(group, b"r1", commit.r1)?;
+    transcript.append_element(group, b"r2", commit.r2)?;
+
+    Ok(())
+}
+
+/// Challenge for a non-interactive proof, binding the run to everything the
+/// caller has already appended to `transcript` (user, session, ...).
+pub fn group_challenge<G: Group>(
+    group: &G,
+    keys: &ProverPublicKeys<&G::Element>,
+    commit: &ProverCommit<&G::Element>,
+    transcript: &Transcript,
+) -> Result<G::Scalar, CryptoError> {
+    let mut transcript = transcript.clone();
+    append_run(&mut transcript, group, keys, commit)?;
+
+    transcript.challenge_scalar(b"c", group)
+}
+
+/// Challenge for an interactive run: the run and the verifier's fresh `nonce`
+/// appended to `transcript`. The verifier sends `nonce` along with `c`, so the
+/// prover can re-derive `c` and refuse to answer one the verifier chose; an
+/// answer to a chosen `c` would be a signature under `x` (see `schnorr`).
+pub fn interactive_challenge<G: Group>(
+    group: &G,
+    keys: &ProverPublicKeys<&G::Element>,
+    commit: &ProverCommit<&G::Element>,
+    nonce: &[u8],
+    transcript: &Transcript,
+) -> Result<G::Scalar, CryptoError> {
+    let mut transcript = transcript.clone();
+    append_run(&mut transcript, group, keys, commit)?;
+    transcript.append_message(b"verifier-nonce", nonce);
+
+    transcript.challenge_scalar(b"c", group)
+}
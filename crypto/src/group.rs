@@ -0,0 +1,308 @@
+use std::fmt;
+
+use curve25519_dalek::scalar::Scalar;
+#[cfg(feature = "openssl")]
+use openssl::bn::{BigNum, BigNumRef};
+use zeroize::Zeroize;
+
+use crate::error::{CryptoError, ParamsError};
+
+/// A value mod the order of a `Group`: a `BigNum` for the OpenSSL groups, a
+/// dalek `Scalar` for Ristretto255. `SecretScalar` wraps the secret ones.
+pub trait GroupScalar: fmt::Debug + PartialEq + Send + Sync + Sized + 'static {
+    /// Marks the value secret, so the backend takes its constant-time paths.
+    fn set_secret(&mut self) {}
+
+    /// Overwrites the value with zeros.
+    fn wipe(&mut self);
+
+    fn try_clone(&self) -> Result<Self, CryptoError>;
+
+    fn is_zero(&self) -> bool;
+}
+
+/// The flag makes OpenSSL take its constant-time paths for `mod_exp` and
+/// point multiplication, and survives `try_clone`.
+#[cfg(feature = "openssl")]
+impl GroupScalar for BigNum {
+    fn set_secret(&mut self) {
+        self.set_const_time();
+    }
+
+    fn wipe(&mut self) {
+        self.clear();
+    }
+
+    fn try_clone(&self) -> Result<BigNum, CryptoError> {
+        let mut copy = BigNumRef::to_owned(self)?;
+        if self.is_const_time() {
+            copy.set_const_time();
+        }
+
+        Ok(copy)
+    }
+
+    fn is_zero(&self) -> bool {
+        self.num_bits() == 0
+    }
+}
+
+/// Dalek's arithmetic is constant-time throughout, so there is nothing to flag.
+impl GroupScalar for Scalar {
+    fn wipe(&mut self) {
+        self.zeroize();
+    }
+
+    fn try_clone(&self) -> Result<Scalar, CryptoError> {
+        Ok(*self)
+    }
+
+    fn is_zero(&self) -> bool {
+        *self == Scalar::ZERO
+    }
+}
+
+/// SLH-DSA signs the verifier's nonce and has no `c`; its challenges carry `()`.
+impl GroupScalar for () {
+    fn wipe(&mut self) {}
+
+    fn try_clone(&self) -> Result<(), CryptoError> {
+        Ok(())
+    }
+
+    fn is_zero(&self) -> bool {
+        false
+    }
+}
+
+/// A prime-order group with two generators `g` and `h` of unknown relative
+/// discrete log, which is all the Chaum-Pedersen protocol needs.
+///
+/// Written multiplicatively: for elliptic curves `exp` is scalar multiplication
+/// and `multi_exp` a multi-scalar multiplication. Scalars are `Self::Scalar`s
+/// in `[0, q)`, and the scalar arithmetic is mod q.
+pub trait Group: Clone + Send + Sync + 'static {
+    type Element: Send + Sync + 'static;
+    type Scalar: GroupScalar;
+
+    fn g(&self) -> &Self::Element;
+    fn h(&self) -> &Self::Element;
+    fn identity(&self) -> Result<Self::Element, CryptoError>;
+
+    /// `base^e`, in constant time if `e` is secret.
+    fn exp(&self, base: &Self::Element, e: &Self::Scalar) -> Result<Self::Element, CryptoError>;
+
+    /// `prod bases_i^exponents_i`, variable-time; only for public values.
+    fn multi_exp(
+        &self,
+        bases: &[&Self::Element],
+        exponents: &[&Self::Scalar],
+    ) -> Result<Self::Element, CryptoError>;
+
+    fn element_eq(&self, a: &Self::Element, b: &Self::Element) -> Result<bool, CryptoError>;
+
+    fn encode_element(&self, element: &Self::Element) -> Result<Vec<u8>, CryptoError>;
+    /// Fails with `InvalidEncoding` on bytes that are not an element.
+    fn decode_element(&self, data: &[u8]) -> Result<Self::Element, CryptoError>;
+
+    /// Rejects elements outside the prime-order subgroup, or the identity.
+    fn check_element(&self, name: &'static str, element: &Self::Element)
+        -> Result<(), ParamsError>;
+
+    /// Encodings of the group and its generators, bound into every Fiat-Shamir challenge.
+    fn description(&self) -> Result<Vec<Vec<u8>>, CryptoError>;
+
+    /// Length of an encoded scalar, the size of q in bytes.
+    fn scalar_len(&self) -> usize;
+
+    /// The big-endian integer `bytes` mod q, in constant time. Hashing or
+    /// drawing `WIDE_MARGIN_BYTES` more than `scalar_len` makes the result
+    /// close to uniform; at most `2 * scalar_len` bytes are accepted.
+    fn reduce_scalar(&self, bytes: &[u8]) -> Result<Self::Scalar, CryptoError>;
+
+    /// `scalar_len` bytes: big-endian for the OpenSSL groups, little-endian
+    /// for Ristretto255 as in RFC 9496.
+    fn encode_scalar(&self, scalar: &Self::Scalar) -> Result<Vec<u8>, CryptoError>;
+
+    /// Fails with `InvalidEncoding` unless `data` is the encoding of a scalar
+    /// in `[0, q)`, so there is exactly one encoding of each.
+    fn decode_scalar(&self, data: &[u8]) -> Result<Self::Scalar, CryptoError>;
+
+    fn scalar_add(&self, a: &Self::Scalar, b: &Self::Scalar) -> Result<Self::Scalar, CryptoError>;
+    fn scalar_sub(&self, a: &Self::Scalar, b: &Self::Scalar) -> Result<Self::Scalar, CryptoError>;
+    /// `a * b`, in constant time if either is secret.
+    fn scalar_mul(&self, a: &Self::Scalar, b: &Self::Scalar) -> Result<Self::Scalar, CryptoError>;
+    /// `1 / a`, or `None` for zero.
+    fn scalar_invert(&self, a: &Self::Scalar) -> Result<Option<Self::Scalar>, CryptoError>;
+
+    /// Checks that the scalar `value` is in `[1, q)`; decoding has already
+    /// ruled out the values past q.
+    fn check_scalar(&self, name: &'static str, value: &Self::Scalar) -> Result<(), ParamsError> {
+        if value.is_zero() {
+            return Err(ParamsError::ScalarOutOfRange(name));
+        }
+
+        Ok(())
+    }
+
+    /// `g^e`; groups with fixed-base tables override this.
+    fn exp_g(&self, e: &Self::Scalar) -> Result<Self::Element, CryptoError> {
+        self.exp(self.g(), e)
+    }
+
+    /// `h^e`; groups with fixed-base tables override this.
+    fn exp_h(&self, e: &Self::Scalar) -> Result<Self::Element, CryptoError> {
+        self.exp(self.h(), e)
+    }
+
+    /// `g^n * y^m`, variable-time.
+    fn exp_g_mul(
+        &self,
+        n: &Self::Scalar,
+        y: &Self::Element,
+        m: &Self::Scalar,
+    ) -> Result<Self::Element, CryptoError> {
+        self.multi_exp(&[self.g(), y], &[n, m])
+    }
+
+    /// `h^n * y^m`, variable-time.
+    fn exp_h_mul(
+        &self,
+        n: &Self::Scalar,
+        y: &Self::Element,
+        m: &Self::Scalar,
+    ) -> Result<Self::Element, CryptoError> {
+        self.multi_exp(&[self.h(), y], &[n, m])
+    }
+
+    /// `g^a * h^b * prod bases_i^exponents_i`, variable-time.
+    fn multi_exp_gh(
+        &self,
+        a: &Self::Scalar,
+        b: &Self::Scalar,
+        bases: &[&Self::Element],
+        exponents: &[&Self::Scalar],
+    ) -> Result<Self::Element, CryptoError> {
+        let bases: Vec<_> = [self.g(), self.h()]
+            .into_iter()
+            .chain(bases.iter().copied())
+            .collect();
+        let exponents: Vec<_> = [a, b]
+            .into_iter()
+            .chain(exponents.iter().copied())
+            .collect();
+
+        self.multi_exp(&bases, &exponents)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::CryptoError;
+    #[cfg(feature = "openssl")]
+    use openssl::nid::Nid;
+
+    use super::*;
+    #[cfg(feature = "openssl")]
+    use crate::dl::{groups::NamedGroup, params::DlParams};
+    #[cfg(feature = "openssl")]
+    use crate::ec::params::EcParams;
+    use crate::random::OsRandom;
+    use crate::ristretto::params::RistrettoParams;
+    use crate::secret::SecretScalar;
+
+    // The overrides some groups provide must agree with the generic definitions
+    fn check_group_ops<G: Group>(group: &G) -> Result<(), CryptoError> {
+        let n = SecretScalar::random(&OsRandom, group)?;
+        let m = SecretScalar::random(&OsRandom, group)?;
+        let (n, m) = (n.expose(), m.expose());
+
+        let y = group.exp_g(m)?;
+        let (g, h) = (group.g(), group.h());
+        let eq = |a: &G::Element, b: &G::Element| group.element_eq(a, b);
+
+        assert!(eq(&y, &group.exp(g, m)?)?, "exp_g");
+        assert!(eq(&group.exp_h(n)?, &group.exp(h, n)?)?, "exp_h");
+        assert!(
+            eq(
+                &group.exp_g_mul(n, &y, m)?,
+                &group.multi_exp(&[g, &y], &[n, m])?
+            )?,
+            "exp_g_mul"
+        );
+        assert!(
+            eq(
+                &group.exp_h_mul(n, &y, m)?,
+                &group.multi_exp(&[h, &y], &[n, m])?
+            )?,
+            "exp_h_mul"
+        );
+        assert!(
+            eq(
+                &group.multi_exp_gh(n, m, &[&y], &[n])?,
+                &group.multi_exp(&[g, h, &y], &[n, m, n])?
+            )?,
+            "multi_exp_gh"
+        );
+        assert!(
+            eq(&group.multi_exp(&[], &[])?, &group.identity()?)?,
+            "empty multi_exp"
+        );
+
+        let decoded = group.decode_element(&group.encode_element(&y)?)?;
+        assert!(eq(&decoded, &y)?, "encoding round trip");
+
+        // Scalar arithmetic: (n + m) - m = n, n * (1 / n) = 1, and the
+        // encoding is canonical
+        let one = group.reduce_scalar(&[1])?;
+        let sum = group.scalar_add(n, m)?;
+        assert!(group.scalar_sub(&sum, m)? == *n, "scalar_sub");
+        let inverse = group.scalar_invert(n)?.expect("n is not zero");
+        assert!(group.scalar_mul(n, &inverse)? == one, "scalar_invert");
+        assert!(group.scalar_invert(&group.reduce_scalar(&[])?)?.is_none());
+        let encoded = group.encode_scalar(&sum)?;
+        assert_eq!(encoded.len(), group.scalar_len());
+        assert!(group.decode_scalar(&encoded)? == sum, "scalar round trip");
+        assert!(matches!(
+            group.decode_scalar(&vec![0xff; group.scalar_len()]),
+            Err(CryptoError::InvalidEncoding)
+        ));
+        assert!(group.decode_scalar(&encoded[1..]).is_err());
+        // 2^(8 * scalar_len) mod q, reduced from the wide end
+        let mut wide = vec![0u8; group.scalar_len() + 1];
+        wide[0] = 1;
+        let shifted = group.reduce_scalar(&wide)?;
+        let mut two_len = group.reduce_scalar(&[1, 0])?;
+        for _ in 1..group.scalar_len() {
+            two_len = group.scalar_mul(&two_len, &group.reduce_scalar(&[1, 0])?)?;
+        }
+        assert!(shifted == two_len, "reduce_scalar");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_group_ops() -> Result<(), CryptoError> {
+        #[cfg(feature = "openssl")]
+        {
+            let dl = DlParams::from_named_group(NamedGroup::Ffdhe2048, b"seed")?;
+            let mut dl_precomputed = dl.clone();
+            dl_precomputed.precompute()?;
+            let ec = EcParams::new(Nid::X9_62_PRIME256V1)?;
+            let mut ec_precomputed = ec.clone();
+            ec_precomputed.precompute()?;
+
+            check_group_ops(&dl)?;
+            check_group_ops(&dl_precomputed)?;
+            check_group_ops(&ec)?;
+            check_group_ops(&ec_precomputed)?;
+        }
+
+        let ristretto = RistrettoParams::new()?;
+        let mut ristretto_precomputed = ristretto.clone();
+        ristretto_precomputed.precompute();
+
+        check_group_ops(&ristretto)?;
+        check_group_ops(&ristretto_precomputed)
+    }
+}
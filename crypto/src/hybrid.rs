@@ -0,0 +1,185 @@
+//! Hybrid identification: a Chaum-Pedersen proof and an SLH-DSA signature
+//! answering one challenge, both of which must verify. The login stays sound
+//! as long as either holds, so accounts are covered against a quantum
+//! adversary without trusting SLH-DSA alone.
+//!
+//! The signature is made on the verifier's nonce in the session transcript
+//! with `c` appended, so both halves come from the same run.
+
+use crate::error::{CryptoError, PasswordError};
+use crate::group::{Group, GroupScalar};
+use crate::identification::{Challenge, IdentificationProver, IdentificationVerifier};
+use crate::password::PasswordKdf;
+use crate::prover::{ChaumPedersenProver, ProverCommit, ProverPublicKeys};
+use crate::secret::SecretScalar;
+use crate::slh_dsa::{SigningKey, SlhDsaProver, SlhDsaVerifier, VerifyingKey};
+use crate::transcript::Transcript;
+use crate::verifier::ChaumPedersenVerifier;
+
+pub struct HybridSecret<S: GroupScalar> {
+    pub x: SecretScalar<S>,
+    pub pq_key: SigningKey,
+}
+
+pub struct HybridPublicKey<Element> {
+    pub keys: ProverPublicKeys<Element>,
+    pub pq_key: VerifyingKey,
+}
+
+pub struct HybridResponse<S> {
+    pub s: S,
+    pub signature: Vec<u8>,
+}
+
+/// The prover's side of hybrid identification over any `Group`.
+pub struct HybridProver<G: Group> {
+    pub classical: ChaumPedersenProver<G>,
+    pub post_quantum: SlhDsaProver,
+}
+
+/// The verifier's side of hybrid identification over any `Group`.
+pub struct HybridVerifier<G: Group> {
+    pub classical: ChaumPedersenVerifier<G>,
+    pub post_quantum: SlhDsaVerifier,
+}
+
+// The transcript the SLH-DSA half signs: the session, and `c` as sent
+fn signed_transcript<G: Group>(
+    group: &G,
+    challenge: &Challenge<G::Scalar>,
+    transcript: &Transcript,
+) -> Result<Transcript, CryptoError> {
+    let mut transcript = transcript.clone();
+    transcript.append_message(b"c", &group.encode_scalar(challenge.scalar()?)?);
+
+    Ok(transcript)
+}
+
+impl<G: Group> IdentificationProver for HybridProver<G> {
+    type Secret = HybridSecret<G::Scalar>;
+    type PublicKey = HybridPublicKey<G::Element>;
+    type Nonce = SecretScalar<G::Scalar>;
+    type Commitment = ProverCommit<G::Element>;
+    type Response = HybridResponse<G::Scalar>;
+    type Scalar = G::Scalar;
+
+    fn generate_secret(&self) -> Result<HybridSecret<G::Scalar>, CryptoError> {
+        Ok(HybridSecret {
+            x: self.classical.generate_secret()?,
+            pq_key: self.post_quantum.generate_secret()?,
+        })
+    }
+
+    // Both secrets come from the password; the KDF keeps them unrelated
+    fn secret_from_password(
+        &self,
+        kdf: &PasswordKdf,
+        user: &str,
+        password: &[u8],
+        salt: &[u8],
+    ) -> Result<HybridSecret<G::Scalar>, PasswordError> {
+        Ok(HybridSecret {
+            x: self
+                .classical
+                .secret_from_password(kdf, user, password, salt)?,
+            pq_key: self
+                .post_quantum
+                .secret_from_password(kdf, user, password, salt)?,
+        })
+    }
+
+    fn public_key(
+        &self,
+        secret: &HybridSecret<G::Scalar>,
+    ) -> Result<HybridPublicKey<G::Element>, CryptoError> {
+        Ok(HybridPublicKey {
+            keys: self.classical.public_key(&secret.x)?,
+            pq_key: self.post_quantum.public_key(&secret.pq_key)?,
+        })
+    }
+
+    fn commitment(
+        &self,
+        secret: &HybridSecret<G::Scalar>,
+        context: &[u8],
+    ) -> Result<(SecretScalar<G::Scalar>, ProverCommit<G::Element>), CryptoError> {
+        self.classical.commitment(&secret.x, context)
+    }
+
+    fn accepts_challenge(
+        &self,
+        key: &HybridPublicKey<G::Element>,
+        commit: &ProverCommit<G::Element>,
+        challenge: &Challenge<G::Scalar>,
+        transcript: &Transcript,
+    ) -> Result<bool, CryptoError> {
+        self.classical
+            .accepts_challenge(&key.keys, commit, challenge, transcript)
+    }
+
+    fn respond(
+        &self,
+        secret: &HybridSecret<G::Scalar>,
+        k: &SecretScalar<G::Scalar>,
+        challenge: &Challenge<G::Scalar>,
+        transcript: &Transcript,
+    ) -> Result<HybridResponse<G::Scalar>, CryptoError> {
+        Ok(HybridResponse {
+            s: self
+                .classical
+                .respond(&secret.x, k, challenge, transcript)?,
+            signature: self.post_quantum.challenge_response(
+                &secret.pq_key,
+                &challenge.nonce,
+                &signed_transcript(&self.classical.params, challenge, transcript)?,
+            )?,
+        })
+    }
+}
+
+impl<G: Group> IdentificationVerifier for HybridVerifier<G> {
+    type PublicKey = HybridPublicKey<G::Element>;
+    type Commitment = ProverCommit<G::Element>;
+    type Response = HybridResponse<G::Scalar>;
+    type Scalar = G::Scalar;
+
+    /// One challenge for both halves: `c` for the Chaum-Pedersen proof, and
+    /// the nonce the SLH-DSA signature is made on along with `c`.
+    fn challenge(
+        &self,
+        key: &HybridPublicKey<G::Element>,
+        commit: &ProverCommit<G::Element>,
+        transcript: &Transcript,
+    ) -> Result<Challenge<G::Scalar>, CryptoError> {
+        self.classical.challenge(&key.keys, commit, transcript)
+    }
+
+    fn decoy_challenge(
+        &self,
+        transcript: &Transcript,
+    ) -> Result<Challenge<G::Scalar>, CryptoError> {
+        self.classical.decoy_challenge(transcript)
+    }
+
+    fn check(
+        &self,
+        key: &HybridPublicKey<G::Element>,
+        commit: &ProverCommit<G::Element>,
+        challenge: &Challenge<G::Scalar>,
+        response: &HybridResponse<G::Scalar>,
+        transcript: &Transcript,
+    ) -> Result<bool, CryptoError> {
+        let classical =
+            self.classical
+                .check(&key.keys, commit, challenge, &response.s, transcript)?;
+        let post_quantum = SlhDsaVerifier::check(
+            &self.post_quantum,
+            &key.pq_key,
+            &challenge.nonce,
+            &signed_transcript(&self.classical.params, challenge, transcript)?,
+            &response.signature,
+        );
+
+        Ok(classical && post_quantum)
+    }
+}
@@ -0,0 +1,147 @@
+//! The shape every interactive login shares: the prover sends a commitment,
+//! the verifier answers with a challenge bound to the session transcript, and
+//! checks the prover's response against both. Chaum-Pedersen, Schnorr,
+//! Okamoto, SLH-DSA and the hybrid of Chaum-Pedersen and SLH-DSA all
+//! implement it, so one server and one client run any of them.
+
+use crate::error::{CryptoError, ParamsError, PasswordError};
+use crate::group::{Group, GroupScalar};
+use crate::password::PasswordKdf;
+use crate::random::RandomSource;
+use crate::transcript::Transcript;
+use crate::util::challenge_nonce;
+
+const DECOY_CHALLENGE: &[u8] = b"decoy-challenge";
+
+/// The verifier's challenge: its fresh `nonce`, and the `c` hashed from the
+/// nonce and the run for protocols that have one. SLH-DSA signs the nonce
+/// itself and has no `c`.
+pub struct Challenge<S> {
+    pub c: Option<S>,
+    pub nonce: Vec<u8>,
+}
+
+impl<S: GroupScalar> Challenge<S> {
+    /// `c`, for the protocols that have one. A challenge without is treated
+    /// like `c = 0`, which is out of range.
+    pub fn scalar(&self) -> Result<&S, CryptoError> {
+        self.c
+            .as_ref()
+            .ok_or(CryptoError::InvalidInput(ParamsError::ScalarOutOfRange(
+                "c",
+            )))
+    }
+
+    pub fn try_clone(&self) -> Result<Challenge<S>, CryptoError> {
+        Ok(Challenge {
+            c: self.c.as_ref().map(GroupScalar::try_clone).transpose()?,
+            nonce: self.nonce.clone(),
+        })
+    }
+}
+
+/// A challenge for a user with no registration: a fresh nonce, and a `c`
+/// hashed from it and the session. Without the key a real `c` is hashed with,
+/// the two can't be told apart.
+pub fn decoy_challenge<G: Group>(
+    rng: &dyn RandomSource,
+    group: &G,
+    transcript: &Transcript,
+) -> Result<Challenge<G::Scalar>, CryptoError> {
+    let nonce = challenge_nonce(rng)?;
+    let mut transcript = transcript.clone();
+    transcript.append_message(DECOY_CHALLENGE, &nonce);
+
+    Ok(Challenge {
+        c: Some(transcript.challenge_scalar(b"c", group)?),
+        nonce,
+    })
+}
+
+/// The verifier's side of an identification protocol.
+pub trait IdentificationVerifier: Send + Sync + 'static {
+    type PublicKey: Send + Sync + 'static;
+    type Commitment: Send + Sync + 'static;
+    type Response;
+    /// The type of `c`: the group's scalar, or `()` for SLH-DSA.
+    type Scalar: GroupScalar;
+
+    /// Challenge for `commitment` by the holder of `key` in the session
+    /// `transcript`.
+    fn challenge(
+        &self,
+        key: &Self::PublicKey,
+        commitment: &Self::Commitment,
+        transcript: &Transcript,
+    ) -> Result<Challenge<Self::Scalar>, CryptoError>;
+
+    /// Challenge for a user with no registration, answered so that the
+    /// response doesn't reveal whether `user` exists.
+    fn decoy_challenge(
+        &self,
+        transcript: &Transcript,
+    ) -> Result<Challenge<Self::Scalar>, CryptoError>;
+
+    /// Checks `response` to `challenge`, with the elements of `key` and
+    /// `commitment` already checked with `check_element`.
+    fn check(
+        &self,
+        key: &Self::PublicKey,
+        commitment: &Self::Commitment,
+        challenge: &Challenge<Self::Scalar>,
+        response: &Self::Response,
+        transcript: &Transcript,
+    ) -> Result<bool, CryptoError>;
+}
+
+/// The prover's side of an identification protocol.
+pub trait IdentificationProver {
+    type Secret;
+    type PublicKey;
+    /// The secret a commitment is made with (`k`), kept until the response.
+    type Nonce;
+    type Commitment;
+    type Response;
+    type Scalar: GroupScalar;
+
+    fn generate_secret(&self) -> Result<Self::Secret, CryptoError>;
+
+    /// The secret `password` stretches to with `kdf`, the same on every device.
+    fn secret_from_password(
+        &self,
+        kdf: &PasswordKdf,
+        user: &str,
+        password: &[u8],
+        salt: &[u8],
+    ) -> Result<Self::Secret, PasswordError>;
+
+    fn public_key(&self, secret: &Self::Secret) -> Result<Self::PublicKey, CryptoError>;
+
+    /// Commitment for one run, with its nonce hedged with `secret` and the
+    /// session `context`, which must differ between runs.
+    fn commitment(
+        &self,
+        secret: &Self::Secret,
+        context: &[u8],
+    ) -> Result<(Self::Nonce, Self::Commitment), CryptoError>;
+
+    /// Whether `challenge` is the one the run and `transcript` commit the
+    /// verifier to. Only those may be answered: answering a `c` the verifier
+    /// picked freely would sign whatever it hashed (see `schnorr`).
+    fn accepts_challenge(
+        &self,
+        key: &Self::PublicKey,
+        commitment: &Self::Commitment,
+        challenge: &Challenge<Self::Scalar>,
+        transcript: &Transcript,
+    ) -> Result<bool, CryptoError>;
+
+    /// Answers an accepted `challenge`.
+    fn respond(
+        &self,
+        secret: &Self::Secret,
+        k: &Self::Nonce,
+        challenge: &Challenge<Self::Scalar>,
+        transcript: &Transcript,
+    ) -> Result<Self::Response, CryptoError>;
+}
@@ -1,6 +1,31 @@
+pub mod analysis;
+#[cfg(feature = "openssl")]
 pub mod context;
+#[cfg(feature = "openssl")]
 pub mod dl;
+#[cfg(feature = "openssl")]
 pub mod ec;
+pub mod error;
+pub mod fiat_shamir;
+pub mod group;
+#[cfg(feature = "openssl")]
+pub mod hybrid;
+pub mod identification;
+pub mod membership;
+#[cfg(feature = "openssl")]
+pub mod multiexp;
+pub mod okamoto;
+#[cfg(feature = "openssl")]
+pub mod opaque;
+pub mod password;
 pub mod prover;
+pub mod random;
+pub mod ristretto;
+pub mod schnorr;
+pub mod secret;
+pub mod sigma;
+#[cfg(feature = "openssl")]
+pub mod slh_dsa;
+pub mod transcript;
 pub mod util;
 pub mod verifier;
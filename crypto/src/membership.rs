@@ -0,0 +1,363 @@
+//! 1-of-n proofs of knowledge: the prover shows it knows `x` for one of a set
+//! of Chaum-Pedersen key pairs without revealing which, by OR-composing the
+//! protocol (Cramer, Damgård and Schoenmakers). Every branch but the real one
+//! is simulated with a challenge chosen in advance; the real branch gets
+//! whatever is left of the verifier's challenge, so the prover can only answer
+//! if it knows one of the secrets.
+
+use std::sync::Arc;
+
+use crate::analysis::simulate;
+use crate::error::{CryptoError, MembershipError};
+use crate::group::{Group, GroupScalar};
+use crate::prover::{ProverChallengeResponse, ProverCommit, ProverPublicKeys};
+use crate::random::{default_source, RandomSource};
+use crate::secret::SecretScalar;
+use crate::transcript::Transcript;
+use crate::util::random_scalar;
+
+const PROTOCOL: &[u8] = b"membership/v1";
+
+/// Challenge and response of one branch.
+pub struct MembershipResponse<S> {
+    pub c: S,
+    pub s: S,
+}
+
+/// Non-interactive proof: one challenge and response per member, in the order
+/// of the key set. The challenges sum to the Fiat-Shamir challenge.
+pub struct MembershipProof<S> {
+    pub responses: Vec<MembershipResponse<S>>,
+}
+
+/// What the prover keeps between committing and answering.
+pub struct MembershipState<S: GroupScalar> {
+    index: usize,
+    k: SecretScalar<S>,
+    simulated: Vec<Option<MembershipResponse<S>>>,
+}
+
+/// The prover's side of the 1-of-n proof over any `Group`.
+pub struct MembershipProver<G: Group> {
+    pub params: G,
+    /// Where the nonce hedge and the simulated branches come from.
+    pub rng: Arc<dyn RandomSource>,
+}
+
+impl<G: Group> MembershipProver<G> {
+    pub fn new(params: G) -> Self {
+        MembershipProver::with_rng(params, default_source())
+    }
+
+    /// A prover drawing its randomness from `rng` instead of the default source.
+    pub fn with_rng(params: G, rng: Arc<dyn RandomSource>) -> Self {
+        MembershipProver { params, rng }
+    }
+
+    /// One commitment per member: real for `keys[index]`, whose secret is `x`,
+    /// and simulated for the others. The simulated branches use public
+    /// random values and variable-time arithmetic, so this runs in time
+    /// roughly proportional to `keys.len()` whatever `index` is. The real
+    /// nonce is hedged with the `transcript` digest.
+    #[allow(clippy::type_complexity)]
+    pub fn commit(
+        &self,
+        keys: &[ProverPublicKeys<&G::Element>],
+        index: usize,
+        x: &SecretScalar<G::Scalar>,
+        transcript: &Transcript,
+    ) -> Result<(MembershipState<G::Scalar>, Vec<ProverCommit<G::Element>>), MembershipError> {
+        let own = keys.get(index).ok_or(MembershipError::NotAMember)?;
+
+        let y1 = self.params.exp_g(x.expose())?;
+        let y2 = self.params.exp_h(x.expose())?;
+        if !self.params.element_eq(&y1, own.y1)? || !self.params.element_eq(&y2, own.y2)? {
+            return Err(MembershipError::NotAMember);
+        }
+
+        let k = SecretScalar::hedged(&*self.rng, &self.params, x, &transcript.digest())?;
+        let own_commit = ProverCommit {
+            r1: self.params.exp_g(k.expose())?,
+            r2: self.params.exp_h(k.expose())?,
+        };
+
+        let mut commits = Vec::with_capacity(keys.len());
+        let mut simulated = Vec::with_capacity(keys.len());
+        let mut own_commit = Some(own_commit);
+        for (j, key) in keys.iter().enumerate() {
+            if j == index {
+                commits.extend(own_commit.take());
+                simulated.push(None);
+                continue;
+            }
+
+            let c = random_scalar(&*self.rng, &self.params)?;
+            let (commit, ProverChallengeResponse { s }) =
+                simulate(&*self.rng, &self.params, key.y1, key.y2, &c)?;
+            commits.push(commit);
+            simulated.push(Some(MembershipResponse { c, s }));
+        }
+
+        Ok((
+            MembershipState {
+                index,
+                k,
+                simulated,
+            },
+            commits,
+        ))
+    }
+
+    /// Splits the verifier's challenge `c`: the real branch gets
+    /// `c - sum of the simulated challenges` and is answered with `x`.
+    pub fn respond(
+        &self,
+        state: MembershipState<G::Scalar>,
+        c: &G::Scalar,
+        x: &SecretScalar<G::Scalar>,
+    ) -> Result<Vec<MembershipResponse<G::Scalar>>, CryptoError> {
+        let group = &self.params;
+
+        let mut own_c = c.try_clone()?;
+        for response in state.simulated.iter().flatten() {
+            own_c = group.scalar_sub(&own_c, &response.c)?;
+        }
+        let cx = SecretScalar::new(group.scalar_mul(&own_c, x.expose())?);
+        let own_s = group.scalar_sub(state.k.expose(), cx.expose())?;
+
+        let mut own = Some(MembershipResponse { c: own_c, s: own_s });
+        Ok(state
+            .simulated
+            .into_iter()
+            .enumerate()
+            .filter_map(|(j, response)| match j == state.index {
+                true => own.take(),
+                false => response,
+            })
+            .collect())
+    }
+
+    /// Non-interactive (Fiat-Shamir) proof, bound to the key set and
+    /// everything appended to `transcript`.
+    pub fn prove(
+        &self,
+        keys: &[ProverPublicKeys<&G::Element>],
+        index: usize,
+        x: &SecretScalar<G::Scalar>,
+        transcript: &Transcript,
+    ) -> Result<MembershipProof<G::Scalar>, MembershipError> {
+        let (state, commits) = self.commit(keys, index, x, transcript)?;
+        let c = challenge(&self.params, keys, &commits, transcript)?;
+
+        Ok(MembershipProof {
+            responses: self.respond(state, &c, x)?,
+        })
+    }
+}
+
+/// The verifier's side of the 1-of-n proof over any `Group`.
+pub struct MembershipVerifier<G: Group> {
+    pub params: G,
+    /// Where interactive challenges come from.
+    pub rng: Arc<dyn RandomSource>,
+}
+
+impl<G: Group> MembershipVerifier<G> {
+    pub fn new(params: G) -> Self {
+        MembershipVerifier::with_rng(params, default_source())
+    }
+
+    /// A verifier drawing its randomness from `rng` instead of the default source.
+    pub fn with_rng(params: G, rng: Arc<dyn RandomSource>) -> Self {
+        MembershipVerifier { params, rng }
+    }
+
+    /// Random challenge in `[0, q)` for the interactive protocol.
+    pub fn random(&self) -> Result<G::Scalar, CryptoError> {
+        random_scalar(&*self.rng, &self.params)
+    }
+
+    /// Checks an interactive run: the branch challenges must sum to `c` and
+    /// every branch must verify.
+    pub fn check(
+        &self,
+        keys: &[ProverPublicKeys<&G::Element>],
+        commits: &[ProverCommit<G::Element>],
+        c: &G::Scalar,
+        responses: &[MembershipResponse<G::Scalar>],
+    ) -> Result<bool, CryptoError> {
+        if commits.len() != keys.len() {
+            return Ok(false);
+        }
+        let Some(expected) = self.expected_commits(keys, c, responses)? else {
+            return Ok(false);
+        };
+
+        for (commit, expected) in commits.iter().zip(&expected) {
+            if !self.params.element_eq(&commit.r1, &expected.r1)?
+                || !self.params.element_eq(&commit.r2, &expected.r2)?
+            {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Checks a non-interactive proof produced for the same key set and `transcript`.
+    pub fn verify(
+        &self,
+        keys: &[ProverPublicKeys<&G::Element>],
+        proof: &MembershipProof<G::Scalar>,
+        transcript: &Transcript,
+    ) -> Result<bool, CryptoError> {
+        let mut c = self.params.reduce_scalar(&[])?;
+        for response in &proof.responses {
+            c = self.params.scalar_add(&c, &response.c)?;
+        }
+        let Some(commits) = self.expected_commits(keys, &c, &proof.responses)? else {
+            return Ok(false);
+        };
+
+        Ok(challenge(&self.params, keys, &commits, transcript)? == c)
+    }
+
+    // The commitments each branch's (c_j, s_j) imply, or None if the branch
+    // challenges don't add up to c or don't match the key set
+    fn expected_commits(
+        &self,
+        keys: &[ProverPublicKeys<&G::Element>],
+        c: &G::Scalar,
+        responses: &[MembershipResponse<G::Scalar>],
+    ) -> Result<Option<Vec<ProverCommit<G::Element>>>, CryptoError> {
+        if responses.len() != keys.len() || keys.is_empty() {
+            return Ok(None);
+        }
+        let group = &self.params;
+
+        let mut sum = group.reduce_scalar(&[])?;
+        let mut commits = Vec::with_capacity(keys.len());
+        for (key, response) in keys.iter().zip(responses) {
+            sum = group.scalar_add(&sum, &response.c)?;
+            commits.push(ProverCommit {
+                r1: group.exp_g_mul(&response.s, key.y1, &response.c)?,
+                r2: group.exp_h_mul(&response.s, key.y2, &response.c)?,
+            });
+        }
+
+        Ok((&sum == c).then_some(commits))
+    }
+}
+
+// Challenge binding the group, every key and every commitment to `transcript`
+fn challenge<G: Group>(
+    group: &G,
+    keys: &[ProverPublicKeys<&G::Element>],
+    commits: &[ProverCommit<G::Element>],
+    transcript: &Transcript,
+) -> Result<G::Scalar, CryptoError> {
+    let mut transcript = transcript.clone();
+    transcript.append_message(b"protocol", PROTOCOL);
+    transcript.append_params(group)?;
+    transcript.append_message(b"members", &(keys.len() as u32).to_be_bytes());
+    for (key, commit) in keys.iter().zip(commits) {
+        transcript.append_element(group, b"y1", key.y1)?;
+        transcript.append_element(group, b"y2", key.y2)?;
+        transcript.append_element(group, b"r1", &commit.r1)?;
+        transcript.append_element(group, b"r2", &commit.r2)?;
+    }
+
+    transcript.challenge_scalar(b"c", group)
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "openssl")]
+    use openssl::nid::Nid;
+
+    use super::*;
+    #[cfg(feature = "openssl")]
+    use crate::dl::{groups::NamedGroup, params::DlParams};
+    #[cfg(feature = "openssl")]
+    use crate::ec::params::EcParams;
+    use crate::prover::{ChaumPedersenProver, Prover};
+    use crate::ristretto::params::RistrettoParams;
+
+    const MEMBERS: usize = 3;
+
+    fn check_membership<G: Group>(group: G) -> Result<(), MembershipError> {
+        let cp = ChaumPedersenProver::new(group.clone())?;
+        let prover = MembershipProver::new(group.clone());
+        let verifier = MembershipVerifier::new(group.clone());
+
+        let secrets = (0..MEMBERS)
+            .map(|_| cp.random())
+            .collect::<Result<Vec<_>, _>>()?;
+        let owned = secrets
+            .iter()
+            .map(|x| cp.public_keys(x))
+            .collect::<Result<Vec<_>, _>>()?;
+        let keys: Vec<_> = owned
+            .iter()
+            .map(|key| ProverPublicKeys {
+                y1: &key.y1,
+                y2: &key.y2,
+            })
+            .collect();
+        let context = Transcript::new(b"context");
+
+        for (index, x) in secrets.iter().enumerate() {
+            // Interactive
+            let (state, commits) = prover.commit(&keys, index, x, &Transcript::new(b"session"))?;
+            let c = verifier.random()?;
+            let responses = prover.respond(state, &c, x)?;
+            assert!(verifier.check(&keys, &commits, &c, &responses)?);
+            assert!(!verifier.check(&keys, &commits, &verifier.random()?, &responses)?);
+
+            // Non-interactive
+            let proof = prover.prove(&keys, index, x, &context)?;
+            assert!(verifier.verify(&keys, &proof, &context)?, "proof failed");
+            assert!(!verifier.verify(&keys, &proof, &Transcript::new(b"other context"))?);
+            assert!(!verifier.verify(&keys[1..], &proof, &context)?);
+        }
+
+        let mut proof = prover.prove(&keys, 0, &secrets[0], &context)?;
+        let reversed: Vec<_> = keys
+            .iter()
+            .rev()
+            .map(|key| ProverPublicKeys {
+                y1: key.y1,
+                y2: key.y2,
+            })
+            .collect();
+        assert!(!verifier.verify(&reversed, &proof, &context)?);
+        proof.responses[1].s =
+            group.scalar_add(&proof.responses[1].s, &group.reduce_scalar(&[1])?)?;
+        assert!(!verifier.verify(&keys, &proof, &context)?);
+
+        assert!(matches!(
+            prover.prove(&keys, 1, &secrets[0], &context),
+            Err(MembershipError::NotAMember)
+        ));
+        assert!(matches!(
+            prover.prove(&keys, MEMBERS, &secrets[0], &context),
+            Err(MembershipError::NotAMember)
+        ));
+
+        // A set of one is plain Chaum-Pedersen
+        let proof = prover.prove(&keys[..1], 0, &secrets[0], &context)?;
+        assert!(verifier.verify(&keys[..1], &proof, &context)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_membership() -> Result<(), MembershipError> {
+        #[cfg(feature = "openssl")]
+        {
+            check_membership(DlParams::from_named_group(NamedGroup::Ffdhe2048, b"seed")?)?;
+            check_membership(EcParams::new(Nid::X9_62_PRIME256V1)?)?;
+        }
+        check_membership(RistrettoParams::new()?)
+    }
+}
@@ -0,0 +1,164 @@
+//! Simultaneous multi-exponentiation.
+//!
+//! These run in variable time and must only be used with public exponents,
+//! i.e. on the verifier side.
+
+use std::os::raw::c_int;
+use std::ptr;
+
+use foreign_types::{ForeignType, ForeignTypeRef};
+use openssl::bn::{BigNum, BigNumContextRef, BigNumRef};
+use openssl::ec::{EcGroup, EcGroupRef, EcPoint, EcPointRef};
+use openssl::error::ErrorStack;
+use openssl_sys::{BIGNUM, BN_CTX, BN_MONT_CTX};
+
+// Part of OpenSSL's public bn.h API, but neither the `openssl` crate nor
+// `openssl-sys` bind it
+extern "C" {
+    fn BN_mod_exp2_mont(
+        rr: *mut BIGNUM,
+        a1: *const BIGNUM,
+        p1: *const BIGNUM,
+        a2: *const BIGNUM,
+        p2: *const BIGNUM,
+        m: *const BIGNUM,
+        ctx: *mut BN_CTX,
+        in_mont: *mut BN_MONT_CTX,
+    ) -> c_int;
+}
+
+fn cvt(result: c_int) -> Result<(), ErrorStack> {
+    if result <= 0 {
+        Err(ErrorStack::get())
+    } else {
+        Ok(())
+    }
+}
+
+/// `a1^p1 * a2^p2 mod m` with a single shared square-and-multiply pass
+/// (Straus/Shamir's trick). `m` must be odd.
+pub fn mod_exp2(
+    a1: &BigNumRef,
+    p1: &BigNumRef,
+    a2: &BigNumRef,
+    p2: &BigNumRef,
+    m: &BigNumRef,
+    ctx: &mut BigNumContextRef,
+) -> Result<BigNum, ErrorStack> {
+    let result = BigNum::new()?;
+    unsafe {
+        cvt(BN_mod_exp2_mont(
+            result.as_ptr(),
+            a1.as_ptr(),
+            p1.as_ptr(),
+            a2.as_ptr(),
+            p2.as_ptr(),
+            m.as_ptr(),
+            ctx.as_ptr(),
+            ptr::null_mut(),
+        ))?;
+    }
+
+    Ok(result)
+}
+
+/// The curve of `group` with `base` as its generator.
+pub(crate) fn with_generator(
+    group: &EcGroupRef,
+    base: &EcPointRef,
+    ctx: &mut BigNumContextRef,
+) -> Result<EcGroup, ErrorStack> {
+    let nid = group.curve_name().ok_or_else(ErrorStack::get)?;
+    let mut with_base = EcGroup::from_curve_name(nid)?;
+
+    let mut order = BigNum::new()?;
+    group.order(&mut order, ctx)?;
+    let mut cofactor = BigNum::new()?;
+    group.cofactor(&mut cofactor, ctx)?;
+    with_base.set_generator(base.to_owned(group)?, order, cofactor)?;
+
+    Ok(with_base)
+}
+
+/// `sum n_i * P_i` over `(P_i, n_i)` terms. Each pair of terms is one
+/// `EC_POINT_mul` on a copy of the curve with the first point as its
+/// generator, which shares the doublings between the two.
+pub fn ec_mul_sum(
+    group: &EcGroupRef,
+    terms: &[(&EcPointRef, &BigNumRef)],
+    ctx: &mut BigNumContextRef,
+) -> Result<EcPoint, ErrorStack> {
+    let mut sum = EcPoint::new(group)?;
+    for pair in terms.chunks(2) {
+        let mut term = EcPoint::new(group)?;
+        match pair {
+            [(p1, n1), (p2, n2)] => {
+                let with_p1 = with_generator(group, p1, ctx)?;
+                term.mul_full(&with_p1, n1, p2, n2, ctx)?;
+            }
+            [(p, n)] => term.mul2(group, p, n, ctx)?,
+            _ => unreachable!("chunks of at most two"),
+        }
+
+        let mut next = EcPoint::new(group)?;
+        next.add(group, &sum, &term, ctx)?;
+        sum = next;
+    }
+
+    Ok(sum)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::CryptoError;
+    use openssl::bn::BigNumContext;
+    use openssl::ec::EcGroup;
+    use openssl::nid::Nid;
+
+    use super::*;
+
+    #[test]
+    fn test_mod_exp2() -> Result<(), CryptoError> {
+        let mut ctx = BigNumContext::new()?;
+        let m = BigNum::get_rfc3526_prime_2048()?;
+        let (a1, a2) = (BigNum::from_u32(3)?, BigNum::from_u32(7)?);
+        let (mut p1, mut p2) = (BigNum::new()?, BigNum::new()?);
+        m.rand_range(&mut p1)?;
+        m.rand_range(&mut p2)?;
+
+        let (mut x1, mut x2, mut expected) = (BigNum::new()?, BigNum::new()?, BigNum::new()?);
+        x1.mod_exp(&a1, &p1, &m, &mut ctx)?;
+        x2.mod_exp(&a2, &p2, &m, &mut ctx)?;
+        expected.mod_mul(&x1, &x2, &m, &mut ctx)?;
+
+        assert_eq!(mod_exp2(&a1, &p1, &a2, &p2, &m, &mut ctx)?, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ec_mul_sum() -> Result<(), CryptoError> {
+        let mut ctx = BigNumContext::new()?;
+        let group = EcGroup::from_curve_name(Nid::X9_62_PRIME256V1)?;
+        let (n1, n2, n3) = (
+            BigNum::from_u32(5)?,
+            BigNum::from_u32(11)?,
+            BigNum::from_u32(16)?,
+        );
+
+        // 5G + 11G == 16G, and an odd term on its own: 5G + 11G + 16G == 32G
+        let g = group.generator_opt().unwrap();
+        let sum = ec_mul_sum(&group, &[(g, &n1), (g, &n2)], &mut ctx)?;
+        let sum3 = ec_mul_sum(&group, &[(g, &n1), (g, &n2), (g, &n3)], &mut ctx)?;
+        let n4 = BigNum::from_u32(32)?;
+        let mut expected3 = EcPoint::new(&group)?;
+        expected3.mul_generator2(&group, &n4, &mut ctx)?;
+        assert!(sum3.eq(&group, &expected3, &mut ctx)?);
+        let mut expected = EcPoint::new(&group)?;
+        expected.mul_generator2(&group, &n3, &mut ctx)?;
+
+        assert!(sum.eq(&group, &expected, &mut ctx)?);
+
+        Ok(())
+    }
+}
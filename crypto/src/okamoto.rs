@@ -0,0 +1,372 @@
+//! Okamoto identification: the prover knows two secrets `(a, b)` with
+//! `y1 = g^a·h^b`, commits `r1 = g^k1·h^k2` and answers `s1 = k1 - c·a`,
+//! `s2 = k2 - c·b`, which the verifier checks as `r1 == g^s1·h^s2·y1^c`.
+//!
+//! Every `y1` has q representations `(a, b)`, and a run looks the same
+//! whichever one the prover holds (witness indistinguishability). A verifier
+//! that deviates from the protocol therefore learns nothing it could use to
+//! impersonate the prover: one that could would, together with the prover's
+//! own representation, find `log_g(h)`. So unlike Chaum-Pedersen, which is
+//! only proven zero-knowledge against an honest verifier, Okamoto is secure
+//! against active attacks under the discrete-log assumption, as long as
+//! nobody knows `log_g(h)`; our `h` is hashed from a public seed.
+
+use std::sync::Arc;
+
+use zeroize::Zeroize;
+
+use crate::error::{CryptoError, PasswordError};
+use crate::group::{Group, GroupScalar};
+use crate::identification::{self, Challenge, IdentificationProver, IdentificationVerifier};
+use crate::password::PasswordKdf;
+use crate::random::{default_source, RandomSource};
+use crate::secret::{SecretScalar, WIDE_MARGIN_BYTES};
+use crate::transcript::Transcript;
+use crate::util::{challenge_nonce, expand_hash};
+
+const PROTOCOL: &[u8] = b"okamoto/v1";
+const WITNESS_DOMAIN: &[u8] = b"zkp_auth/okamoto-witness/v1";
+
+/// A representation `(a, b)` of `y1 = g^a·h^b`; also the nonce pair `(k1, k2)`.
+pub struct OkamotoWitness<S: GroupScalar> {
+    pub a: SecretScalar<S>,
+    pub b: SecretScalar<S>,
+}
+
+impl<S: GroupScalar> OkamotoWitness<S> {
+    /// Splits a single secret, such as a password-derived `x`, into `(a, b)`.
+    pub fn from_secret<G: Group<Scalar = S>>(
+        group: &G,
+        x: &SecretScalar<S>,
+    ) -> Result<Self, CryptoError> {
+        let mut x_bytes = group.encode_scalar(x.expose())?;
+
+        let len = group.scalar_len() + WIDE_MARGIN_BYTES;
+        let mut wide = expand_hash(WITNESS_DOMAIN, &[&x_bytes], 2 * len);
+        let a = SecretScalar::reduce_wide(group, &wide[..len]);
+        let b = SecretScalar::reduce_wide(group, &wide[len..]);
+        x_bytes.zeroize();
+        wide.zeroize();
+
+        Ok(OkamotoWitness { a: a?, b: b? })
+    }
+}
+
+pub struct OkamotoResponse<S> {
+    pub s1: S,
+    pub s2: S,
+}
+
+/// The prover's side of Okamoto identification over any `Group`.
+pub struct OkamotoProver<G: Group> {
+    pub params: G,
+    /// Where `(a, b)`, `(k1, k2)` and the nonce hedges come from.
+    pub rng: Arc<dyn RandomSource>,
+}
+
+impl<G: Group> OkamotoProver<G> {
+    pub fn new(params: G) -> Self {
+        OkamotoProver::with_rng(params, default_source())
+    }
+
+    /// A prover drawing its randomness from `rng` instead of the default source.
+    pub fn with_rng(params: G, rng: Arc<dyn RandomSource>) -> Self {
+        OkamotoProver { params, rng }
+    }
+
+    /// Random pair in `[0, q)²`, used for both `(a, b)` and `(k1, k2)`.
+    pub fn random(&self) -> Result<OkamotoWitness<G::Scalar>, CryptoError> {
+        Ok(OkamotoWitness {
+            a: SecretScalar::random(&*self.rng, &self.params)?,
+            b: SecretScalar::random(&*self.rng, &self.params)?,
+        })
+    }
+
+    /// Nonces `(k1, k2)` hedged with `(a, b)` and the session `context`, as in
+    /// `Prover::hedged_nonce`.
+    pub fn hedged_nonce(
+        &self,
+        witness: &OkamotoWitness<G::Scalar>,
+        context: &[u8],
+    ) -> Result<OkamotoWitness<G::Scalar>, CryptoError> {
+        let group = &self.params;
+
+        Ok(OkamotoWitness {
+            a: SecretScalar::hedged(&*self.rng, group, &witness.a, &[context, b"k1"].concat())?,
+            b: SecretScalar::hedged(&*self.rng, group, &witness.b, &[context, b"k2"].concat())?,
+        })
+    }
+
+    /// `y1 = g^a·h^b`.
+    pub fn public_key(
+        &self,
+        witness: &OkamotoWitness<G::Scalar>,
+    ) -> Result<G::Element, CryptoError> {
+        self.represent(witness)
+    }
+
+    /// `r1 = g^k1·h^k2`.
+    pub fn commit(&self, k: &OkamotoWitness<G::Scalar>) -> Result<G::Element, CryptoError> {
+        self.represent(k)
+    }
+
+    pub fn challenge_response(
+        &self,
+        k: &OkamotoWitness<G::Scalar>,
+        c: &G::Scalar,
+        witness: &OkamotoWitness<G::Scalar>,
+    ) -> Result<OkamotoResponse<G::Scalar>, CryptoError> {
+        let group = &self.params;
+
+        // c * a and c * b reveal the witness to anyone who knows c, so wipe them as well
+        let ca = SecretScalar::new(group.scalar_mul(c, witness.a.expose())?);
+        let cb = SecretScalar::new(group.scalar_mul(c, witness.b.expose())?);
+
+        Ok(OkamotoResponse {
+            s1: group.scalar_sub(k.a.expose(), ca.expose())?,
+            s2: group.scalar_sub(k.b.expose(), cb.expose())?,
+        })
+    }
+
+    // g^a * h^b, with each power taken in constant time and only the results multiplied
+    fn represent(&self, pair: &OkamotoWitness<G::Scalar>) -> Result<G::Element, CryptoError> {
+        let ga = self.params.exp_g(pair.a.expose())?;
+        let hb = self.params.exp_h(pair.b.expose())?;
+        let one = self.params.reduce_scalar(&[1])?;
+
+        self.params.multi_exp(&[&ga, &hb], &[&one, &one])
+    }
+}
+
+/// The verifier's side of Okamoto identification over any `Group`.
+pub struct OkamotoVerifier<G: Group> {
+    pub params: G,
+    /// Where challenge nonces come from.
+    pub rng: Arc<dyn RandomSource>,
+}
+
+impl<G: Group> OkamotoVerifier<G> {
+    pub fn new(params: G) -> Self {
+        OkamotoVerifier::with_rng(params, default_source())
+    }
+
+    /// A verifier drawing its randomness from `rng` instead of the default source.
+    pub fn with_rng(params: G, rng: Arc<dyn RandomSource>) -> Self {
+        OkamotoVerifier { params, rng }
+    }
+
+    /// Fresh randomness for an interactive challenge, sent to the prover with `c`.
+    pub fn challenge_nonce(&self) -> Result<Vec<u8>, CryptoError> {
+        challenge_nonce(&*self.rng)
+    }
+
+    /// Challenge for an interactive run (`interactive_challenge`).
+    pub fn challenge(
+        &self,
+        y1: &G::Element,
+        r1: &G::Element,
+        nonce: &[u8],
+        transcript: &Transcript,
+    ) -> Result<G::Scalar, CryptoError> {
+        interactive_challenge(&self.params, y1, r1, nonce, transcript)
+    }
+
+    /// Checks `r1 == g^s1·h^s2·y1^c`, with `y1` and `r1` already checked with
+    /// `check_element`.
+    pub fn check(
+        &self,
+        y1: &G::Element,
+        r1: &G::Element,
+        c: &G::Scalar,
+        response: &OkamotoResponse<G::Scalar>,
+    ) -> Result<bool, CryptoError> {
+        let expected = self
+            .params
+            .multi_exp_gh(&response.s1, &response.s2, &[y1], &[c])?;
+
+        self.params.element_eq(&expected, r1)
+    }
+}
+
+/// Challenge for an interactive run: the run and the verifier's fresh `nonce`
+/// appended to `transcript`, so the prover can re-derive it like
+/// `fiat_shamir::interactive_challenge`.
+pub fn interactive_challenge<G: Group>(
+    group: &G,
+    y1: &G::Element,
+    r1: &G::Element,
+    nonce: &[u8],
+    transcript: &Transcript,
+) -> Result<G::Scalar, CryptoError> {
+    let mut transcript = transcript.clone();
+    transcript.append_message(b"protocol", PROTOCOL);
+    transcript.append_params(group)?;
+    transcript.append_element(group, b"y1", y1)?;
+    transcript.append_element(group, b"r1", r1)?;
+    transcript.append_message(b"verifier-nonce", nonce);
+
+    transcript.challenge_scalar(b"c", group)
+}
+
+impl<G: Group> IdentificationProver for OkamotoProver<G> {
+    type Secret = OkamotoWitness<G::Scalar>;
+    type PublicKey = G::Element;
+    type Nonce = OkamotoWitness<G::Scalar>;
+    type Commitment = G::Element;
+    type Response = OkamotoResponse<G::Scalar>;
+    type Scalar = G::Scalar;
+
+    fn generate_secret(&self) -> Result<OkamotoWitness<G::Scalar>, CryptoError> {
+        self.random()
+    }
+
+    // Derives x as for the other protocols and splits it into (a, b)
+    fn secret_from_password(
+        &self,
+        kdf: &PasswordKdf,
+        user: &str,
+        password: &[u8],
+        salt: &[u8],
+    ) -> Result<OkamotoWitness<G::Scalar>, PasswordError> {
+        let x = kdf.derive_secret(&self.params, user, password, salt)?;
+
+        Ok(OkamotoWitness::from_secret(&self.params, &x)?)
+    }
+
+    fn public_key(&self, witness: &OkamotoWitness<G::Scalar>) -> Result<G::Element, CryptoError> {
+        OkamotoProver::public_key(self, witness)
+    }
+
+    fn commitment(
+        &self,
+        witness: &OkamotoWitness<G::Scalar>,
+        context: &[u8],
+    ) -> Result<(OkamotoWitness<G::Scalar>, G::Element), CryptoError> {
+        let k = self.hedged_nonce(witness, context)?;
+        let r1 = self.commit(&k)?;
+
+        Ok((k, r1))
+    }
+
+    fn accepts_challenge(
+        &self,
+        y1: &G::Element,
+        r1: &G::Element,
+        challenge: &Challenge<G::Scalar>,
+        transcript: &Transcript,
+    ) -> Result<bool, CryptoError> {
+        let expected = interactive_challenge(&self.params, y1, r1, &challenge.nonce, transcript)?;
+
+        Ok(challenge.c.as_ref() == Some(&expected))
+    }
+
+    fn respond(
+        &self,
+        witness: &OkamotoWitness<G::Scalar>,
+        k: &OkamotoWitness<G::Scalar>,
+        challenge: &Challenge<G::Scalar>,
+        _transcript: &Transcript,
+    ) -> Result<OkamotoResponse<G::Scalar>, CryptoError> {
+        self.challenge_response(k, challenge.scalar()?, witness)
+    }
+}
+
+impl<G: Group> IdentificationVerifier for OkamotoVerifier<G> {
+    type PublicKey = G::Element;
+    type Commitment = G::Element;
+    type Response = OkamotoResponse<G::Scalar>;
+    type Scalar = G::Scalar;
+
+    fn challenge(
+        &self,
+        y1: &G::Element,
+        r1: &G::Element,
+        transcript: &Transcript,
+    ) -> Result<Challenge<G::Scalar>, CryptoError> {
+        let nonce = self.challenge_nonce()?;
+        let c = OkamotoVerifier::challenge(self, y1, r1, &nonce, transcript)?;
+
+        Ok(Challenge { c: Some(c), nonce })
+    }
+
+    fn decoy_challenge(
+        &self,
+        transcript: &Transcript,
+    ) -> Result<Challenge<G::Scalar>, CryptoError> {
+        identification::decoy_challenge(&*self.rng, &self.params, transcript)
+    }
+
+    fn check(
+        &self,
+        y1: &G::Element,
+        r1: &G::Element,
+        challenge: &Challenge<G::Scalar>,
+        response: &OkamotoResponse<G::Scalar>,
+        _transcript: &Transcript,
+    ) -> Result<bool, CryptoError> {
+        OkamotoVerifier::check(self, y1, r1, challenge.scalar()?, response)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "openssl")]
+    use openssl::nid::Nid;
+
+    use super::*;
+    #[cfg(feature = "openssl")]
+    use crate::dl::{groups::NamedGroup, params::DlParams};
+    #[cfg(feature = "openssl")]
+    use crate::ec::params::EcParams;
+    use crate::ristretto::params::RistrettoParams;
+
+    fn check_okamoto<G: Group>(group: G) -> Result<(), CryptoError> {
+        let prover = OkamotoProver::new(group.clone());
+        let verifier = OkamotoVerifier::new(group.clone());
+        let session = Transcript::new(b"test");
+
+        let witness = prover.random()?;
+        let y1 = prover.public_key(&witness)?;
+        let k = prover.hedged_nonce(&witness, b"session")?;
+        let r1 = prover.commit(&k)?;
+
+        let nonce = verifier.challenge_nonce()?;
+        let c = verifier.challenge(&y1, &r1, &nonce, &session)?;
+        assert_eq!(
+            c,
+            interactive_challenge(&group, &y1, &r1, &nonce, &session)?
+        );
+        let response = prover.challenge_response(&k, &c, &witness)?;
+        assert!(verifier.check(&y1, &r1, &c, &response)?);
+
+        // Wrong witness, tampered responses, wrong commitment
+        let forged = prover.challenge_response(&k, &c, &prover.random()?)?;
+        assert!(!verifier.check(&y1, &r1, &c, &forged)?);
+        let tampered = OkamotoResponse {
+            s1: response.s1.try_clone()?,
+            s2: group.scalar_add(&response.s2, &group.reduce_scalar(&[1])?)?,
+        };
+        assert!(!verifier.check(&y1, &r1, &c, &tampered)?);
+        assert!(!verifier.check(&y1, &prover.commit(&prover.random()?)?, &c, &response)?);
+
+        // A split password secret is deterministic and uses both bases
+        let x = SecretScalar::random(&*prover.rng, &group)?;
+        let split = OkamotoWitness::from_secret(&group, &x)?;
+        let again = OkamotoWitness::from_secret(&group, &x)?;
+        assert_eq!(split.a.expose(), again.a.expose());
+        assert_eq!(split.b.expose(), again.b.expose());
+        assert_ne!(split.a.expose(), split.b.expose());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_okamoto() -> Result<(), CryptoError> {
+        #[cfg(feature = "openssl")]
+        {
+            check_okamoto(DlParams::from_named_group(NamedGroup::Ffdhe2048, b"seed")?)?;
+            check_okamoto(EcParams::new(Nid::X9_62_PRIME256V1)?)?;
+        }
+        check_okamoto(RistrettoParams::new()?)
+    }
+}
@@ -0,0 +1,241 @@
+use std::sync::Arc;
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use openssl::memcmp;
+use zeroize::Zeroizing;
+
+use crate::error::OpaqueError;
+use crate::random::{default_source, RandomSource};
+use crate::util::hmac_sha512;
+
+use super::messages::{
+    read_credential_response, Envelope, Ke1, Ke2, Ke3, RegistrationRecord, RegistrationRequest,
+    RegistrationResponse,
+};
+use super::{
+    cleartext_credentials, credential_response_pad, encode, expand, extract, oprf, preamble,
+    random_key_pair, random_nonce, ExportKey, Keys, Ksf, SessionKey, CONTEXT, DH_KEY_PAIR_INFO, NH,
+    NM, NSEED,
+};
+
+/// What the client keeps between its two registration messages.
+pub struct ClientRegistrationState {
+    password: Zeroizing<Vec<u8>>,
+    blind: Zeroizing<Scalar>,
+}
+
+/// A finished registration: the record to upload, and the export key.
+pub struct ClientRegistration {
+    pub record: RegistrationRecord,
+    pub export_key: ExportKey,
+}
+
+/// What the client keeps between `Ke1` and `Ke2`.
+pub struct ClientLoginState {
+    password: Zeroizing<Vec<u8>>,
+    blind: Zeroizing<Scalar>,
+    client_secret: Zeroizing<Scalar>,
+    ke1: Ke1,
+}
+
+/// A finished login: `Ke3` for the server, and the keys only the two of them hold.
+pub struct ClientLogin {
+    pub ke3: Ke3,
+    pub session_key: SessionKey,
+    pub export_key: ExportKey,
+}
+
+// The client's long-term key pair and the export key, recovered from an envelope
+struct EnvelopeKeys {
+    auth_tag: [u8; NM],
+    client_private_key: Zeroizing<Scalar>,
+    client_public_key: RistrettoPoint,
+    export_key: ExportKey,
+}
+
+/// The client's side of OPAQUE.
+pub struct OpaqueClient {
+    /// What the OPRF output is stretched with, such as Argon2id costs; must
+    /// match across devices.
+    pub ksf: Box<dyn Ksf>,
+    /// Where blinds, nonces and ephemeral keys come from.
+    pub rng: Arc<dyn RandomSource>,
+    /// Must be the server's.
+    pub context: Vec<u8>,
+}
+
+impl OpaqueClient {
+    pub fn new(ksf: impl Ksf + 'static) -> Self {
+        OpaqueClient::with_rng(ksf, default_source())
+    }
+
+    /// A client drawing its randomness from `rng` instead of the default source.
+    pub fn with_rng(ksf: impl Ksf + 'static, rng: Arc<dyn RandomSource>) -> Self {
+        OpaqueClient {
+            ksf: Box::new(ksf),
+            rng,
+            context: CONTEXT.to_vec(),
+        }
+    }
+
+    /// Blinds `password` for the server's OPRF.
+    pub fn registration_start(
+        &self,
+        password: &[u8],
+    ) -> Result<(RegistrationRequest, ClientRegistrationState), OpaqueError> {
+        let (blind, blinded_message) = oprf::blind(password, &*self.rng)?;
+        let state = ClientRegistrationState {
+            password: Zeroizing::new(password.to_vec()),
+            blind,
+        };
+
+        Ok((RegistrationRequest { blinded_message }, state))
+    }
+
+    /// Seals a fresh envelope under the OPRF output, giving the record the
+    /// server stores.
+    pub fn registration_finish(
+        &self,
+        state: ClientRegistrationState,
+        response: &RegistrationResponse,
+    ) -> Result<ClientRegistration, OpaqueError> {
+        let randomized_password =
+            self.randomized_password(&state.password, &state.blind, &response.evaluated_message)?;
+
+        let nonce = random_nonce(&*self.rng)?;
+        let keys = envelope_keys(&randomized_password, &nonce, &response.server_public_key)?;
+        let mut masking_key = Zeroizing::new([0u8; NH]);
+        masking_key.copy_from_slice(&expand(&randomized_password, &[b"MaskingKey"], NH)?);
+
+        let record = RegistrationRecord {
+            client_public_key: keys.client_public_key,
+            masking_key,
+            envelope: Envelope {
+                nonce,
+                auth_tag: keys.auth_tag,
+            },
+        };
+
+        Ok(ClientRegistration {
+            record,
+            export_key: keys.export_key,
+        })
+    }
+
+    /// Blinds `password` and starts the key exchange with a fresh key share.
+    pub fn login_start(&self, password: &[u8]) -> Result<(Ke1, ClientLoginState), OpaqueError> {
+        let (blind, blinded_message) = oprf::blind(password, &*self.rng)?;
+        let (client_secret, client_public_keyshare) = random_key_pair(&*self.rng)?;
+
+        let ke1 = Ke1 {
+            blinded_message,
+            client_nonce: random_nonce(&*self.rng)?,
+            client_public_keyshare,
+        };
+        let state = ClientLoginState {
+            password: Zeroizing::new(password.to_vec()),
+            blind,
+            client_secret,
+            ke1: ke1.clone(),
+        };
+
+        Ok((ke1, state))
+    }
+
+    /// Opens the envelope in `ke2`, checks the server's MAC and answers with
+    /// `Ke3`. A wrong password fails with `EnvelopeRecovery`, a server that
+    /// doesn't hold the record with `EnvelopeRecovery` or `ServerAuthentication`.
+    pub fn login_finish(
+        &self,
+        state: ClientLoginState,
+        ke2: &Ke2,
+    ) -> Result<ClientLogin, OpaqueError> {
+        let randomized_password =
+            self.randomized_password(&state.password, &state.blind, &ke2.evaluated_message)?;
+
+        // Unmask the server's public key and the envelope
+        let masking_key = expand(&randomized_password, &[b"MaskingKey"], NH)?;
+        let pad = credential_response_pad(&masking_key, &ke2.masking_nonce)?;
+        let plaintext: Zeroizing<Vec<u8>> = Zeroizing::new(
+            pad.iter()
+                .zip(ke2.masked_response)
+                .map(|(pad, masked)| pad ^ masked)
+                .collect(),
+        );
+        let (server_public_key, envelope) =
+            read_credential_response(&plaintext).map_err(|_| OpaqueError::EnvelopeRecovery)?;
+
+        let keys = envelope_keys(&randomized_password, &envelope.nonce, &server_public_key)?;
+        if !memcmp::eq(&keys.auth_tag, &envelope.auth_tag) {
+            return Err(OpaqueError::EnvelopeRecovery);
+        }
+
+        let preamble = preamble(
+            &self.context,
+            &encode(&keys.client_public_key),
+            &state.ke1,
+            &encode(&server_public_key),
+            ke2,
+        );
+        let exchange = Keys::derive(
+            [
+                *state.client_secret * ke2.server_public_keyshare,
+                *state.client_secret * server_public_key,
+                *keys.client_private_key * ke2.server_public_keyshare,
+            ],
+            &preamble,
+        )?;
+
+        if !memcmp::eq(&exchange.server_mac(&preamble), &ke2.server_mac) {
+            return Err(OpaqueError::ServerAuthentication);
+        }
+
+        Ok(ClientLogin {
+            ke3: Ke3 {
+                client_mac: exchange.client_mac(&preamble, &ke2.server_mac),
+            },
+            session_key: exchange.session_key,
+            export_key: keys.export_key,
+        })
+    }
+
+    // Extract(oprf_output || Stretch(oprf_output)): the key everything the
+    // client derives from the password hangs off
+    fn randomized_password(
+        &self,
+        password: &[u8],
+        blind: &Scalar,
+        evaluated: &RistrettoPoint,
+    ) -> Result<Zeroizing<Vec<u8>>, OpaqueError> {
+        let output = oprf::finalize(password, blind, evaluated)?;
+        let mut stretched = Zeroizing::new([0u8; NH]);
+        self.ksf.stretch(&*output, &mut *stretched)?;
+
+        let ikm = Zeroizing::new([output.as_slice(), stretched.as_slice()].concat());
+
+        Ok(extract(&ikm)?)
+    }
+}
+
+// The keys an envelope with `nonce` is made of, and the MAC that seals it
+fn envelope_keys(
+    randomized_password: &[u8],
+    nonce: &[u8],
+    server_public_key: &RistrettoPoint,
+) -> Result<EnvelopeKeys, OpaqueError> {
+    let auth_key = expand(randomized_password, &[nonce, b"AuthKey"], NH)?;
+    let export_key = expand(randomized_password, &[nonce, b"ExportKey"], NH)?;
+    let seed = expand(randomized_password, &[nonce, b"PrivateKey"], NSEED)?;
+    let (client_private_key, client_public_key) = oprf::derive_key_pair(&seed, DH_KEY_PAIR_INFO)?;
+
+    let cleartext = cleartext_credentials(&encode(server_public_key), &encode(&client_public_key));
+    let auth_tag = hmac_sha512(&auth_key, &[nonce, &cleartext]);
+
+    Ok(EnvelopeKeys {
+        auth_tag,
+        client_private_key,
+        client_public_key,
+        export_key,
+    })
+}
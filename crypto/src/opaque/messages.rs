@@ -0,0 +1,273 @@
+//! OPAQUE's messages and their fixed-length RFC 9807 serializations.
+//! Deserialization rejects wrong lengths, invalid encodings and the identity.
+
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::traits::Identity;
+use zeroize::Zeroizing;
+
+use crate::error::OpaqueError;
+
+use super::{encode, NH, NM, NN, NPK};
+
+/// Size of an envelope: its nonce and MAC.
+pub const ENVELOPE_LEN: usize = NN + NM;
+
+/// Size of the masked server public key and envelope in a `Ke2`.
+pub const MASKED_RESPONSE_LEN: usize = NPK + ENVELOPE_LEN;
+
+// Reads fixed-size fields off the front of a message
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn bytes<const N: usize>(&mut self) -> Result<[u8; N], OpaqueError> {
+        if self.0.len() < N {
+            return Err(OpaqueError::InvalidMessage);
+        }
+        let mut field = [0u8; N];
+        field.copy_from_slice(&self.0[..N]);
+        self.0 = &self.0[N..];
+
+        Ok(field)
+    }
+
+    fn element(&mut self) -> Result<RistrettoPoint, OpaqueError> {
+        let element = CompressedRistretto(self.bytes()?)
+            .decompress()
+            .ok_or(OpaqueError::InvalidMessage)?;
+        if element == RistrettoPoint::identity() {
+            return Err(OpaqueError::InvalidMessage);
+        }
+
+        Ok(element)
+    }
+
+    fn finish(self) -> Result<(), OpaqueError> {
+        match self.0.is_empty() {
+            true => Ok(()),
+            false => Err(OpaqueError::InvalidMessage),
+        }
+    }
+}
+
+/// The client's blinded password, the first registration message.
+pub struct RegistrationRequest {
+    pub blinded_message: RistrettoPoint,
+}
+
+impl RegistrationRequest {
+    pub fn serialize(&self) -> Vec<u8> {
+        encode(&self.blinded_message).to_vec()
+    }
+
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, OpaqueError> {
+        let mut reader = Reader(bytes);
+        let request = RegistrationRequest {
+            blinded_message: reader.element()?,
+        };
+        reader.finish()?;
+
+        Ok(request)
+    }
+}
+
+/// The server's OPRF evaluation and its public key.
+pub struct RegistrationResponse {
+    pub evaluated_message: RistrettoPoint,
+    pub server_public_key: RistrettoPoint,
+}
+
+impl RegistrationResponse {
+    pub fn serialize(&self) -> Vec<u8> {
+        [
+            encode(&self.evaluated_message),
+            encode(&self.server_public_key),
+        ]
+        .concat()
+    }
+
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, OpaqueError> {
+        let mut reader = Reader(bytes);
+        let response = RegistrationResponse {
+            evaluated_message: reader.element()?,
+            server_public_key: reader.element()?,
+        };
+        reader.finish()?;
+
+        Ok(response)
+    }
+}
+
+/// Where the client's private key is recovered from: the nonce it is
+/// derived under, and a MAC over the server's public key and the identities.
+#[derive(Clone)]
+pub struct Envelope {
+    pub nonce: [u8; NN],
+    pub auth_tag: [u8; NM],
+}
+
+impl Envelope {
+    pub fn serialize(&self) -> Vec<u8> {
+        [self.nonce.as_slice(), &self.auth_tag].concat()
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, OpaqueError> {
+        Ok(Envelope {
+            nonce: reader.bytes()?,
+            auth_tag: reader.bytes()?,
+        })
+    }
+}
+
+/// What the server stores for a user once registration is done.
+#[derive(Clone)]
+pub struct RegistrationRecord {
+    pub client_public_key: RistrettoPoint,
+    pub masking_key: Zeroizing<[u8; NH]>,
+    pub envelope: Envelope,
+}
+
+impl RegistrationRecord {
+    pub fn serialize(&self) -> Vec<u8> {
+        [
+            encode(&self.client_public_key).as_slice(),
+            self.masking_key.as_slice(),
+            &self.envelope.serialize(),
+        ]
+        .concat()
+    }
+
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, OpaqueError> {
+        let mut reader = Reader(bytes);
+        let record = RegistrationRecord {
+            client_public_key: reader.element()?,
+            masking_key: Zeroizing::new(reader.bytes()?),
+            envelope: Envelope::read(&mut reader)?,
+        };
+        reader.finish()?;
+
+        Ok(record)
+    }
+}
+
+/// The client's first login message: its blinded password, a nonce and an
+/// ephemeral key share.
+#[derive(Clone)]
+pub struct Ke1 {
+    pub blinded_message: RistrettoPoint,
+    pub client_nonce: [u8; NN],
+    pub client_public_keyshare: RistrettoPoint,
+}
+
+impl Ke1 {
+    pub fn serialize(&self) -> Vec<u8> {
+        [
+            encode(&self.blinded_message).as_slice(),
+            &self.client_nonce,
+            &encode(&self.client_public_keyshare),
+        ]
+        .concat()
+    }
+
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, OpaqueError> {
+        let mut reader = Reader(bytes);
+        let ke1 = Ke1 {
+            blinded_message: reader.element()?,
+            client_nonce: reader.bytes()?,
+            client_public_keyshare: reader.element()?,
+        };
+        reader.finish()?;
+
+        Ok(ke1)
+    }
+}
+
+/// The server's login message: the OPRF evaluation, its public key and the
+/// envelope masked under the record's masking key, then its nonce, ephemeral
+/// key share and MAC.
+pub struct Ke2 {
+    pub evaluated_message: RistrettoPoint,
+    pub masking_nonce: [u8; NN],
+    pub masked_response: [u8; MASKED_RESPONSE_LEN],
+    pub server_nonce: [u8; NN],
+    pub server_public_keyshare: RistrettoPoint,
+    pub server_mac: [u8; NM],
+}
+
+impl Ke2 {
+    /// The credential response, the part of `Ke2` that comes from the record.
+    pub fn credential_response(&self) -> Vec<u8> {
+        [
+            encode(&self.evaluated_message).as_slice(),
+            &self.masking_nonce,
+            &self.masked_response,
+        ]
+        .concat()
+    }
+
+    pub fn serialize(&self) -> Vec<u8> {
+        [
+            self.credential_response().as_slice(),
+            &self.server_nonce,
+            &encode(&self.server_public_keyshare),
+            &self.server_mac,
+        ]
+        .concat()
+    }
+
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, OpaqueError> {
+        let mut reader = Reader(bytes);
+        let ke2 = Ke2 {
+            evaluated_message: reader.element()?,
+            masking_nonce: reader.bytes()?,
+            masked_response: reader.bytes()?,
+            server_nonce: reader.bytes()?,
+            server_public_keyshare: reader.element()?,
+            server_mac: reader.bytes()?,
+        };
+        reader.finish()?;
+
+        Ok(ke2)
+    }
+}
+
+/// The client's MAC, which completes the login.
+pub struct Ke3 {
+    pub client_mac: [u8; NM],
+}
+
+impl Ke3 {
+    pub fn serialize(&self) -> Vec<u8> {
+        self.client_mac.to_vec()
+    }
+
+    pub fn deserialize(bytes: &[u8]) -> Result<Self, OpaqueError> {
+        let mut reader = Reader(bytes);
+        let ke3 = Ke3 {
+            client_mac: reader.bytes()?,
+        };
+        reader.finish()?;
+
+        Ok(ke3)
+    }
+}
+
+/// The server's public key and envelope, as masked in `Ke2`.
+pub(super) fn credential_response_plaintext(
+    server_public_key: &RistrettoPoint,
+    envelope: &Envelope,
+) -> Zeroizing<Vec<u8>> {
+    Zeroizing::new([encode(server_public_key).to_vec(), envelope.serialize()].concat())
+}
+
+/// Splits an unmasked credential response back into the server's public key
+/// and the envelope.
+pub(super) fn read_credential_response(
+    plaintext: &[u8],
+) -> Result<(RistrettoPoint, Envelope), OpaqueError> {
+    let mut reader = Reader(plaintext);
+    let server_public_key = reader.element()?;
+    let envelope = Envelope::read(&mut reader)?;
+    reader.finish()?;
+
+    Ok((server_public_key, envelope))
+}
@@ -0,0 +1,529 @@
+//! OPAQUE (RFC 9807), an asymmetric password-authenticated key exchange:
+//! the user logs in with nothing but a password, and both sides end up with
+//! the same session key. The server stores a record it cannot attack offline.
+//! It holds no password hash, only an envelope sealed under the output of an
+//! OPRF keyed by the server, so every password guess takes an online run. The
+//! login is a 3DH key exchange, with the client's long-term key recovered
+//! from the envelope.
+//!
+//! This is the ristretto255-SHA512 configuration: the OPRF suite of RFC 9497
+//! over ristretto255, HKDF-SHA-512 as the KDF, HMAC-SHA-512 as the MAC,
+//! SHA-512 as the hash and Argon2id (`PasswordKdf`, with the RFC's all-zero
+//! salt) as the key stretching function, which `Ksf` lets tests swap for the
+//! identity of the RFC's test vectors. Identities are left at their defaults,
+//! the public keys, and the user name is the credential identifier.
+
+pub mod client;
+pub mod messages;
+pub mod oprf;
+pub mod server;
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use openssl::error::ErrorStack;
+use openssl::md::Md;
+use openssl::pkey::Id;
+use openssl::pkey_ctx::{HkdfMode, PkeyCtx};
+use openssl::sha::sha512;
+use zeroize::Zeroizing;
+
+use crate::error::{CryptoError, OpaqueError};
+use crate::password::PasswordKdf;
+use crate::random::RandomSource;
+use crate::util::hmac_sha512;
+
+/// Nonce size.
+pub const NN: usize = 32;
+/// Hash, KDF and MAC output size, and the size of the OPRF seed.
+pub const NH: usize = 64;
+/// Serialized public key and element size.
+pub const NPK: usize = 32;
+/// MAC size.
+pub const NM: usize = 64;
+/// Size of the keys derived in the key exchange.
+pub const NX: usize = 64;
+/// Size of the seeds key pairs are derived from.
+pub const NSEED: usize = 32;
+/// Size of the seed a user's OPRF key is derived from.
+pub const NOK: usize = 32;
+
+/// Key the login ends with, the same on both sides.
+pub type SessionKey = Zeroizing<Vec<u8>>;
+
+/// Key only the client can derive, for encrypting data of its own, such as
+/// a backup of other secrets, under the password.
+pub type ExportKey = Zeroizing<Vec<u8>>;
+
+/// Binds the key exchange to this application, unless the client and server
+/// are given another context.
+pub const CONTEXT: &[u8] = b"zkp_auth/opaque/v1";
+
+// The RFC's Argon2id configuration uses a fixed all-zero salt
+const KSF_SALT: [u8; 16] = [0; 16];
+
+/// The key stretching function the OPRF output is hardened with before
+/// anything is derived from it, so each guess against a stolen record costs
+/// as much as the client's own login.
+pub trait Ksf: Send + Sync {
+    /// Stretches `input` into `output`, which is as long as `input`.
+    fn stretch(&self, input: &[u8], output: &mut [u8]) -> Result<(), OpaqueError>;
+}
+
+impl Ksf for PasswordKdf {
+    fn stretch(&self, input: &[u8], output: &mut [u8]) -> Result<(), OpaqueError> {
+        Ok(PasswordKdf::stretch(self, input, &KSF_SALT, output)?)
+    }
+}
+
+/// The identity KSF the RFC's test vectors are computed with. It adds no
+/// cost to guessing, so it is for known-answer tests only.
+pub struct IdentityKsf;
+
+impl Ksf for IdentityKsf {
+    fn stretch(&self, input: &[u8], output: &mut [u8]) -> Result<(), OpaqueError> {
+        if input.len() != output.len() {
+            return Err(OpaqueError::InvalidInput);
+        }
+        output.copy_from_slice(input);
+
+        Ok(())
+    }
+}
+
+const DH_KEY_PAIR_INFO: &[u8] = b"OPAQUE-DeriveDiffieHellmanKeyPair";
+
+// HKDF-Extract with the empty salt OPAQUE always uses
+fn extract(ikm: &[u8]) -> Result<Zeroizing<Vec<u8>>, ErrorStack> {
+    let mut ctx = PkeyCtx::new_id(Id::HKDF)?;
+    ctx.derive_init()?;
+    ctx.set_hkdf_mode(HkdfMode::EXTRACT_ONLY)?;
+    ctx.set_hkdf_md(Md::sha512())?;
+    ctx.set_hkdf_key(ikm)?;
+
+    let mut prk = Zeroizing::new(vec![0u8; NH]);
+    ctx.derive(Some(&mut prk))?;
+
+    Ok(prk)
+}
+
+// HKDF-Expand of `prk` to `len` bytes, with the concatenated `info`
+fn expand(prk: &[u8], info: &[&[u8]], len: usize) -> Result<Zeroizing<Vec<u8>>, ErrorStack> {
+    let mut ctx = PkeyCtx::new_id(Id::HKDF)?;
+    ctx.derive_init()?;
+    ctx.set_hkdf_mode(HkdfMode::EXPAND_ONLY)?;
+    ctx.set_hkdf_md(Md::sha512())?;
+    ctx.set_hkdf_key(prk)?;
+    ctx.add_hkdf_info(&info.concat())?;
+
+    let mut okm = Zeroizing::new(vec![0u8; len]);
+    ctx.derive(Some(&mut okm))?;
+
+    Ok(okm)
+}
+
+// Derive-Secret: Expand-Label to NX bytes, with the label prefixed by "OPAQUE-"
+fn derive_secret(
+    secret: &[u8],
+    label: &[u8],
+    transcript_hash: &[u8],
+) -> Result<Zeroizing<Vec<u8>>, ErrorStack> {
+    let label = [b"OPAQUE-", label].concat();
+
+    expand(
+        secret,
+        &[
+            &(NX as u16).to_be_bytes(),
+            &[label.len() as u8],
+            &label,
+            &[transcript_hash.len() as u8],
+            transcript_hash,
+        ],
+        NX,
+    )
+}
+
+fn random_nonce(rng: &dyn RandomSource) -> Result<[u8; NN], CryptoError> {
+    let mut nonce = [0u8; NN];
+    rng.fill_bytes(&mut nonce)?;
+
+    Ok(nonce)
+}
+
+// A fresh ephemeral key pair, derived from a random seed as the RFC does
+fn random_key_pair(
+    rng: &dyn RandomSource,
+) -> Result<(Zeroizing<Scalar>, RistrettoPoint), OpaqueError> {
+    let mut seed = Zeroizing::new([0u8; NSEED]);
+    rng.fill_bytes(&mut *seed)?;
+
+    oprf::derive_key_pair(&*seed, DH_KEY_PAIR_INFO)
+}
+
+fn encode(element: &RistrettoPoint) -> [u8; NPK] {
+    element.compress().to_bytes()
+}
+
+// The pad the server's public key and the envelope are masked with, so only
+// the holder of the password can see them, or tell a real record from a fake one
+fn credential_response_pad(
+    masking_key: &[u8],
+    masking_nonce: &[u8; NN],
+) -> Result<Zeroizing<Vec<u8>>, ErrorStack> {
+    expand(
+        masking_key,
+        &[masking_nonce, b"CredentialResponsePad"],
+        messages::MASKED_RESPONSE_LEN,
+    )
+}
+
+// What the envelope authenticates: the server's public key and both
+// identities, which default to the public keys
+fn cleartext_credentials(server_public_key: &[u8; NPK], client_public_key: &[u8; NPK]) -> Vec<u8> {
+    let server_identity = server_public_key;
+    let client_identity = client_public_key;
+
+    [
+        server_public_key.as_slice(),
+        &(server_identity.len() as u16).to_be_bytes(),
+        server_identity,
+        &(client_identity.len() as u16).to_be_bytes(),
+        client_identity,
+    ]
+    .concat()
+}
+
+// Everything both sides have seen up to the server's MAC
+fn preamble(
+    context: &[u8],
+    client_public_key: &[u8; NPK],
+    ke1: &messages::Ke1,
+    server_public_key: &[u8; NPK],
+    ke2: &messages::Ke2,
+) -> Vec<u8> {
+    let (client_identity, server_identity) = (client_public_key, server_public_key);
+
+    [
+        b"OPAQUEv1-".as_slice(),
+        &(context.len() as u16).to_be_bytes(),
+        context,
+        &(client_identity.len() as u16).to_be_bytes(),
+        client_identity,
+        &ke1.serialize(),
+        &(server_identity.len() as u16).to_be_bytes(),
+        server_identity,
+        &ke2.credential_response(),
+        &ke2.server_nonce,
+        &encode(&ke2.server_public_keyshare),
+    ]
+    .concat()
+}
+
+// The MAC keys and session key of one key exchange
+struct Keys {
+    km2: Zeroizing<Vec<u8>>,
+    km3: Zeroizing<Vec<u8>>,
+    session_key: SessionKey,
+}
+
+impl Keys {
+    // The three Diffie-Hellman shares, as serialized elements, and the preamble
+    fn derive(dh: [RistrettoPoint; 3], preamble: &[u8]) -> Result<Keys, ErrorStack> {
+        let ikm = Zeroizing::new(dh.iter().flat_map(encode).collect::<Vec<u8>>());
+        let preamble_hash = sha512(preamble);
+
+        let prk = extract(&ikm)?;
+        let handshake_secret = derive_secret(&prk, b"HandshakeSecret", &preamble_hash)?;
+
+        Ok(Keys {
+            km2: derive_secret(&handshake_secret, b"ServerMAC", &[])?,
+            km3: derive_secret(&handshake_secret, b"ClientMAC", &[])?,
+            session_key: derive_secret(&prk, b"SessionKey", &preamble_hash)?,
+        })
+    }
+
+    fn server_mac(&self, preamble: &[u8]) -> [u8; NM] {
+        hmac_sha512(&self.km2, &[&sha512(preamble)])
+    }
+
+    fn client_mac(&self, preamble: &[u8], server_mac: &[u8; NM]) -> [u8; NM] {
+        hmac_sha512(&self.km3, &[&sha512(&[preamble, server_mac].concat())])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+    use std::sync::{Arc, Mutex};
+
+    use super::client::OpaqueClient;
+    use super::messages::{Ke1, Ke2, Ke3, RegistrationRecord};
+    use super::server::{OpaqueServer, ServerSetup};
+    use super::*;
+    use crate::password::PasswordKdf;
+    use crate::random::OpenSslRandom;
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    // Hands out the vector's blinds, nonces and seeds in the order they are
+    // drawn. A 32-byte blind fills the low half of the 64 bytes a scalar is
+    // reduced from, which gives back the blind.
+    struct Script(Mutex<VecDeque<Vec<u8>>>);
+
+    impl Script {
+        fn new(values: &[&str]) -> Arc<Script> {
+            Arc::new(Script(Mutex::new(values.iter().map(|v| hex(v)).collect())))
+        }
+    }
+
+    impl RandomSource for Script {
+        fn fill_bytes(&self, dest: &mut [u8]) -> Result<(), CryptoError> {
+            let value = self.0.lock().unwrap().pop_front().expect("script ran out");
+            dest.fill(0);
+            dest[..value.len()].copy_from_slice(&value);
+
+            Ok(())
+        }
+    }
+
+    // Cheap costs; the protocol doesn't depend on them
+    const KDF: PasswordKdf = PasswordKdf {
+        m_cost: 64,
+        t_cost: 1,
+        p_cost: 1,
+    };
+
+    fn server() -> Result<OpaqueServer, OpaqueError> {
+        Ok(OpaqueServer::new(ServerSetup::generate(&OpenSslRandom)?))
+    }
+
+    fn register(
+        client: &OpaqueClient,
+        server: &OpaqueServer,
+        user: &[u8],
+        password: &[u8],
+    ) -> Result<(RegistrationRecord, ExportKey), OpaqueError> {
+        let (request, state) = client.registration_start(password)?;
+        let response = server.registration_response(&request, user)?;
+        let registration = client.registration_finish(state, &response)?;
+
+        Ok((registration.record, registration.export_key))
+    }
+
+    // A login of `user` with `password` against `record`, through serialized messages
+    fn login(
+        client: &OpaqueClient,
+        server: &OpaqueServer,
+        record: Option<&RegistrationRecord>,
+        user: &[u8],
+        password: &[u8],
+    ) -> Result<(SessionKey, SessionKey, ExportKey), OpaqueError> {
+        let (ke1, client_state) = client.login_start(password)?;
+        let ke1 = Ke1::deserialize(&ke1.serialize())?;
+        let (ke2, server_state) = server.login_start(record, user, &ke1)?;
+        let ke2 = Ke2::deserialize(&ke2.serialize())?;
+        let login = client.login_finish(client_state, &ke2)?;
+        let ke3 = Ke3::deserialize(&login.ke3.serialize())?;
+        let server_key = server.login_finish(server_state, &ke3)?;
+
+        Ok((login.session_key, server_key, login.export_key))
+    }
+
+    #[test]
+    fn test_opaque() -> Result<(), OpaqueError> {
+        let client = OpaqueClient::new(KDF);
+        let server = server()?;
+
+        let (record, export_key) = register(&client, &server, b"alice", b"hunter2")?;
+        let record = RegistrationRecord::deserialize(&record.serialize())?;
+        let (client_key, server_key, login_export_key) =
+            login(&client, &server, Some(&record), b"alice", b"hunter2")?;
+        assert_eq!(*client_key, *server_key);
+        assert_eq!(client_key.len(), NX);
+        assert_eq!(*export_key, *login_export_key);
+
+        // Every login agrees on a fresh key
+        let (again, _, _) = login(&client, &server, Some(&record), b"alice", b"hunter2")?;
+        assert_ne!(*client_key, *again);
+
+        // A wrong password, or a record under another user's OPRF key, doesn't open the envelope
+        for (user, password) in [(&b"alice"[..], &b"hunter3"[..]), (b"bob", b"hunter2")] {
+            assert!(matches!(
+                login(&client, &server, Some(&record), user, password),
+                Err(OpaqueError::EnvelopeRecovery)
+            ));
+        }
+
+        // Unknown users get a fake record that fails the same way
+        assert!(matches!(
+            login(&client, &server, None, b"carol", b"hunter2"),
+            Err(OpaqueError::EnvelopeRecovery)
+        ));
+
+        // Another server with the same record can't complete the key exchange
+        assert!(login(
+            &client,
+            &self::server()?,
+            Some(&record),
+            b"alice",
+            b"hunter2"
+        )
+        .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_opaque_vectors() -> Result<(), OpaqueError> {
+        // RFC 9807, Appendix C.1.1: OPAQUE-3DH Real Test Vector 1, with the
+        // identity KSF and default identities
+        let password = hex("436f7272656374486f72736542617474657279537461706c65");
+        let credential_identifier = hex("31323334");
+        let blind_registration = "76cfbfe758db884bebb33582331ba9f159720ca8784a2a070a265d9c2d6abe01";
+        let envelope_nonce = "ac13171b2f17bc2c74997f0fce1e1f35bec6b91fe2e12dbd323d23ba7a38dfec";
+        let masking_nonce = "38fe59af0df2c79f57b8780278f5ae47355fe1f817119041951c80f612fdfc6d";
+        let server_nonce = "71cd9960ecef2fe0d0f7494986fa3d8b2bb01963537e60efb13981e138e3d4a1";
+        let server_keyshare_seed =
+            "05a4f54206eef1ba2f615bc0aa285cb22f26d1153b5b40a1e85ff80da12f982f";
+
+        let oprf_seed = hex(concat!(
+            "f433d0227b0b9dd54f7c4422b600e764e47fb503f1f9a0f0a47c6606b054a7fd",
+            "c65347f1a08f277e22358bbabe26f823fca82c7848e9a75661f4ec5d5c1989ef"
+        ));
+        let private_key = hex("47451a85372f8b3537e249d7b54188091fb18edde78094b43e2ba42b5eb89f0d");
+        let setup = ServerSetup::from_keys(
+            oprf_seed.try_into().unwrap(),
+            Scalar::from_canonical_bytes(private_key.try_into().unwrap()).unwrap(),
+            [0; NH],
+        );
+        assert_eq!(
+            encode(&setup.public_key).to_vec(),
+            hex("b2fe7af9f48cc502d016729d2fe25cdd433f2c4bc904660b2a382c9b79df1a78")
+        );
+
+        let context = hex("4f50415155452d504f43");
+        let mut client = OpaqueClient::with_rng(
+            IdentityKsf,
+            Script::new(&[blind_registration, envelope_nonce]),
+        );
+        client.context = context.clone();
+        let mut server = OpaqueServer::with_rng(
+            setup,
+            Script::new(&[masking_nonce, server_keyshare_seed, server_nonce]),
+        );
+        server.context = context;
+
+        let (request, state) = client.registration_start(&password)?;
+        assert_eq!(
+            request.serialize(),
+            hex("5059ff249eb1551b7ce4991f3336205bde44a105a032e747d21bf382e75f7a71")
+        );
+        let response = server.registration_response(&request, &credential_identifier)?;
+        assert_eq!(
+            response.serialize(),
+            hex(concat!(
+                "7408a268083e03abc7097fc05b587834539065e86fb0c7b6342fcf5e01e5b019",
+                "b2fe7af9f48cc502d016729d2fe25cdd433f2c4bc904660b2a382c9b79df1a78"
+            ))
+        );
+        let registration = client.registration_finish(state, &response)?;
+        assert_eq!(
+            registration.record.serialize(),
+            hex(concat!(
+                "76a845464c68a5d2f7e442436bb1424953b17d3e2e289ccbaccafb57ac5c3675",
+                "1ac5844383c7708077dea41cbefe2fa15724f449e535dd7dd562e66f5ecfb958",
+                "64eadddec9db5874959905117dad40a4524111849799281fefe3c51fa82785c5",
+                "ac13171b2f17bc2c74997f0fce1e1f35bec6b91fe2e12dbd323d23ba7a38dfec",
+                "634b0f5b96109c198a8027da51854c35bee90d1e1c781806d07d49b76de6a28b",
+                "8d9e9b6c93b9f8b64d16dddd9c5bfb5fea48ee8fd2f75012a8b308605cdd8ba5"
+            ))
+        );
+
+        // The login from the server's side, fed the vector's KE1 and KE3
+        let ke1 = Ke1::deserialize(&hex(concat!(
+            "c4dedb0ba6ed5d965d6f250fbe554cd45cba5dfcce3ce836e4aee778aa3cd44d",
+            "da7e07376d6d6f034cfa9bb537d11b8c6b4238c334333d1f0aebb380cae6a6cc",
+            "6e29bee50701498605b2c085d7b241ca15ba5c32027dd21ba420b94ce60da326"
+        )))?;
+        let (ke2, server_state) =
+            server.login_start(Some(&registration.record), &credential_identifier, &ke1)?;
+        assert_eq!(
+            ke2.serialize(),
+            hex(concat!(
+                "7e308140890bcde30cbcea28b01ea1ecfbd077cff62c4def8efa075aabcbb471",
+                "38fe59af0df2c79f57b8780278f5ae47355fe1f817119041951c80f612fdfc6d",
+                "d6ec60bcdb26dc455ddf3e718f1020490c192d70dfc7e403981179d8073d1146",
+                "a4f9aa1ced4e4cd984c657eb3b54ced3848326f70331953d91b02535af44d9fe",
+                "dc80188ca46743c52786e0382f95ad85c08f6afcd1ccfbff95e2bdeb015b166c",
+                "6b20b92f832cc6df01e0b86a7efd92c1c804ff865781fa93f2f20b446c8371b6",
+                "71cd9960ecef2fe0d0f7494986fa3d8b2bb01963537e60efb13981e138e3d4a1",
+                "c4f62198a9d6fa9170c42c3c71f1971b29eb1d5d0bd733e40816c91f7912cc4a",
+                "660c48dae03e57aaa38f3d0cffcfc21852ebc8b405d15bd6744945ba1a93438a",
+                "162b6111699d98a16bb55b7bdddfe0fc5608b23da246e7bd73b47369169c5c90"
+            ))
+        );
+        let ke3 = Ke3::deserialize(&hex(concat!(
+            "4455df4f810ac31a6748835888564b536e6da5d9944dfea9e34defb9575fe5e2",
+            "661ef61d2ae3929bcf57e53d464113d364365eb7d1a57b629707ca48da18e442"
+        )))?;
+        let session_key = server.login_finish(server_state, &ke3)?;
+        assert_eq!(
+            *session_key,
+            hex(concat!(
+                "42afde6f5aca0cfa5c163763fbad55e73a41db6b41bc87b8e7b62214a8eedc67",
+                "31fa3cb857d657ab9b3764b89a84e91ebcb4785166fbb02cedfcbdfda215b96f"
+            ))
+        );
+
+        assert_eq!(
+            *registration.export_key,
+            hex(concat!(
+                "1ef15b4fa99e8a852412450ab78713aad30d21fa6966c9b8c9fb3262a970dc62",
+                "950d4dd4ed62598229b1b72794fc0335199d9f7fcc6eaedde92cc04870e63f16"
+            ))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_opaque_tampering() -> Result<(), OpaqueError> {
+        let client = OpaqueClient::with_rng(KDF, Arc::new(OpenSslRandom));
+        let server = server()?;
+        let (record, _) = register(&client, &server, b"alice", b"hunter2")?;
+
+        // A server whose MAC doesn't verify is rejected
+        let (ke1, client_state) = client.login_start(b"hunter2")?;
+        let (mut ke2, _) = server.login_start(Some(&record), b"alice", &ke1)?;
+        ke2.server_mac[0] ^= 1;
+        assert!(matches!(
+            client.login_finish(client_state, &ke2),
+            Err(OpaqueError::ServerAuthentication)
+        ));
+
+        // So is a client whose MAC doesn't
+        let (ke1, client_state) = client.login_start(b"hunter2")?;
+        let (ke2, server_state) = server.login_start(Some(&record), b"alice", &ke1)?;
+        let mut ke3 = client.login_finish(client_state, &ke2)?.ke3;
+        ke3.client_mac[0] ^= 1;
+        assert!(matches!(
+            server.login_finish(server_state, &ke3),
+            Err(OpaqueError::ClientAuthentication)
+        ));
+
+        // Messages of the wrong length, or with the identity, don't decode
+        let bytes = ke1.serialize();
+        assert!(Ke1::deserialize(&bytes[1..]).is_err());
+        assert!(Ke1::deserialize(&[bytes.as_slice(), &[0]].concat()).is_err());
+        assert!(matches!(
+            Ke1::deserialize(&[0u8; 96]),
+            Err(OpaqueError::InvalidMessage)
+        ));
+
+        Ok(())
+    }
+}
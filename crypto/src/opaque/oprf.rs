@@ -0,0 +1,266 @@
+//! The OPRF OPAQUE is built on: RFC 9497 in base mode with the
+//! ristretto255-SHA512 suite. The client blinds its password, the server
+//! multiplies the blinded element by its per-user key, and the client unblinds
+//! and hashes the result. The server never sees the password, and the client
+//! learns nothing about the key but the one output.
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::Identity;
+use openssl::sha::Sha512;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::error::{CryptoError, OpaqueError};
+use crate::random::RandomSource;
+
+use super::NH;
+
+// "OPRFV1-" || I2OSP(modeOPRF, 1) || "-" || identifier
+const CONTEXT_STRING: &[u8] = b"OPRFV1-\x00-ristretto255-SHA512";
+
+// Output size of SHA-512, and its input block size
+const HASH_LEN: usize = 64;
+const BLOCK_LEN: usize = 128;
+
+/// `expand_message_xmd` from RFC 9380 with SHA-512: `len` pseudorandom bytes
+/// from the concatenated `msg`, separated from other uses by `dst`. Every
+/// caller asks for at most 255 blocks with a `dst` of at most 255 bytes.
+pub fn expand_message_xmd(msg: &[&[u8]], dst: &[u8], len: usize) -> Vec<u8> {
+    let dst_prime = [dst, &[dst.len() as u8]].concat();
+
+    let mut hasher = Sha512::new();
+    hasher.update(&[0u8; BLOCK_LEN]);
+    for part in msg {
+        hasher.update(part);
+    }
+    hasher.update(&(len as u16).to_be_bytes());
+    hasher.update(&[0]);
+    hasher.update(&dst_prime);
+    let b0 = hasher.finish();
+
+    // b_1 = H(b_0 || 1 || DST'), b_i = H((b_0 xor b_{i-1}) || i || DST')
+    let mut uniform = Vec::with_capacity(len + HASH_LEN);
+    let mut previous = [0u8; HASH_LEN];
+    for i in 1..=len.div_ceil(HASH_LEN) {
+        let mut chained = b0;
+        for (byte, prev) in chained.iter_mut().zip(previous) {
+            *byte ^= prev;
+        }
+
+        let mut block = Sha512::new();
+        block.update(&chained);
+        block.update(&[i as u8]);
+        block.update(&dst_prime);
+        previous = block.finish();
+        uniform.extend_from_slice(&previous);
+    }
+    uniform.truncate(len);
+
+    uniform
+}
+
+fn dst(prefix: &[u8]) -> Vec<u8> {
+    [prefix, CONTEXT_STRING].concat()
+}
+
+/// Maps `input` to a group element with the ristretto255 hash-to-group.
+pub fn hash_to_group(input: &[u8]) -> RistrettoPoint {
+    let mut uniform = [0u8; 64];
+    uniform.copy_from_slice(&expand_message_xmd(&[input], &dst(b"HashToGroup-"), 64));
+
+    RistrettoPoint::from_uniform_bytes(&uniform)
+}
+
+/// Maps `input` to a scalar, reducing 64 uniform bytes modulo the group order.
+pub fn hash_to_scalar(input: &[&[u8]], dst: &[u8]) -> Scalar {
+    let mut wide = [0u8; 64];
+    wide.copy_from_slice(&expand_message_xmd(input, dst, 64));
+    let scalar = Scalar::from_bytes_mod_order_wide(&wide);
+    wide.zeroize();
+
+    scalar
+}
+
+/// Uniform non-zero scalar.
+pub fn random_scalar(rng: &dyn RandomSource) -> Result<Scalar, CryptoError> {
+    let mut wide = Zeroizing::new([0u8; 64]);
+    loop {
+        rng.fill_bytes(&mut *wide)?;
+        let scalar = Scalar::from_bytes_mod_order_wide(&wide);
+        if scalar != Scalar::ZERO {
+            return Ok(scalar);
+        }
+    }
+}
+
+/// Derives a key pair from `seed`, separated by `info` (`DeriveKeyPair`).
+pub fn derive_key_pair(
+    seed: &[u8],
+    info: &[u8],
+) -> Result<(Zeroizing<Scalar>, RistrettoPoint), OpaqueError> {
+    let info_len = u16::try_from(info.len()).map_err(|_| OpaqueError::DeriveKeyPair)?;
+    let dst = dst(b"DeriveKeyPair");
+
+    for counter in 0..=u8::MAX {
+        let private_key = Zeroizing::new(hash_to_scalar(
+            &[seed, &info_len.to_be_bytes(), info, &[counter]],
+            &dst,
+        ));
+        if *private_key != Scalar::ZERO {
+            let public_key = RistrettoPoint::mul_base(&private_key);
+            return Ok((private_key, public_key));
+        }
+    }
+
+    Err(OpaqueError::DeriveKeyPair)
+}
+
+/// Blinds `input` with a fresh scalar: returns the blind and `blind·H(input)`.
+pub fn blind(
+    input: &[u8],
+    rng: &dyn RandomSource,
+) -> Result<(Zeroizing<Scalar>, RistrettoPoint), OpaqueError> {
+    let element = hash_to_group(input);
+    if element == RistrettoPoint::identity() {
+        return Err(OpaqueError::InvalidInput);
+    }
+
+    let blind = Zeroizing::new(random_scalar(rng)?);
+    let blinded = *blind * element;
+
+    Ok((blind, blinded))
+}
+
+/// The server's side: `key·blinded`.
+pub fn blind_evaluate(key: &Scalar, blinded: &RistrettoPoint) -> RistrettoPoint {
+    key * blinded
+}
+
+/// Unblinds `evaluated` and hashes it with `input` into the OPRF output.
+pub fn finalize(
+    input: &[u8],
+    blind: &Scalar,
+    evaluated: &RistrettoPoint,
+) -> Result<Zeroizing<[u8; NH]>, OpaqueError> {
+    let input_len = u16::try_from(input.len()).map_err(|_| OpaqueError::InvalidInput)?;
+    let unblinded = Zeroizing::new((blind.invert() * evaluated).compress().to_bytes());
+
+    let mut hasher = Sha512::new();
+    hasher.update(&input_len.to_be_bytes());
+    hasher.update(input);
+    hasher.update(&(unblinded.len() as u16).to_be_bytes());
+    hasher.update(&*unblinded);
+    hasher.update(b"Finalize");
+
+    Ok(Zeroizing::new(hasher.finish()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::random::OpenSslRandom;
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_expand_message_xmd() {
+        // RFC 9380, Appendix K.3
+        const DST: &[u8] = b"QUUX-V01-CS02-with-expander-SHA512-256";
+        assert_eq!(
+            expand_message_xmd(&[b""], DST, 0x20),
+            hex("6b9a7312411d92f921c6f68ca0b6380730a1a4d982c507211a90964c394179ba")
+        );
+        assert_eq!(
+            expand_message_xmd(&[b"abcdef", b"0123456789"], DST, 0x20),
+            hex("087e45a86e2939ee8b91100af1583c4938e0f5fc6c9db4b107b83346bc967f58")
+        );
+        assert_eq!(
+            expand_message_xmd(&[b"abc"], DST, 0x80),
+            hex(concat!(
+                "7f1dddd13c08b543f2e2037b14cefb255b44c83cc397c1786d975653e36a6b11",
+                "bdd7732d8b38adb4a0edc26a0cef4bb45217135456e58fbca1703cd6032cb134",
+                "7ee720b87972d63fbf232587043ed2901bce7f22610c0419751c065922b48843",
+                "1851041310ad659e4b23520e1772ab29dcdeb2002222a363f0c2b1c972b3efe1"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_oprf_vectors() -> Result<(), OpaqueError> {
+        // RFC 9497, Appendix A.1.1: ristretto255-SHA512 in OPRF mode
+        let (key, _) = derive_key_pair(&[0xa3; 32], b"test key")?;
+        assert_eq!(
+            key.to_bytes().to_vec(),
+            hex("5ebcea5ee37023ccb9fc2d2019f9d7737be85591ae8652ffa9ef0f4d37063b0e")
+        );
+
+        let blind = Scalar::from_canonical_bytes(
+            hex("64d37aed22a27f5191de1c1d69fadb899d8862b58eb4220029e036ec4c1f6706")
+                .try_into()
+                .unwrap(),
+        )
+        .unwrap();
+        let vectors = [
+            (
+                "00",
+                "609a0ae68c15a3cf6903766461307e5c8bb2f95e7e6550e1ffa2dc99e412803c",
+                "7ec6578ae5120958eb2db1745758ff379e77cb64fe77b0b2d8cc917ea0869c7e",
+                concat!(
+                    "527759c3d9366f277d8c6020418d96bb393ba2afb20ff90df23fb7708264e2f3",
+                    "ab9135e3bd69955851de4b1f9fe8a0973396719b7912ba9ee8aa7d0b5e24bcf6"
+                ),
+            ),
+            (
+                "5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a5a",
+                "da27ef466870f5f15296299850aa088629945a17d1f5b7f5ff043f76b3c06418",
+                "b4cbf5a4f1eeda5a63ce7b77c7d23f461db3fcab0dd28e4e17cecb5c90d02c25",
+                concat!(
+                    "f4a74c9c592497375e796aa837e907b1a045d34306a749db9f34221f7e750cb4",
+                    "f2a6413a6bf6fa5e19ba6348eb673934a722a7ede2e7621306d18951e7cf2c73"
+                ),
+            ),
+        ];
+        for (input, blinded, evaluated, output) in vectors {
+            let input = hex(input);
+            let blinded_element = blind * hash_to_group(&input);
+            assert_eq!(blinded_element.compress().to_bytes().to_vec(), hex(blinded));
+            let evaluation = blind_evaluate(&key, &blinded_element);
+            assert_eq!(evaluation.compress().to_bytes().to_vec(), hex(evaluated));
+            assert_eq!(finalize(&input, &blind, &evaluation)?.to_vec(), hex(output));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_oprf() -> Result<(), OpaqueError> {
+        let rng = OpenSslRandom;
+        let (key, _) = derive_key_pair(b"seed", b"info")?;
+
+        // The output depends on the input and the key, not on the blind
+        let (blind_a, blinded_a) = blind(b"hunter2", &rng)?;
+        let (blind_b, blinded_b) = blind(b"hunter2", &rng)?;
+        assert_ne!(blinded_a, blinded_b);
+        let output = finalize(b"hunter2", &blind_a, &blind_evaluate(&key, &blinded_a))?;
+        let again = finalize(b"hunter2", &blind_b, &blind_evaluate(&key, &blinded_b))?;
+        assert_eq!(*output, *again);
+
+        let (other_key, _) = derive_key_pair(b"seed", b"other info")?;
+        let other = finalize(
+            b"hunter2",
+            &blind_a,
+            &blind_evaluate(&other_key, &blinded_a),
+        )?;
+        assert_ne!(*output, *other);
+        let (blind_c, blinded_c) = blind(b"hunter3", &rng)?;
+        let other = finalize(b"hunter3", &blind_c, &blind_evaluate(&key, &blinded_c))?;
+        assert_ne!(*output, *other);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,221 @@
+use std::sync::Arc;
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use openssl::memcmp;
+use zeroize::Zeroizing;
+
+use crate::error::OpaqueError;
+use crate::random::{default_source, RandomSource};
+
+use super::messages::{
+    credential_response_plaintext, Envelope, Ke1, Ke2, Ke3, RegistrationRecord,
+    RegistrationRequest, RegistrationResponse, MASKED_RESPONSE_LEN,
+};
+use super::{
+    credential_response_pad, encode, expand, oprf, preamble, random_key_pair, random_nonce, Keys,
+    SessionKey, CONTEXT, DH_KEY_PAIR_INFO, NH, NM, NN, NOK, NSEED,
+};
+
+const OPRF_KEY_INFO: &[u8] = b"OPAQUE-DeriveKeyPair";
+
+/// The server's long-term OPAQUE keys. A record only works with the setup
+/// it was registered under.
+pub struct ServerSetup {
+    // Every user's OPRF key is derived from this and their credential identifier
+    oprf_seed: Zeroizing<[u8; NH]>,
+    private_key: Zeroizing<Scalar>,
+    pub public_key: RistrettoPoint,
+    // Fake records for unknown users are derived from this
+    fake_record_key: Zeroizing<[u8; NH]>,
+}
+
+impl ServerSetup {
+    pub fn generate(rng: &dyn RandomSource) -> Result<ServerSetup, OpaqueError> {
+        let mut oprf_seed = Zeroizing::new([0u8; NH]);
+        rng.fill_bytes(&mut *oprf_seed)?;
+        let mut fake_record_key = Zeroizing::new([0u8; NH]);
+        rng.fill_bytes(&mut *fake_record_key)?;
+        let (private_key, public_key) = random_key_pair(rng)?;
+
+        Ok(ServerSetup {
+            oprf_seed,
+            private_key,
+            public_key,
+            fake_record_key,
+        })
+    }
+
+    /// A setup restored from its keys, such as ones kept across restarts.
+    pub fn from_keys(
+        oprf_seed: [u8; NH],
+        private_key: Scalar,
+        fake_record_key: [u8; NH],
+    ) -> ServerSetup {
+        ServerSetup {
+            oprf_seed: Zeroizing::new(oprf_seed),
+            public_key: RistrettoPoint::mul_base(&private_key),
+            private_key: Zeroizing::new(private_key),
+            fake_record_key: Zeroizing::new(fake_record_key),
+        }
+    }
+}
+
+/// What the server keeps between `Ke2` and `Ke3`.
+pub struct ServerLoginState {
+    expected_client_mac: [u8; NM],
+    session_key: SessionKey,
+}
+
+/// The server's side of OPAQUE.
+pub struct OpaqueServer {
+    pub setup: ServerSetup,
+    /// Where masking nonces and ephemeral keys come from.
+    pub rng: Arc<dyn RandomSource>,
+    /// Must be the client's.
+    pub context: Vec<u8>,
+}
+
+impl OpaqueServer {
+    pub fn new(setup: ServerSetup) -> Self {
+        OpaqueServer::with_rng(setup, default_source())
+    }
+
+    /// A server drawing its randomness from `rng` instead of the default source.
+    pub fn with_rng(setup: ServerSetup, rng: Arc<dyn RandomSource>) -> Self {
+        OpaqueServer {
+            setup,
+            rng,
+            context: CONTEXT.to_vec(),
+        }
+    }
+
+    /// Evaluates the OPRF on the blinded password under the key of
+    /// `credential_identifier`.
+    pub fn registration_response(
+        &self,
+        request: &RegistrationRequest,
+        credential_identifier: &[u8],
+    ) -> Result<RegistrationResponse, OpaqueError> {
+        let oprf_key = self.oprf_key(credential_identifier)?;
+
+        Ok(RegistrationResponse {
+            evaluated_message: oprf::blind_evaluate(&oprf_key, &request.blinded_message),
+            server_public_key: self.setup.public_key,
+        })
+    }
+
+    /// Answers `ke1` with the masked envelope from `record` and the server's
+    /// half of the key exchange. Without a record, a fake one stands in, so
+    /// the response doesn't reveal whether the user is registered.
+    pub fn login_start(
+        &self,
+        record: Option<&RegistrationRecord>,
+        credential_identifier: &[u8],
+        ke1: &Ke1,
+    ) -> Result<(Ke2, ServerLoginState), OpaqueError> {
+        let fake;
+        let record = match record {
+            Some(record) => record,
+            None => {
+                fake = self.fake_record(credential_identifier)?;
+                &fake
+            }
+        };
+        let oprf_key = self.oprf_key(credential_identifier)?;
+
+        let masking_nonce = random_nonce(&*self.rng)?;
+        let pad = credential_response_pad(&*record.masking_key, &masking_nonce)?;
+        let plaintext = credential_response_plaintext(&self.setup.public_key, &record.envelope);
+        let mut masked_response = [0u8; MASKED_RESPONSE_LEN];
+        for ((masked, pad), plain) in masked_response
+            .iter_mut()
+            .zip(pad.iter())
+            .zip(plaintext.iter())
+        {
+            *masked = pad ^ plain;
+        }
+
+        let (server_secret, server_public_keyshare) = random_key_pair(&*self.rng)?;
+        let mut ke2 = Ke2 {
+            evaluated_message: oprf::blind_evaluate(&oprf_key, &ke1.blinded_message),
+            masking_nonce,
+            masked_response,
+            server_nonce: random_nonce(&*self.rng)?,
+            server_public_keyshare,
+            server_mac: [0u8; NM], // Filled in below; the preamble doesn't cover it
+        };
+
+        let preamble = preamble(
+            &self.context,
+            &encode(&record.client_public_key),
+            ke1,
+            &encode(&self.setup.public_key),
+            &ke2,
+        );
+        let keys = Keys::derive(
+            [
+                *server_secret * ke1.client_public_keyshare,
+                *self.setup.private_key * ke1.client_public_keyshare,
+                *server_secret * record.client_public_key,
+            ],
+            &preamble,
+        )?;
+        ke2.server_mac = keys.server_mac(&preamble);
+
+        let state = ServerLoginState {
+            expected_client_mac: keys.client_mac(&preamble, &ke2.server_mac),
+            session_key: keys.session_key,
+        };
+
+        Ok((ke2, state))
+    }
+
+    /// Checks the client's MAC in `ke3`; the session key is only released if
+    /// the client proved it knows the password.
+    pub fn login_finish(
+        &self,
+        state: ServerLoginState,
+        ke3: &Ke3,
+    ) -> Result<SessionKey, OpaqueError> {
+        if !memcmp::eq(&state.expected_client_mac, &ke3.client_mac) {
+            return Err(OpaqueError::ClientAuthentication);
+        }
+
+        Ok(state.session_key)
+    }
+
+    fn oprf_key(&self, credential_identifier: &[u8]) -> Result<Zeroizing<Scalar>, OpaqueError> {
+        let seed = expand(
+            &*self.setup.oprf_seed,
+            &[credential_identifier, b"OprfKey"],
+            NOK,
+        )?;
+        let (oprf_key, _) = oprf::derive_key_pair(&seed, OPRF_KEY_INFO)?;
+
+        Ok(oprf_key)
+    }
+
+    // A random-looking record for an unknown user, the same on every login so
+    // that repeated attempts can't tell it from a real one
+    fn fake_record(&self, credential_identifier: &[u8]) -> Result<RegistrationRecord, OpaqueError> {
+        let key = &*self.setup.fake_record_key;
+        let seed = expand(key, &[credential_identifier, b"FakeClientKey"], NSEED)?;
+        let (_, client_public_key) = oprf::derive_key_pair(&seed, DH_KEY_PAIR_INFO)?;
+        let mut masking_key = Zeroizing::new([0u8; NH]);
+        masking_key.copy_from_slice(&expand(
+            key,
+            &[credential_identifier, b"FakeMaskingKey"],
+            NH,
+        )?);
+
+        Ok(RegistrationRecord {
+            client_public_key,
+            masking_key,
+            envelope: Envelope {
+                nonce: [0u8; NN],
+                auth_tag: [0u8; NM],
+            },
+        })
+    }
+}
@@ -0,0 +1,226 @@
+use argon2::{Algorithm, Argon2, Params, Version};
+use zeroize::Zeroize;
+
+use crate::error::{CryptoError, PasswordError};
+use crate::group::{Group, GroupScalar};
+use crate::random::default_source;
+use crate::secret::{SecretScalar, WIDE_MARGIN_BYTES};
+use crate::util::expand_hash;
+
+const PASSWORD_DOMAIN: &[u8] = b"zkp_auth/password-to-secret/v1";
+const SEED_DOMAIN: &[u8] = b"zkp_auth/password-to-seed/v1";
+
+/// Length of the per-user salts `random_salt` makes.
+pub const SALT_LEN: usize = argon2::RECOMMENDED_SALT_LEN;
+
+// Length of the Argon2id output, before it is hashed into the group order
+const STRETCHED_LEN: usize = 64;
+
+/// Fresh per-user salt, stored by the server next to the public keys.
+pub fn random_salt() -> Result<Vec<u8>, CryptoError> {
+    let mut salt = vec![0u8; SALT_LEN];
+    default_source().fill_bytes(&mut salt)?;
+
+    Ok(salt)
+}
+
+/// Argon2id cost parameters for turning a password into the secret `x`.
+/// `Default` is the OWASP recommendation: 19 MiB, 2 passes, 1 lane.
+#[derive(Clone, Copy, Debug)]
+pub struct PasswordKdf {
+    /// Memory in KiB.
+    pub m_cost: u32,
+    pub t_cost: u32,
+    pub p_cost: u32,
+}
+
+impl Default for PasswordKdf {
+    fn default() -> Self {
+        PasswordKdf {
+            m_cost: Params::DEFAULT_M_COST,
+            t_cost: Params::DEFAULT_T_COST,
+            p_cost: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+impl PasswordKdf {
+    /// Argon2id of `input` under `salt`, filling `output`.
+    pub fn stretch(
+        &self,
+        input: &[u8],
+        salt: &[u8],
+        output: &mut [u8],
+    ) -> Result<(), PasswordError> {
+        let params = Params::new(self.m_cost, self.t_cost, self.p_cost, Some(output.len()))?;
+        let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, params);
+
+        Ok(argon2.hash_password_into(input, salt, output)?)
+    }
+
+    /// Derives `x` in `[1, q)` of `group` from the user name, password and salt.
+    /// The same inputs always give the same `x`; the same password in another
+    /// group, or for another user, gives an unrelated one.
+    pub fn derive_secret<G: Group>(
+        &self,
+        group: &G,
+        user: &str,
+        password: &[u8],
+        salt: &[u8],
+    ) -> Result<SecretScalar<G::Scalar>, PasswordError> {
+        let mut stretched = self.stretch_user(user, password, salt)?;
+
+        // Bind the group, so x differs between groups with the same order size
+        let mut parts = vec![stretched.to_vec()];
+        parts.extend(group.description()?);
+        let parts: Vec<&[u8]> = parts.iter().map(Vec::as_slice).collect();
+        let len = group.scalar_len() + WIDE_MARGIN_BYTES;
+        let mut wide = expand_hash(PASSWORD_DOMAIN, &parts, len);
+        stretched.zeroize();
+
+        let x = SecretScalar::reduce_wide(group, &wide);
+        wide.zeroize();
+        let x = x?;
+
+        if x.expose().is_zero() {
+            return Err(PasswordError::ZeroSecret);
+        }
+
+        Ok(x)
+    }
+
+    /// Derives a seed for keys that are not group scalars, such as SLH-DSA's,
+    /// like `derive_secret`. `domain` names the kind of key, so the same
+    /// password gives unrelated seeds for different kinds.
+    pub fn derive_seed(
+        &self,
+        domain: &[u8],
+        user: &str,
+        password: &[u8],
+        salt: &[u8],
+        seed: &mut [u8],
+    ) -> Result<(), PasswordError> {
+        let mut stretched = self.stretch_user(user, password, salt)?;
+        let mut expanded = expand_hash(SEED_DOMAIN, &[domain, &stretched], seed.len());
+        seed.copy_from_slice(&expanded);
+        stretched.zeroize();
+        expanded.zeroize();
+
+        Ok(())
+    }
+
+    // Argon2id of the password, with the user name hashed in too, so reusing
+    // a salt across users is harmless
+    fn stretch_user(
+        &self,
+        user: &str,
+        password: &[u8],
+        salt: &[u8],
+    ) -> Result<[u8; STRETCHED_LEN], PasswordError> {
+        let mut input = Vec::with_capacity(8 + user.len() + password.len());
+        input.extend((user.len() as u64).to_be_bytes());
+        input.extend(user.as_bytes());
+        input.extend(password);
+        let mut stretched = [0u8; STRETCHED_LEN];
+        let hashed = self.stretch(&input, salt, &mut stretched);
+        input.zeroize();
+        hashed?;
+
+        Ok(stretched)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "openssl")]
+    use openssl::nid::Nid;
+
+    use super::*;
+    #[cfg(feature = "openssl")]
+    use crate::ec::params::EcParams;
+    use crate::ristretto::params::RistrettoParams;
+
+    // Cheap costs; the derivation logic doesn't depend on them
+    const KDF: PasswordKdf = PasswordKdf {
+        m_cost: 64,
+        t_cost: 1,
+        p_cost: 1,
+    };
+
+    // Derives alice's x in `group`, checking it is deterministic and
+    // differs for any other input, and returns its encoding
+    fn check_derive_secret<G: Group>(group: &G, salt: &[u8]) -> Result<Vec<u8>, PasswordError> {
+        let x = KDF.derive_secret(group, "alice", b"hunter2", salt)?;
+        let again = KDF.derive_secret(group, "alice", b"hunter2", salt)?;
+        assert_eq!(
+            x.expose(),
+            again.expose(),
+            "derivation is not deterministic"
+        );
+
+        for other in [
+            KDF.derive_secret(group, "alice", b"hunter3", salt)?,
+            KDF.derive_secret(group, "bob", b"hunter2", salt)?,
+            KDF.derive_secret(group, "alice", b"hunter2", &random_salt()?)?,
+        ] {
+            assert_ne!(
+                x.expose(),
+                other.expose(),
+                "unrelated inputs gave the same x"
+            );
+        }
+
+        // Argon2 needs at least 8 bytes of salt
+        assert!(matches!(
+            KDF.derive_secret(group, "alice", b"hunter2", b"short"),
+            Err(PasswordError::Kdf(_))
+        ));
+
+        Ok(group.encode_scalar(x.expose())?)
+    }
+
+    #[test]
+    fn test_derive_secret() -> Result<(), PasswordError> {
+        let salt = random_salt()?;
+        let ristretto = check_derive_secret(&RistrettoParams::new()?, &salt)?;
+
+        // Another salt gives an unrelated x
+        let other = check_derive_secret(&RistrettoParams::new()?, &random_salt()?)?;
+        assert_ne!(other, ristretto);
+
+        #[cfg(feature = "openssl")]
+        {
+            let ec = EcParams::new(Nid::X9_62_PRIME256V1)?;
+            let x = KDF.derive_secret(&ec, "alice", b"hunter2", &salt)?;
+            assert!(x.expose().is_const_time(), "const-time flag not set");
+            assert!(x.expose() < ec.order());
+
+            // The same password in another group gives an unrelated x
+            assert_ne!(check_derive_secret(&ec, &salt)?, ristretto);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_derive_seed() -> Result<(), PasswordError> {
+        let salt = random_salt()?;
+        let seed = |domain: &[u8], user, password: &[u8]| {
+            let mut seed = [0u8; 48];
+            KDF.derive_seed(domain, user, password, &salt, &mut seed)
+                .map(|()| seed)
+        };
+
+        let expected = seed(b"test", "alice", b"hunter2")?;
+        assert_eq!(expected, seed(b"test", "alice", b"hunter2")?);
+        for other in [
+            seed(b"test", "alice", b"hunter3")?,
+            seed(b"test", "bob", b"hunter2")?,
+            seed(b"other", "alice", b"hunter2")?,
+        ] {
+            assert_ne!(expected, other, "unrelated inputs gave the same seed");
+        }
+
+        Ok(())
+    }
+}
@@ -1,4 +1,13 @@
-use openssl::{bn::BigNum, error::ErrorStack};
+use std::sync::Arc;
+
+use crate::error::{CryptoError, PasswordError};
+use crate::fiat_shamir::{self, NonInteractiveProof};
+use crate::group::Group;
+use crate::identification::{Challenge, IdentificationProver};
+use crate::password::PasswordKdf;
+use crate::random::{default_source, RandomSource};
+use crate::secret::SecretScalar;
+use crate::transcript::Transcript;
 
 pub struct ProverPublicKeys<Element> {
     pub y1: Element,
@@ -10,21 +19,203 @@ pub struct ProverCommit<Element> {
     pub r2: Element,
 }
 
-pub struct ProverChallengeResponse {
-    pub s: BigNum,
+pub struct ProverChallengeResponse<S> {
+    pub s: S,
 }
 
-pub trait Prover<Params, Element> {
-    fn new(params: Params) -> Result<Self, ErrorStack>
+pub trait Prover<G: Group> {
+    fn new(params: G) -> Result<Self, CryptoError>
     where
         Self: Sized;
-    fn random(&self) -> Result<BigNum, ErrorStack>;
-    fn public_keys(&self, x: &BigNum) -> Result<ProverPublicKeys<Element>, ErrorStack>;
-    fn commit(&self, k: &BigNum) -> Result<ProverCommit<Element>, ErrorStack>;
+    /// Random secret in `[0, q)`, used for both `x` and `k`.
+    fn random(&self) -> Result<SecretScalar<G::Scalar>, CryptoError>;
+    /// Commitment nonce `k` hashed from `x`, fresh randomness and the session
+    /// `context`, so a weak or repeating RNG does not leak `x`.
+    fn hedged_nonce(
+        &self,
+        x: &SecretScalar<G::Scalar>,
+        context: &[u8],
+    ) -> Result<SecretScalar<G::Scalar>, CryptoError>;
+    fn public_keys(
+        &self,
+        x: &SecretScalar<G::Scalar>,
+    ) -> Result<ProverPublicKeys<G::Element>, CryptoError>;
+    fn commit(&self, k: &SecretScalar<G::Scalar>) -> Result<ProverCommit<G::Element>, CryptoError>;
+    fn challenge_response(
+        &self,
+        k: &SecretScalar<G::Scalar>,
+        c: &G::Scalar,
+        x: &SecretScalar<G::Scalar>,
+    ) -> Result<ProverChallengeResponse<G::Scalar>, CryptoError>;
+    /// Non-interactive (Fiat-Shamir) proof of knowledge of `x`, bound to
+    /// everything appended to `transcript`.
+    fn prove(
+        &self,
+        x: &SecretScalar<G::Scalar>,
+        transcript: &Transcript,
+    ) -> Result<NonInteractiveProof<G::Scalar>, CryptoError>;
+}
+
+/// The prover's side of Chaum-Pedersen over any `Group`.
+pub struct ChaumPedersenProver<G: Group> {
+    pub params: G,
+    /// Where `x`, `k` and the nonce hedges come from.
+    pub rng: Arc<dyn RandomSource>,
+}
+
+impl<G: Group> ChaumPedersenProver<G> {
+    /// A prover drawing its randomness from `rng` instead of the default source.
+    pub fn with_rng(params: G, rng: Arc<dyn RandomSource>) -> ChaumPedersenProver<G> {
+        ChaumPedersenProver { params, rng }
+    }
+}
+
+impl<G: Group> Prover<G> for ChaumPedersenProver<G> {
+    fn new(params: G) -> Result<ChaumPedersenProver<G>, CryptoError> {
+        Ok(ChaumPedersenProver::with_rng(params, default_source()))
+    }
+
+    fn random(&self) -> Result<SecretScalar<G::Scalar>, CryptoError> {
+        SecretScalar::random(&*self.rng, &self.params)
+    }
+
+    fn hedged_nonce(
+        &self,
+        x: &SecretScalar<G::Scalar>,
+        context: &[u8],
+    ) -> Result<SecretScalar<G::Scalar>, CryptoError> {
+        SecretScalar::hedged(&*self.rng, &self.params, x, context)
+    }
+
+    fn public_keys(
+        &self,
+        x: &SecretScalar<G::Scalar>,
+    ) -> Result<ProverPublicKeys<G::Element>, CryptoError> {
+        let y1 = self.params.exp_g(x.expose())?;
+        let y2 = self.params.exp_h(x.expose())?;
+
+        Ok(ProverPublicKeys { y1, y2 })
+    }
+
+    fn commit(&self, k: &SecretScalar<G::Scalar>) -> Result<ProverCommit<G::Element>, CryptoError> {
+        let r1 = self.params.exp_g(k.expose())?;
+        let r2 = self.params.exp_h(k.expose())?;
+
+        Ok(ProverCommit { r1, r2 })
+    }
+
     fn challenge_response(
         &self,
-        k: &BigNum,
-        c: &BigNum,
-        x: &BigNum,
-    ) -> Result<ProverChallengeResponse, ErrorStack>;
+        k: &SecretScalar<G::Scalar>,
+        c: &G::Scalar,
+        x: &SecretScalar<G::Scalar>,
+    ) -> Result<ProverChallengeResponse<G::Scalar>, CryptoError> {
+        // c * x reveals x to anyone who knows c, so wipe it as well
+        let cx = SecretScalar::new(self.params.scalar_mul(c, x.expose())?);
+
+        Ok(ProverChallengeResponse {
+            s: self.params.scalar_sub(k.expose(), cx.expose())?,
+        })
+    }
+
+    fn prove(
+        &self,
+        x: &SecretScalar<G::Scalar>,
+        transcript: &Transcript,
+    ) -> Result<NonInteractiveProof<G::Scalar>, CryptoError> {
+        let keys = self.public_keys(x)?;
+        let k = self.hedged_nonce(x, &transcript.digest())?;
+        let commit = self.commit(&k)?;
+
+        let c = fiat_shamir::group_challenge(
+            &self.params,
+            &ProverPublicKeys {
+                y1: &keys.y1,
+                y2: &keys.y2,
+            },
+            &ProverCommit {
+                r1: &commit.r1,
+                r2: &commit.r2,
+            },
+            transcript,
+        )?;
+        let ProverChallengeResponse { s } = self.challenge_response(&k, &c, x)?;
+
+        Ok(NonInteractiveProof { c, s })
+    }
+}
+
+impl<G: Group> IdentificationProver for ChaumPedersenProver<G> {
+    type Secret = SecretScalar<G::Scalar>;
+    type PublicKey = ProverPublicKeys<G::Element>;
+    type Nonce = SecretScalar<G::Scalar>;
+    type Commitment = ProverCommit<G::Element>;
+    type Response = G::Scalar;
+    type Scalar = G::Scalar;
+
+    fn generate_secret(&self) -> Result<SecretScalar<G::Scalar>, CryptoError> {
+        self.random()
+    }
+
+    fn secret_from_password(
+        &self,
+        kdf: &PasswordKdf,
+        user: &str,
+        password: &[u8],
+        salt: &[u8],
+    ) -> Result<SecretScalar<G::Scalar>, PasswordError> {
+        kdf.derive_secret(&self.params, user, password, salt)
+    }
+
+    fn public_key(
+        &self,
+        x: &SecretScalar<G::Scalar>,
+    ) -> Result<ProverPublicKeys<G::Element>, CryptoError> {
+        self.public_keys(x)
+    }
+
+    fn commitment(
+        &self,
+        x: &SecretScalar<G::Scalar>,
+        context: &[u8],
+    ) -> Result<(SecretScalar<G::Scalar>, ProverCommit<G::Element>), CryptoError> {
+        let k = self.hedged_nonce(x, context)?;
+        let commit = self.commit(&k)?;
+
+        Ok((k, commit))
+    }
+
+    fn accepts_challenge(
+        &self,
+        keys: &ProverPublicKeys<G::Element>,
+        commit: &ProverCommit<G::Element>,
+        challenge: &Challenge<G::Scalar>,
+        transcript: &Transcript,
+    ) -> Result<bool, CryptoError> {
+        let expected = fiat_shamir::interactive_challenge(
+            &self.params,
+            &ProverPublicKeys {
+                y1: &keys.y1,
+                y2: &keys.y2,
+            },
+            &ProverCommit {
+                r1: &commit.r1,
+                r2: &commit.r2,
+            },
+            &challenge.nonce,
+            transcript,
+        )?;
+
+        Ok(challenge.c.as_ref() == Some(&expected))
+    }
+
+    fn respond(
+        &self,
+        x: &SecretScalar<G::Scalar>,
+        k: &SecretScalar<G::Scalar>,
+        challenge: &Challenge<G::Scalar>,
+        _transcript: &Transcript,
+    ) -> Result<G::Scalar, CryptoError> {
+        Ok(self.challenge_response(k, challenge.scalar()?, x)?.s)
+    }
 }
@@ -0,0 +1,356 @@
+//! Sources of randomness for the provers and verifiers. Secrets, nonce
+//! hedges, challenges and batch weights are all drawn through a
+//! `RandomSource`, so they can come from OpenSSL, from the operating system,
+//! from a DRBG reseeded under our own policy, or from a fixed seed for
+//! reproducible transcripts.
+
+use std::sync::{Arc, Mutex, PoisonError};
+
+#[cfg(feature = "openssl")]
+use openssl::rand::rand_bytes;
+use zeroize::Zeroize;
+
+use crate::error::CryptoError;
+use crate::util::hmac_sha512;
+
+// SHA-512 output, the size of the HMAC-DRBG key and chaining value
+const OUTLEN: usize = 64;
+
+// Entropy drawn per (re)seed: 256 bits of security strength plus a 128-bit nonce
+const SEED_BYTES: usize = 48;
+
+// SP 800-90A caps a single HMAC-DRBG request at 2^19 bits
+const MAX_REQUEST_BYTES: usize = 1 << 16;
+
+/// Requests an `HmacDrbg` serves before it reseeds from its entropy source.
+pub const DEFAULT_RESEED_INTERVAL: u64 = 1 << 16;
+
+pub trait RandomSource: Send + Sync {
+    /// Fills `dest` with random bytes.
+    fn fill_bytes(&self, dest: &mut [u8]) -> Result<(), CryptoError>;
+}
+
+/// OpenSSL's DRBG (`RAND_bytes`), the default source.
+#[cfg(feature = "openssl")]
+pub struct OpenSslRandom;
+
+#[cfg(feature = "openssl")]
+impl RandomSource for OpenSslRandom {
+    fn fill_bytes(&self, dest: &mut [u8]) -> Result<(), CryptoError> {
+        rand_bytes(dest).map_err(CryptoError::Rng)
+    }
+}
+
+/// The operating system's generator (`getrandom(2)`, `BCryptGenRandom`, ...),
+/// bypassing OpenSSL; the default source without the `openssl` feature.
+pub struct OsRandom;
+
+impl RandomSource for OsRandom {
+    fn fill_bytes(&self, dest: &mut [u8]) -> Result<(), CryptoError> {
+        getrandom::getrandom(dest).map_err(CryptoError::Entropy)
+    }
+}
+
+/// HMAC-DRBG (NIST SP 800-90A) over SHA-512. It seeds from `entropy` and
+/// reseeds from it every `reseed_interval` requests, or whenever `reseed`
+/// is called, so the entropy policy is ours rather than the library's.
+pub struct HmacDrbg {
+    state: Mutex<DrbgState>,
+    entropy: Arc<dyn RandomSource>,
+    reseed_interval: u64,
+}
+
+impl HmacDrbg {
+    /// Instantiates the DRBG from `entropy`. `personalization` separates
+    /// instances that share an entropy source, such as different servers.
+    pub fn new(
+        entropy: Arc<dyn RandomSource>,
+        personalization: &[u8],
+        reseed_interval: u64,
+    ) -> Result<HmacDrbg, CryptoError> {
+        let mut seed = [0u8; SEED_BYTES];
+        entropy.fill_bytes(&mut seed)?;
+        let state = DrbgState::instantiate(&seed, personalization);
+        seed.zeroize();
+
+        Ok(HmacDrbg {
+            state: Mutex::new(state),
+            entropy,
+            reseed_interval,
+        })
+    }
+
+    /// Mixes fresh entropy and `additional` input into the state.
+    pub fn reseed(&self, additional: &[u8]) -> Result<(), CryptoError> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        self.reseed_locked(&mut state, additional)
+    }
+
+    fn reseed_locked(&self, state: &mut DrbgState, additional: &[u8]) -> Result<(), CryptoError> {
+        let mut seed = [0u8; SEED_BYTES];
+        self.entropy.fill_bytes(&mut seed)?;
+        state.reseed(&seed, additional);
+        seed.zeroize();
+
+        Ok(())
+    }
+}
+
+impl RandomSource for HmacDrbg {
+    fn fill_bytes(&self, dest: &mut [u8]) -> Result<(), CryptoError> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        for chunk in dest.chunks_mut(MAX_REQUEST_BYTES) {
+            if state.reseed_counter > self.reseed_interval {
+                self.reseed_locked(&mut state, &[])?;
+            }
+            state.generate(chunk);
+        }
+
+        Ok(())
+    }
+}
+
+/// HMAC-DRBG instantiated from a fixed seed and never reseeded, so the same
+/// seed always gives the same stream. For known-answer tests and reproducible
+/// transcripts only: anyone who knows the seed knows every secret drawn.
+pub struct SeededRandom {
+    state: Mutex<DrbgState>,
+}
+
+impl SeededRandom {
+    pub fn new(seed: &[u8]) -> Result<SeededRandom, CryptoError> {
+        Ok(SeededRandom {
+            state: Mutex::new(DrbgState::instantiate(seed, &[])),
+        })
+    }
+}
+
+impl RandomSource for SeededRandom {
+    fn fill_bytes(&self, dest: &mut [u8]) -> Result<(), CryptoError> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        for chunk in dest.chunks_mut(MAX_REQUEST_BYTES) {
+            state.generate(chunk);
+        }
+
+        Ok(())
+    }
+}
+
+/// The source provers and verifiers use unless given another.
+#[cfg(feature = "openssl")]
+pub fn default_source() -> Arc<dyn RandomSource> {
+    Arc::new(OpenSslRandom)
+}
+
+/// The source provers and verifiers use unless given another.
+#[cfg(not(feature = "openssl"))]
+pub fn default_source() -> Arc<dyn RandomSource> {
+    Arc::new(OsRandom)
+}
+
+// Key K and chaining value V of SP 800-90A section 10.1.2, wiped on drop
+struct DrbgState {
+    k: [u8; OUTLEN],
+    v: [u8; OUTLEN],
+    reseed_counter: u64,
+}
+
+impl DrbgState {
+    fn instantiate(entropy: &[u8], personalization: &[u8]) -> DrbgState {
+        let mut state = DrbgState {
+            k: [0x00; OUTLEN],
+            v: [0x01; OUTLEN],
+            reseed_counter: 1,
+        };
+        state.update(&[entropy, personalization]);
+
+        state
+    }
+
+    fn reseed(&mut self, entropy: &[u8], additional: &[u8]) {
+        self.update(&[entropy, additional]);
+        self.reseed_counter = 1;
+    }
+
+    fn generate(&mut self, dest: &mut [u8]) {
+        for chunk in dest.chunks_mut(OUTLEN) {
+            self.v = hmac_sha512(&self.k, &[&self.v]);
+            chunk.copy_from_slice(&self.v[..chunk.len()]);
+        }
+        self.update(&[]);
+        self.reseed_counter += 1;
+    }
+
+    // HMAC_DRBG_Update: the second round only runs when there is provided data
+    fn update(&mut self, provided: &[&[u8]]) {
+        let has_data = provided.iter().any(|part| !part.is_empty());
+        for round in [0x00u8, 0x01] {
+            if round == 0x01 && !has_data {
+                break;
+            }
+            let round = [round];
+            let mut parts: Vec<&[u8]> = vec![&self.v, &round];
+            parts.extend(provided);
+            self.k = hmac_sha512(&self.k, &parts);
+            self.v = hmac_sha512(&self.k, &[&self.v]);
+        }
+    }
+}
+
+impl Drop for DrbgState {
+    fn drop(&mut self) {
+        self.k.zeroize();
+        self.v.zeroize();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[cfg(feature = "openssl")]
+    use openssl::bn::BigNum;
+
+    use super::*;
+    #[cfg(feature = "openssl")]
+    use crate::dl::{groups::NamedGroup, params::DlParams, prover::DlProver, verifier::DlVerifier};
+    use crate::group::Group;
+    #[cfg(feature = "openssl")]
+    use crate::prover::ProverCommit;
+    use crate::prover::{ChaumPedersenProver, Prover, ProverPublicKeys};
+    use crate::ristretto::params::RistrettoParams;
+    use crate::transcript::Transcript;
+    #[cfg(feature = "openssl")]
+    use crate::util::rng;
+    use crate::verifier::{ChaumPedersenVerifier, Verifier};
+
+    // Counts the (re)seeds an HmacDrbg draws
+    struct CountingSource(AtomicUsize);
+
+    impl RandomSource for CountingSource {
+        fn fill_bytes(&self, dest: &mut [u8]) -> Result<(), CryptoError> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            OsRandom.fill_bytes(dest)
+        }
+    }
+
+    #[test]
+    fn test_seeded_random() -> Result<(), CryptoError> {
+        // HMAC-DRBG with SHA-512, entropy input "zkp_auth" and no nonce or
+        // personalization string: the first 32 bytes generated
+        let expected = hex("1e4113e033f27e2c1042a058b496fe894ead648c52e7575acd8550a99662c6c3");
+        let mut out = [0u8; 32];
+        SeededRandom::new(b"zkp_auth")?.fill_bytes(&mut out)?;
+        assert_eq!(out.to_vec(), expected);
+
+        // Same seed, same stream, however it is split into requests
+        let (a, b) = (SeededRandom::new(b"seed")?, SeededRandom::new(b"seed")?);
+        let (mut x, mut y) = ([0u8; 100], [0u8; 100]);
+        a.fill_bytes(&mut x)?;
+        b.fill_bytes(&mut y)?;
+        assert_eq!(x, y);
+        a.fill_bytes(&mut x)?;
+        assert_ne!(x, y);
+
+        let mut z = [0u8; 100];
+        SeededRandom::new(b"other seed")?.fill_bytes(&mut z)?;
+        assert_ne!(y, z);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hmac_drbg_reseeds() -> Result<(), CryptoError> {
+        let entropy = Arc::new(CountingSource(AtomicUsize::new(0)));
+        let drbg = HmacDrbg::new(entropy.clone(), b"test", 3)?;
+        assert_eq!(entropy.0.load(Ordering::SeqCst), 1);
+
+        let mut out = [0u8; 16];
+        for _ in 0..3 {
+            drbg.fill_bytes(&mut out)?;
+        }
+        assert_eq!(entropy.0.load(Ordering::SeqCst), 1);
+        drbg.fill_bytes(&mut out)?;
+        assert_eq!(entropy.0.load(Ordering::SeqCst), 2);
+        drbg.reseed(b"additional")?;
+        assert_eq!(entropy.0.load(Ordering::SeqCst), 3);
+
+        // Two instances on the same entropy source never agree
+        let other = HmacDrbg::new(entropy, b"test", 3)?;
+        let mut other_out = [0u8; 16];
+        other.fill_bytes(&mut other_out)?;
+        drbg.fill_bytes(&mut out)?;
+        assert_ne!(out, other_out);
+
+        Ok(())
+    }
+
+    #[cfg(feature = "openssl")]
+    #[test]
+    fn test_rng_in_range() -> Result<(), CryptoError> {
+        let source = SeededRandom::new(b"range")?;
+        let p = BigNum::from_u32(1000)?;
+        for _ in 0..200 {
+            let value = rng(&source, &p)?;
+            assert!(!value.is_negative() && value < p);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reproducible_transcript() -> Result<(), CryptoError> {
+        #[cfg(feature = "openssl")]
+        fn run(seed: &[u8]) -> Result<Vec<Vec<u8>>, CryptoError> {
+            let params = DlParams::from_named_group(NamedGroup::Ffdhe2048, b"seed")?;
+            let source: Arc<dyn RandomSource> = Arc::new(SeededRandom::new(seed)?);
+            let prover = DlProver::with_rng(params.clone(), source.clone());
+            let verifier = DlVerifier::with_rng(params, source);
+
+            let x = prover.random()?;
+            let ProverPublicKeys { y1, y2 } = prover.public_keys(&x)?;
+            let k = prover.hedged_nonce(&x, b"session")?;
+            let ProverCommit { r1, r2 } = prover.commit(&k)?;
+            let nonce = verifier.challenge_nonce()?;
+            let c = verifier.challenge(&y1, &y2, &r1, &r2, &nonce, &Transcript::new(b"test"))?;
+            let s = prover.challenge_response(&k, &c, &x)?.s;
+            assert!(verifier.check(&y1, &y2, &r1, &r2, &c, &s)?);
+
+            Ok([y1, r1, c, s].iter().map(|value| value.to_vec()).collect())
+        }
+
+        #[cfg(feature = "openssl")]
+        {
+            assert_eq!(run(b"kat")?, run(b"kat")?);
+            assert_ne!(run(b"kat")?, run(b"other")?);
+        }
+
+        // Non-interactive proofs are reproducible as well
+        let params = RistrettoParams::from_seed(b"seed");
+        let prove = || -> Result<Vec<u8>, CryptoError> {
+            let prover =
+                ChaumPedersenProver::with_rng(params.clone(), Arc::new(SeededRandom::new(b"kat")?));
+            let x = prover.random()?;
+            let proof = prover.prove(&x, &Transcript::new(b"test"))?;
+            let ProverPublicKeys { y1, y2 } = prover.public_keys(&x)?;
+            let verifier = ChaumPedersenVerifier::new(params.clone())?;
+            assert!(verifier.verify(&y1, &y2, &proof, &Transcript::new(b"test"))?);
+
+            Ok([
+                params.encode_scalar(&proof.c)?,
+                params.encode_scalar(&proof.s)?,
+            ]
+            .concat())
+        };
+        assert_eq!(prove()?, prove()?);
+
+        Ok(())
+    }
+
+    fn hex(s: &str) -> Vec<u8> {
+        (0..s.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+            .collect()
+    }
+}
@@ -0,0 +1,164 @@
+pub mod params;
+pub mod prover;
+pub mod verifier;
+
+#[cfg(test)]
+mod tests {
+    use crate::error::CryptoError;
+    use curve25519_dalek::{ristretto::RistrettoPoint, scalar::Scalar};
+
+    use crate::{
+        prover::{Prover, ProverChallengeResponse, ProverCommit, ProverPublicKeys},
+        ristretto::{
+            params::RistrettoParams, prover::RistrettoProver, verifier::RistrettoVerifier,
+        },
+        transcript::Transcript,
+        verifier::{ProofTranscript, Verifier},
+    };
+
+    #[test]
+    fn test_ristretto_chaum_pedersen_protocol() -> Result<(), CryptoError> {
+        let mut precomputed = RistrettoParams::new()?;
+        precomputed.precompute();
+
+        for params in [RistrettoParams::new()?, precomputed] {
+            let prover = RistrettoProver::new(params.clone())?;
+            let verifier = RistrettoVerifier::new(params.clone())?;
+
+            let x = prover.random()?;
+            let ProverPublicKeys { y1, y2 } = prover.public_keys(&x)?;
+            let k = prover.random()?;
+            let ProverCommit { r1, r2 } = prover.commit(&k)?;
+            let c = verifier.random()?;
+            let ProverChallengeResponse { s } = prover.challenge_response(&k, &c, &x)?;
+
+            let valid = verifier.check(&y1, &y2, &r1, &r2, &c, &s)?;
+            assert!(
+                valid,
+                "Ristretto Chaum-Pedersen protocol verification failed"
+            );
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ristretto_incorrect_prover_secret() -> Result<(), CryptoError> {
+        let params = RistrettoParams::new()?;
+        let prover = RistrettoProver::new(params.clone())?;
+        let verifier = RistrettoVerifier::new(params.clone())?;
+
+        let x = prover.random()?;
+        let ProverPublicKeys { y1, y2 } = prover.public_keys(&x)?;
+        let k = prover.random()?;
+        let ProverCommit { r1, r2 } = prover.commit(&k)?;
+        let c = verifier.random()?;
+
+        // Use an incorrect secret for the challenge response
+        let incorrect_x = prover.random()?;
+        let ProverChallengeResponse { s } = prover.challenge_response(&k, &c, &incorrect_x)?;
+
+        let valid = verifier.check(&y1, &y2, &r1, &r2, &c, &s)?;
+        assert!(!valid, "Verification should fail with incorrect secret");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ristretto_tampered_commitment() -> Result<(), CryptoError> {
+        let params = RistrettoParams::new()?;
+        let prover = RistrettoProver::new(params.clone())?;
+        let verifier = RistrettoVerifier::new(params.clone())?;
+
+        let x = prover.random()?;
+        let ProverPublicKeys { y1, y2 } = prover.public_keys(&x)?;
+        let k = prover.random()?;
+        let ProverCommit { r1, r2 } = prover.commit(&k)?;
+        let c = verifier.random()?;
+        let ProverChallengeResponse { s } = prover.challenge_response(&k, &c, &x)?;
+
+        // Tamper with r1
+        let tampered_r1 = r1 + params.g;
+
+        let valid = verifier.check(&y1, &y2, &tampered_r1, &r2, &c, &s)?;
+        assert!(!valid, "Verification should fail with incorrect commitment");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_ristretto_non_interactive_proof() -> Result<(), CryptoError> {
+        let params = RistrettoParams::new()?;
+        let prover = RistrettoProver::new(params.clone())?;
+        let verifier = RistrettoVerifier::new(params.clone())?;
+
+        let x = prover.random()?;
+        let ProverPublicKeys { y1, y2 } = prover.public_keys(&x)?;
+        let proof = prover.prove(&x, &Transcript::new(b"batch-job-42"))?;
+
+        let valid = verifier.verify(&y1, &y2, &proof, &Transcript::new(b"batch-job-42"))?;
+        assert!(valid, "Non-interactive proof verification failed");
+
+        let valid = verifier.verify(&y1, &y2, &proof, &Transcript::new(b"batch-job-43"))?;
+        assert!(!valid, "Verification should fail with a different context");
+
+        Ok(())
+    }
+
+    // (y1, y2, r1, r2, c, s) of one honest run
+    type Run = (
+        RistrettoPoint,
+        RistrettoPoint,
+        RistrettoPoint,
+        RistrettoPoint,
+        Scalar,
+        Scalar,
+    );
+
+    fn transcripts(runs: &[Run]) -> Vec<ProofTranscript<'_, RistrettoPoint, Scalar>> {
+        runs.iter()
+            .map(|(y1, y2, r1, r2, c, s)| ProofTranscript {
+                y1,
+                y2,
+                r1,
+                r2,
+                c,
+                s,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_ristretto_check_batch() -> Result<(), CryptoError> {
+        let params = RistrettoParams::new()?;
+        let prover = RistrettoProver::new(params.clone())?;
+        let verifier = RistrettoVerifier::new(params.clone())?;
+
+        let mut runs = Vec::new();
+        for _ in 0..8 {
+            let x = prover.random()?;
+            let ProverPublicKeys { y1, y2 } = prover.public_keys(&x)?;
+            let k = prover.random()?;
+            let ProverCommit { r1, r2 } = prover.commit(&k)?;
+            let c = verifier.random()?;
+            let ProverChallengeResponse { s } = prover.challenge_response(&k, &c, &x)?;
+            runs.push((y1, y2, r1, r2, c, s));
+        }
+
+        let verdicts = verifier.check_batch(&transcripts(&runs))?;
+        assert_eq!(verdicts, vec![true; 8], "Honest batch failed verification");
+
+        // Tamper with s of run 2 and c of run 5
+        runs[2].5 += Scalar::ONE;
+        runs[5].4 += Scalar::ONE;
+
+        let verdicts = verifier.check_batch(&transcripts(&runs))?;
+        assert_eq!(
+            verdicts,
+            vec![true, true, false, true, true, false, true, true],
+            "Batch verification did not isolate the tampered runs"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,323 @@
+use std::sync::Arc;
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoBasepointTable, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::{Identity, VartimeMultiscalarMul};
+use zeroize::Zeroize;
+
+use crate::error::{CryptoError, ParamsError};
+use crate::group::Group;
+use crate::random::default_source;
+use crate::util::expand_hash;
+
+const HASH_TO_GROUP_DOMAIN: &[u8] = b"zkp_auth/ristretto255/hash-to-group/v1";
+
+// Size of an encoded scalar, and of the widest input `reduce_scalar` takes
+const SCALAR_LEN: usize = 32;
+const WIDE_LEN: usize = 64;
+
+/// Maps `seed` to a group element whose discrete log to any base is unknown,
+/// with the Ristretto one-way map on 64 uniform bytes.
+pub fn hash_to_group(seed: &[u8]) -> RistrettoPoint {
+    let mut bytes = [0u8; 64];
+    bytes.copy_from_slice(&expand_hash(HASH_TO_GROUP_DOMAIN, &[seed], 64));
+
+    RistrettoPoint::from_uniform_bytes(&bytes)
+}
+
+/// Fixed-base tables for `g` and `h`.
+pub struct RistrettoTables {
+    g: RistrettoBasepointTable,
+    h: RistrettoBasepointTable,
+}
+
+/// The Ristretto255 group, implemented in pure Rust by `curve25519-dalek`.
+#[derive(Clone)]
+pub struct RistrettoParams {
+    pub g: RistrettoPoint,
+    pub h: RistrettoPoint,
+    /// Seed `h` was derived from with `hash_to_group`, if known.
+    pub seed: Option<Vec<u8>>,
+    /// Fixed-base tables for `g` and `h`, built by `precompute`.
+    pub tables: Option<Arc<RistrettoTables>>,
+}
+
+impl RistrettoParams {
+    /// The standard base point as `g`, and `h` derived from a fresh seed so
+    /// nobody knows log_g(h).
+    pub fn new() -> Result<RistrettoParams, CryptoError> {
+        let mut seed = [0u8; 32];
+        default_source().fill_bytes(&mut seed)?;
+
+        Ok(RistrettoParams::from_seed(&seed))
+    }
+
+    pub fn with_params(g: RistrettoPoint, h: RistrettoPoint) -> RistrettoParams {
+        RistrettoParams {
+            g,
+            h,
+            seed: None,
+            tables: None,
+        }
+    }
+
+    /// The standard base point as `g`, and `h` derived deterministically from `seed`.
+    pub fn from_seed(seed: &[u8]) -> RistrettoParams {
+        let mut params =
+            RistrettoParams::with_params(RISTRETTO_BASEPOINT_POINT, hash_to_group(seed));
+        params.seed = Some(seed.to_vec());
+
+        params
+    }
+
+    /// Builds the fixed-base tables for `g` and `h`. Clones share them.
+    pub fn precompute(&mut self) {
+        self.tables = Some(Arc::new(RistrettoTables {
+            g: RistrettoBasepointTable::create(&self.g),
+            h: RistrettoBasepointTable::create(&self.h),
+        }));
+    }
+
+    /// Re-derives `h` from `seed` and checks it matches the one in use.
+    pub fn verify_generation(&self, seed: &[u8]) -> bool {
+        hash_to_group(seed) == self.h
+    }
+
+    /// Checks that g and h are distinct and not the identity. Every decodable
+    /// Ristretto element is in the prime-order group, so there is nothing else to check.
+    pub fn validate(&self) -> Result<(), ParamsError> {
+        self.check_element("g", &self.g)?;
+        self.check_element("h", &self.h)?;
+
+        if self.g == self.h {
+            return Err(ParamsError::SameGenerators);
+        }
+
+        Ok(())
+    }
+
+    /// Checks that `element` is not the identity.
+    pub fn check_element(
+        &self,
+        name: &'static str,
+        element: &RistrettoPoint,
+    ) -> Result<(), ParamsError> {
+        if *element == RistrettoPoint::identity() {
+            return Err(ParamsError::Identity(name));
+        }
+
+        Ok(())
+    }
+
+    fn mul_table(
+        &self,
+        point: &RistrettoPoint,
+        table: Option<&RistrettoBasepointTable>,
+        e: &Scalar,
+    ) -> RistrettoPoint {
+        match table {
+            Some(table) => table * e,
+            None => point * e,
+        }
+    }
+}
+
+impl Group for RistrettoParams {
+    type Element = RistrettoPoint;
+    type Scalar = Scalar;
+
+    fn g(&self) -> &RistrettoPoint {
+        &self.g
+    }
+
+    fn h(&self) -> &RistrettoPoint {
+        &self.h
+    }
+
+    fn identity(&self) -> Result<RistrettoPoint, CryptoError> {
+        Ok(RistrettoPoint::identity())
+    }
+
+    fn exp(&self, base: &RistrettoPoint, e: &Scalar) -> Result<RistrettoPoint, CryptoError> {
+        Ok(self.mul_table(base, None, e))
+    }
+
+    fn multi_exp(
+        &self,
+        bases: &[&RistrettoPoint],
+        exponents: &[&Scalar],
+    ) -> Result<RistrettoPoint, CryptoError> {
+        Ok(RistrettoPoint::vartime_multiscalar_mul(
+            exponents.iter().copied(),
+            bases.iter().copied(),
+        ))
+    }
+
+    fn element_eq(&self, a: &RistrettoPoint, b: &RistrettoPoint) -> Result<bool, CryptoError> {
+        Ok(a == b)
+    }
+
+    fn encode_element(&self, element: &RistrettoPoint) -> Result<Vec<u8>, CryptoError> {
+        Ok(element.compress().to_bytes().to_vec())
+    }
+
+    fn decode_element(&self, data: &[u8]) -> Result<RistrettoPoint, CryptoError> {
+        CompressedRistretto::from_slice(data)
+            .ok()
+            .and_then(|compressed| compressed.decompress())
+            .ok_or(CryptoError::InvalidEncoding)
+    }
+
+    fn check_element(
+        &self,
+        name: &'static str,
+        element: &RistrettoPoint,
+    ) -> Result<(), ParamsError> {
+        RistrettoParams::check_element(self, name, element)
+    }
+
+    fn description(&self) -> Result<Vec<Vec<u8>>, CryptoError> {
+        Ok(vec![
+            b"ristretto255".to_vec(),
+            self.encode_element(&self.g)?,
+            self.encode_element(&self.h)?,
+        ])
+    }
+
+    fn scalar_len(&self) -> usize {
+        SCALAR_LEN
+    }
+
+    // Reversed into a little-endian 64-byte buffer, which dalek reduces in
+    // constant time
+    fn reduce_scalar(&self, bytes: &[u8]) -> Result<Scalar, CryptoError> {
+        if bytes.len() > WIDE_LEN {
+            return Err(CryptoError::InvalidEncoding);
+        }
+
+        let mut wide = [0u8; WIDE_LEN];
+        for (byte, be) in wide.iter_mut().zip(bytes.iter().rev()) {
+            *byte = *be;
+        }
+        let scalar = Scalar::from_bytes_mod_order_wide(&wide);
+        wide.zeroize();
+
+        Ok(scalar)
+    }
+
+    fn encode_scalar(&self, scalar: &Scalar) -> Result<Vec<u8>, CryptoError> {
+        Ok(scalar.to_bytes().to_vec())
+    }
+
+    fn decode_scalar(&self, data: &[u8]) -> Result<Scalar, CryptoError> {
+        let bytes: [u8; SCALAR_LEN] = data.try_into().map_err(|_| CryptoError::InvalidEncoding)?;
+
+        Option::from(Scalar::from_canonical_bytes(bytes)).ok_or(CryptoError::InvalidEncoding)
+    }
+
+    fn scalar_add(&self, a: &Scalar, b: &Scalar) -> Result<Scalar, CryptoError> {
+        Ok(a + b)
+    }
+
+    fn scalar_sub(&self, a: &Scalar, b: &Scalar) -> Result<Scalar, CryptoError> {
+        Ok(a - b)
+    }
+
+    fn scalar_mul(&self, a: &Scalar, b: &Scalar) -> Result<Scalar, CryptoError> {
+        Ok(a * b)
+    }
+
+    fn scalar_invert(&self, a: &Scalar) -> Result<Option<Scalar>, CryptoError> {
+        Ok((*a != Scalar::ZERO).then(|| a.invert()))
+    }
+
+    fn exp_g(&self, e: &Scalar) -> Result<RistrettoPoint, CryptoError> {
+        let table = self.tables.as_ref().map(|tables| &tables.g);
+        Ok(self.mul_table(&self.g, table, e))
+    }
+
+    fn exp_h(&self, e: &Scalar) -> Result<RistrettoPoint, CryptoError> {
+        let table = self.tables.as_ref().map(|tables| &tables.h);
+        Ok(self.mul_table(&self.h, table, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::CryptoError;
+
+    #[test]
+    fn test_hash_to_group() -> Result<(), ParamsError> {
+        let params = RistrettoParams::from_seed(b"seed");
+        params.validate()?;
+
+        assert!(
+            params.verify_generation(b"seed"),
+            "h does not match its seed"
+        );
+        assert!(
+            !params.verify_generation(b"other seed"),
+            "h matches a different seed"
+        );
+        assert!(
+            params.g != hash_to_group(b"other seed"),
+            "hash_to_group hit the base point"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_element() -> Result<(), CryptoError> {
+        let params = RistrettoParams::from_seed(b"seed");
+
+        assert!(matches!(
+            params.check_element("y1", &RistrettoPoint::identity()),
+            Err(ParamsError::Identity("y1"))
+        ));
+        assert!(params.check_element("y1", &params.h).is_ok());
+
+        // Not every 32-byte string is a valid encoding
+        assert!(params.decode_element(&[0xff; 32]).is_err());
+        assert!(params.decode_element(&[0; 31]).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scalar_reduction() -> Result<(), CryptoError> {
+        let params = RistrettoParams::from_seed(b"seed");
+
+        // l = 2^252 + 27742317777372353535851937790883648493, big-endian
+        let mut order = [0u8; 32];
+        order[0] = 0x10;
+        order[16..].copy_from_slice(&[
+            0x14, 0xde, 0xf9, 0xde, 0xa2, 0xf7, 0x9c, 0xd6, 0x58, 0x12, 0x63, 0x1a, 0x5c, 0xf5,
+            0xd3, 0xed,
+        ]);
+        assert_eq!(params.reduce_scalar(&order)?, Scalar::ZERO);
+        assert!(params
+            .decode_scalar(&params.encode_scalar(&-Scalar::ONE)?)
+            .is_ok());
+
+        // (l + 1) * 2^256 + (l + 1) = 2^256 + 1 mod l, wider than 32 bytes
+        let mut order_plus_one = order;
+        order_plus_one[31] += 1;
+        let wide = [order_plus_one, order_plus_one].concat();
+        let mut two_256 = [0u8; 64];
+        two_256[32] = 1;
+        let expected = Scalar::from_bytes_mod_order_wide(&two_256) + Scalar::ONE;
+        assert_eq!(params.reduce_scalar(&wide)?, expected);
+
+        // Little-endian encoding, and only the canonical one decodes
+        assert_eq!(params.encode_scalar(&Scalar::ONE)?[0], 1);
+        let mut l = order;
+        l.reverse();
+        assert!(params.decode_scalar(&l).is_err());
+        assert!(params.reduce_scalar(&[0u8; 65]).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,5 @@
+use crate::prover::ChaumPedersenProver;
+
+use super::params::RistrettoParams;
+
+pub type RistrettoProver = ChaumPedersenProver<RistrettoParams>;
@@ -0,0 +1,5 @@
+use crate::verifier::ChaumPedersenVerifier;
+
+use super::params::RistrettoParams;
+
+pub type RistrettoVerifier = ChaumPedersenVerifier<RistrettoParams>;
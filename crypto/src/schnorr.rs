@@ -0,0 +1,525 @@
+//! Single-base Schnorr: identification (`y1 = g^x`, one commitment `g^k`)
+//! and signatures under the same `x`. Deployments that don't need the second
+//! base `h` send and store half as much as with Chaum-Pedersen and verify one
+//! equation instead of two. A Chaum-Pedersen registration's `y1` is a Schnorr
+//! key as well, so either kind of user can sign.
+//!
+//! Answering a challenge in the interactive login is producing `s = k - c·x`
+//! for a committed `g^k`. A signature is the same answer for a challenge
+//! hashed from the message, so a verifier that could pick `c` after seeing
+//! `r1` could obtain a signature on any message. The client therefore only
+//! answers challenges it re-derives from the login transcript
+//! (`fiat_shamir::interactive_challenge`, `schnorr::interactive_challenge`),
+//! which are domain-separated from the one signatures are hashed under.
+
+use std::sync::Arc;
+
+use crate::error::{CryptoError, PasswordError};
+use crate::group::Group;
+use crate::identification::{self, Challenge, IdentificationProver, IdentificationVerifier};
+use crate::password::PasswordKdf;
+use crate::prover::{ChaumPedersenProver, ProverChallengeResponse, ProverPublicKeys};
+use crate::random::{default_source, RandomSource};
+use crate::secret::SecretScalar;
+use crate::transcript::Transcript;
+use crate::util::challenge_nonce;
+use crate::verifier::ChaumPedersenVerifier;
+
+const PROTOCOL: &[u8] = b"schnorr-identification/v1";
+const SIGNATURE_PROTOCOL: &[u8] = b"zkp_auth/schnorr-signature/v2";
+
+/// The prover's side of Schnorr identification over any `Group`.
+pub struct SchnorrProver<G: Group> {
+    pub params: G,
+    /// Where `x`, `k` and the nonce hedges come from.
+    pub rng: Arc<dyn RandomSource>,
+}
+
+impl<G: Group> SchnorrProver<G> {
+    pub fn new(params: G) -> Self {
+        SchnorrProver::with_rng(params, default_source())
+    }
+
+    /// A prover drawing its randomness from `rng` instead of the default source.
+    pub fn with_rng(params: G, rng: Arc<dyn RandomSource>) -> Self {
+        SchnorrProver { params, rng }
+    }
+
+    /// Random secret in `[0, q)`, used for both `x` and `k`.
+    pub fn random(&self) -> Result<SecretScalar<G::Scalar>, CryptoError> {
+        SecretScalar::random(&*self.rng, &self.params)
+    }
+
+    /// Commitment nonce `k` hashed from `x`, fresh randomness and the session
+    /// `context`, as in `Prover::hedged_nonce`.
+    pub fn hedged_nonce(
+        &self,
+        x: &SecretScalar<G::Scalar>,
+        context: &[u8],
+    ) -> Result<SecretScalar<G::Scalar>, CryptoError> {
+        SecretScalar::hedged(&*self.rng, &self.params, x, context)
+    }
+
+    /// `y1 = g^x`.
+    pub fn public_key(&self, x: &SecretScalar<G::Scalar>) -> Result<G::Element, CryptoError> {
+        self.params.exp_g(x.expose())
+    }
+
+    /// `r1 = g^k`.
+    pub fn commit(&self, k: &SecretScalar<G::Scalar>) -> Result<G::Element, CryptoError> {
+        self.params.exp_g(k.expose())
+    }
+
+    pub fn challenge_response(
+        &self,
+        k: &SecretScalar<G::Scalar>,
+        c: &G::Scalar,
+        x: &SecretScalar<G::Scalar>,
+    ) -> Result<ProverChallengeResponse<G::Scalar>, CryptoError> {
+        Ok(ProverChallengeResponse {
+            s: respond(&self.params, k, c, x)?,
+        })
+    }
+}
+
+/// The verifier's side of Schnorr identification over any `Group`.
+pub struct SchnorrVerifier<G: Group> {
+    pub params: G,
+    /// Where challenge nonces come from.
+    pub rng: Arc<dyn RandomSource>,
+}
+
+impl<G: Group> SchnorrVerifier<G> {
+    pub fn new(params: G) -> Self {
+        SchnorrVerifier::with_rng(params, default_source())
+    }
+
+    /// A verifier drawing its randomness from `rng` instead of the default source.
+    pub fn with_rng(params: G, rng: Arc<dyn RandomSource>) -> Self {
+        SchnorrVerifier { params, rng }
+    }
+
+    /// Fresh randomness for an interactive challenge, sent to the prover with `c`.
+    pub fn challenge_nonce(&self) -> Result<Vec<u8>, CryptoError> {
+        challenge_nonce(&*self.rng)
+    }
+
+    /// Challenge for an interactive run (`interactive_challenge`).
+    pub fn challenge(
+        &self,
+        y1: &G::Element,
+        r1: &G::Element,
+        nonce: &[u8],
+        transcript: &Transcript,
+    ) -> Result<G::Scalar, CryptoError> {
+        interactive_challenge(&self.params, y1, r1, nonce, transcript)
+    }
+
+    /// Checks `r1 == g^s * y1^c`, with `y1` and `r1` already checked with
+    /// `check_element`.
+    pub fn check(
+        &self,
+        y1: &G::Element,
+        r1: &G::Element,
+        c: &G::Scalar,
+        s: &G::Scalar,
+    ) -> Result<bool, CryptoError> {
+        let expected = self.params.exp_g_mul(s, y1, c)?;
+
+        self.params.element_eq(&expected, r1)
+    }
+}
+
+/// Challenge for an interactive identification run: the run and the
+/// verifier's fresh `nonce` appended to `transcript`, so the prover can
+/// re-derive it like `fiat_shamir::interactive_challenge`.
+pub fn interactive_challenge<G: Group>(
+    group: &G,
+    y1: &G::Element,
+    r1: &G::Element,
+    nonce: &[u8],
+    transcript: &Transcript,
+) -> Result<G::Scalar, CryptoError> {
+    let mut transcript = transcript.clone();
+    transcript.append_message(b"protocol", PROTOCOL);
+    transcript.append_params(group)?;
+    transcript.append_element(group, b"y1", y1)?;
+    transcript.append_element(group, b"r1", r1)?;
+    transcript.append_message(b"verifier-nonce", nonce);
+
+    transcript.challenge_scalar(b"c", group)
+}
+
+/// Signature `(c, s)` with `c = H(transcript, params, y1, g^s·y1^c)`.
+pub struct SchnorrSignature<S> {
+    pub c: S,
+    pub s: S,
+}
+
+/// Signs with `x` over any `Group`.
+pub struct SchnorrSigner<G: Group> {
+    pub params: G,
+    /// Where the nonce hedges come from.
+    pub rng: Arc<dyn RandomSource>,
+}
+
+impl<G: Group> SchnorrSigner<G> {
+    pub fn new(params: G) -> Self {
+        SchnorrSigner::with_rng(params, default_source())
+    }
+
+    /// A signer drawing its randomness from `rng` instead of the default source.
+    pub fn with_rng(params: G, rng: Arc<dyn RandomSource>) -> Self {
+        SchnorrSigner { params, rng }
+    }
+
+    /// Signs `transcript`, which carries the message along with whatever
+    /// context it is approved in. The nonce is hedged with the transcript, so
+    /// even a repeating `rng` never signs two messages with the same `k`.
+    pub fn sign(
+        &self,
+        x: &SecretScalar<G::Scalar>,
+        transcript: &Transcript,
+    ) -> Result<SchnorrSignature<G::Scalar>, CryptoError> {
+        let k = SecretScalar::hedged(&*self.rng, &self.params, x, &transcript.digest())?;
+        let y1 = self.params.exp_g(x.expose())?;
+        let r = self.params.exp_g(k.expose())?;
+        let c = challenge(&self.params, &y1, &r, transcript)?;
+
+        let s = respond(&self.params, &k, &c, x)?;
+
+        Ok(SchnorrSignature { c, s })
+    }
+}
+
+/// Checks signatures against `y1` over any `Group`.
+pub struct SchnorrSignatureVerifier<G: Group> {
+    pub params: G,
+}
+
+impl<G: Group> SchnorrSignatureVerifier<G> {
+    pub fn new(params: G) -> Self {
+        SchnorrSignatureVerifier { params }
+    }
+
+    /// Checks `signature` on `transcript` under `y1`, which must already have
+    /// passed `check_element`. `decode_scalar` only accepts the canonical `s`
+    /// in `[0, q)`, so signatures are not malleable.
+    pub fn verify(
+        &self,
+        y1: &G::Element,
+        transcript: &Transcript,
+        signature: &SchnorrSignature<G::Scalar>,
+    ) -> Result<bool, CryptoError> {
+        let r = self.params.exp_g_mul(&signature.s, y1, &signature.c)?;
+
+        Ok(challenge(&self.params, y1, &r, transcript)? == signature.c)
+    }
+}
+
+/// Identification protocols whose public key includes a Schnorr key
+/// `y1 = g^x`, which signatures can be checked against as well.
+pub trait SchnorrKey: IdentificationVerifier {
+    type Group: Group;
+
+    fn group(&self) -> &Self::Group;
+    fn schnorr_key(key: &Self::PublicKey) -> &<Self::Group as Group>::Element;
+}
+
+/// The prover's side of `SchnorrKey`: the `x` to sign with.
+pub trait SchnorrSecret: IdentificationProver {
+    type Group: Group;
+
+    fn group(&self) -> &Self::Group;
+    fn schnorr_secret(secret: &Self::Secret) -> &SecretScalar<<Self::Group as Group>::Scalar>;
+}
+
+impl<G: Group> IdentificationProver for SchnorrProver<G> {
+    type Secret = SecretScalar<G::Scalar>;
+    type PublicKey = G::Element;
+    type Nonce = SecretScalar<G::Scalar>;
+    type Commitment = G::Element;
+    type Response = G::Scalar;
+    type Scalar = G::Scalar;
+
+    fn generate_secret(&self) -> Result<SecretScalar<G::Scalar>, CryptoError> {
+        self.random()
+    }
+
+    fn secret_from_password(
+        &self,
+        kdf: &PasswordKdf,
+        user: &str,
+        password: &[u8],
+        salt: &[u8],
+    ) -> Result<SecretScalar<G::Scalar>, PasswordError> {
+        kdf.derive_secret(&self.params, user, password, salt)
+    }
+
+    fn public_key(&self, x: &SecretScalar<G::Scalar>) -> Result<G::Element, CryptoError> {
+        SchnorrProver::public_key(self, x)
+    }
+
+    fn commitment(
+        &self,
+        x: &SecretScalar<G::Scalar>,
+        context: &[u8],
+    ) -> Result<(SecretScalar<G::Scalar>, G::Element), CryptoError> {
+        let k = self.hedged_nonce(x, context)?;
+        let r1 = self.commit(&k)?;
+
+        Ok((k, r1))
+    }
+
+    fn accepts_challenge(
+        &self,
+        y1: &G::Element,
+        r1: &G::Element,
+        challenge: &Challenge<G::Scalar>,
+        transcript: &Transcript,
+    ) -> Result<bool, CryptoError> {
+        let expected = interactive_challenge(&self.params, y1, r1, &challenge.nonce, transcript)?;
+
+        Ok(challenge.c.as_ref() == Some(&expected))
+    }
+
+    fn respond(
+        &self,
+        x: &SecretScalar<G::Scalar>,
+        k: &SecretScalar<G::Scalar>,
+        challenge: &Challenge<G::Scalar>,
+        _transcript: &Transcript,
+    ) -> Result<G::Scalar, CryptoError> {
+        respond(&self.params, k, challenge.scalar()?, x)
+    }
+}
+
+impl<G: Group> IdentificationVerifier for SchnorrVerifier<G> {
+    type PublicKey = G::Element;
+    type Commitment = G::Element;
+    type Response = G::Scalar;
+    type Scalar = G::Scalar;
+
+    fn challenge(
+        &self,
+        y1: &G::Element,
+        r1: &G::Element,
+        transcript: &Transcript,
+    ) -> Result<Challenge<G::Scalar>, CryptoError> {
+        let nonce = self.challenge_nonce()?;
+        let c = SchnorrVerifier::challenge(self, y1, r1, &nonce, transcript)?;
+
+        Ok(Challenge { c: Some(c), nonce })
+    }
+
+    fn decoy_challenge(
+        &self,
+        transcript: &Transcript,
+    ) -> Result<Challenge<G::Scalar>, CryptoError> {
+        identification::decoy_challenge(&*self.rng, &self.params, transcript)
+    }
+
+    fn check(
+        &self,
+        y1: &G::Element,
+        r1: &G::Element,
+        challenge: &Challenge<G::Scalar>,
+        s: &G::Scalar,
+        _transcript: &Transcript,
+    ) -> Result<bool, CryptoError> {
+        SchnorrVerifier::check(self, y1, r1, challenge.scalar()?, s)
+    }
+}
+
+impl<G: Group> SchnorrKey for SchnorrVerifier<G> {
+    type Group = G;
+
+    fn group(&self) -> &G {
+        &self.params
+    }
+
+    fn schnorr_key(y1: &G::Element) -> &G::Element {
+        y1
+    }
+}
+
+impl<G: Group> SchnorrSecret for SchnorrProver<G> {
+    type Group = G;
+
+    fn group(&self) -> &G {
+        &self.params
+    }
+
+    fn schnorr_secret(x: &SecretScalar<G::Scalar>) -> &SecretScalar<G::Scalar> {
+        x
+    }
+}
+
+// A Chaum-Pedersen registration's y1 is a Schnorr key
+impl<G: Group> SchnorrKey for ChaumPedersenVerifier<G> {
+    type Group = G;
+
+    fn group(&self) -> &G {
+        &self.params
+    }
+
+    fn schnorr_key(keys: &ProverPublicKeys<G::Element>) -> &G::Element {
+        &keys.y1
+    }
+}
+
+impl<G: Group> SchnorrSecret for ChaumPedersenProver<G> {
+    type Group = G;
+
+    fn group(&self) -> &G {
+        &self.params
+    }
+
+    fn schnorr_secret(x: &SecretScalar<G::Scalar>) -> &SecretScalar<G::Scalar> {
+        x
+    }
+}
+
+// s = k - c * x mod q
+fn respond<G: Group>(
+    group: &G,
+    k: &SecretScalar<G::Scalar>,
+    c: &G::Scalar,
+    x: &SecretScalar<G::Scalar>,
+) -> Result<G::Scalar, CryptoError> {
+    // c * x reveals x to anyone who knows c, so wipe it as well
+    let cx = SecretScalar::new(group.scalar_mul(c, x.expose())?);
+
+    group.scalar_sub(k.expose(), cx.expose())
+}
+
+fn challenge<G: Group>(
+    group: &G,
+    y1: &G::Element,
+    r: &G::Element,
+    transcript: &Transcript,
+) -> Result<G::Scalar, CryptoError> {
+    let mut transcript = transcript.clone();
+    transcript.append_message(b"protocol", SIGNATURE_PROTOCOL);
+    transcript.append_params(group)?;
+    transcript.append_element(group, b"y1", y1)?;
+    transcript.append_element(group, b"r", r)?;
+
+    transcript.challenge_scalar(b"c", group)
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "openssl")]
+    use openssl::nid::Nid;
+
+    use super::*;
+    #[cfg(feature = "openssl")]
+    use crate::dl::{groups::NamedGroup, params::DlParams};
+    #[cfg(feature = "openssl")]
+    use crate::ec::params::EcParams;
+    use crate::group::GroupScalar;
+    use crate::prover::{ChaumPedersenProver, Prover, ProverPublicKeys};
+    use crate::ristretto::params::RistrettoParams;
+
+    fn action(action: &[u8]) -> Transcript {
+        let mut transcript = Transcript::new(b"test");
+        transcript.append_message(b"action", action);
+        transcript
+    }
+
+    fn check_schnorr<G: Group>(group: G) -> Result<(), CryptoError> {
+        let prover = ChaumPedersenProver::new(group.clone())?;
+        let signer = SchnorrSigner::new(group.clone());
+        let verifier = SchnorrSignatureVerifier::new(group.clone());
+
+        // The registered login key is the verification key
+        let x = prover.random()?;
+        let ProverPublicKeys { y1, y2 } = prover.public_keys(&x)?;
+
+        let signature = signer.sign(&x, &action(b"payout 42 to alice"))?;
+        assert!(verifier.verify(&y1, &action(b"payout 42 to alice"), &signature)?);
+        assert!(!verifier.verify(&y1, &action(b"payout 43 to alice"), &signature)?);
+        assert!(!verifier.verify(&y2, &action(b"payout 42 to alice"), &signature)?);
+        let other = prover.public_keys(&prover.random()?)?;
+        assert!(!verifier.verify(&other.y1, &action(b"payout 42 to alice"), &signature)?);
+
+        // The same message approved in another context does not verify
+        let mut elsewhere = action(b"payout 42 to alice");
+        elsewhere.append_message(b"nonce", b"another challenge");
+        assert!(!verifier.verify(&y1, &elsewhere, &signature)?);
+
+        // Hedged nonces: signing twice gives different, equally valid signatures
+        let again = signer.sign(&x, &action(b"payout 42 to alice"))?;
+        assert_ne!(signature.c, again.c);
+        assert!(verifier.verify(&y1, &action(b"payout 42 to alice"), &again)?);
+
+        // s + q would give the same commitment, but does not decode (see the
+        // group tests); any other s fails
+        let one = group.reduce_scalar(&[1])?;
+        let malleated = SchnorrSignature {
+            c: signature.c.try_clone()?,
+            s: group.scalar_add(&signature.s, &one)?,
+        };
+        assert!(!verifier.verify(&y1, &action(b"payout 42 to alice"), &malleated)?);
+
+        Ok(())
+    }
+
+    fn check_identification<G: Group>(group: G) -> Result<(), CryptoError> {
+        let prover = SchnorrProver::new(group.clone());
+        let verifier = SchnorrVerifier::new(group.clone());
+        let session = Transcript::new(b"test");
+
+        let x = prover.random()?;
+        let y1 = prover.public_key(&x)?;
+        let k = prover.hedged_nonce(&x, b"session")?;
+        let r1 = prover.commit(&k)?;
+
+        // The prover re-derives the challenge, which is fresh for every nonce
+        let nonce = verifier.challenge_nonce()?;
+        let c = verifier.challenge(&y1, &r1, &nonce, &session)?;
+        assert_eq!(
+            c,
+            interactive_challenge(&group, &y1, &r1, &nonce, &session)?
+        );
+        let other_nonce = verifier.challenge_nonce()?;
+        assert_ne!(c, verifier.challenge(&y1, &r1, &other_nonce, &session)?);
+
+        let ProverChallengeResponse { s } = prover.challenge_response(&k, &c, &x)?;
+        assert!(verifier.check(&y1, &r1, &c, &s)?);
+
+        // Wrong secret, wrong response, wrong commitment
+        let other = prover.random()?;
+        let ProverChallengeResponse { s: forged } = prover.challenge_response(&k, &c, &other)?;
+        assert!(!verifier.check(&y1, &r1, &c, &forged)?);
+        let shifted = group.scalar_add(&s, &group.reduce_scalar(&[1])?)?;
+        assert!(!verifier.check(&y1, &r1, &c, &shifted)?);
+        assert!(!verifier.check(&y1, &prover.commit(&other)?, &c, &s)?);
+
+        // Identification and signatures never share a challenge, even on one transcript
+        assert_ne!(c, challenge(&group, &y1, &r1, &session)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_schnorr_identification() -> Result<(), CryptoError> {
+        #[cfg(feature = "openssl")]
+        {
+            check_identification(DlParams::from_named_group(NamedGroup::Ffdhe2048, b"seed")?)?;
+            check_identification(EcParams::new(Nid::X9_62_PRIME256V1)?)?;
+        }
+        check_identification(RistrettoParams::new()?)
+    }
+
+    #[test]
+    fn test_schnorr() -> Result<(), CryptoError> {
+        #[cfg(feature = "openssl")]
+        {
+            check_schnorr(DlParams::from_named_group(NamedGroup::Ffdhe2048, b"seed")?)?;
+            check_schnorr(EcParams::new(Nid::X9_62_PRIME256V1)?)?;
+        }
+        check_schnorr(RistrettoParams::new()?)
+    }
+}
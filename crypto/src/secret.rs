@@ -0,0 +1,162 @@
+use std::fmt;
+
+use zeroize::Zeroize;
+
+use crate::error::CryptoError;
+use crate::group::{Group, GroupScalar};
+use crate::random::RandomSource;
+use crate::util::expand_hash;
+
+const HEDGED_NONCE_DOMAIN: &[u8] = b"zkp_auth/chaum-pedersen/hedged-nonce/v1";
+
+// Fresh randomness mixed into every hedged nonce
+const HEDGE_BYTES: usize = 32;
+
+/// Extra bytes hashed beyond the size of the order so the reduction bias is negligible.
+pub const WIDE_MARGIN_BYTES: usize = 16;
+
+/// A secret exponent or scalar (the prover's `x` or nonce `k`).
+///
+/// The wrapped value is marked secret with `GroupScalar::set_secret`, so the
+/// backend takes its constant-time paths, and is wiped on drop. `Debug`
+/// never prints it.
+pub struct SecretScalar<S: GroupScalar>(S);
+
+impl<S: GroupScalar> SecretScalar<S> {
+    pub fn new(mut value: S) -> SecretScalar<S> {
+        value.set_secret();
+
+        SecretScalar(value)
+    }
+
+    /// Random scalar in `[0, q)` of `group` drawn from `source`.
+    pub fn random<G: Group<Scalar = S>>(
+        source: &dyn RandomSource,
+        group: &G,
+    ) -> Result<SecretScalar<S>, CryptoError> {
+        let mut bytes = vec![0u8; group.scalar_len() + WIDE_MARGIN_BYTES];
+        source.fill_bytes(&mut bytes)?;
+        let random = SecretScalar::reduce_wide(group, &bytes);
+        bytes.zeroize();
+
+        random
+    }
+
+    /// Nonce in `[0, q)` hashed from the secret `x`, fresh randomness from
+    /// `source` and `context`, like RFC 6979 with added randomness: two runs
+    /// never share a nonce unless the source repeats *and* `x` and `context`
+    /// are the same, and a predictable source alone does not reveal it.
+    pub fn hedged<G: Group<Scalar = S>>(
+        source: &dyn RandomSource,
+        group: &G,
+        x: &SecretScalar<S>,
+        context: &[u8],
+    ) -> Result<SecretScalar<S>, CryptoError> {
+        let mut hedge = [0u8; HEDGE_BYTES];
+        source.fill_bytes(&mut hedge)?;
+        let mut x_bytes = group.encode_scalar(x.expose())?;
+
+        let len = group.scalar_len() + WIDE_MARGIN_BYTES;
+        let mut wide = expand_hash(HEDGED_NONCE_DOMAIN, &[&x_bytes, &hedge, context], len);
+        let k = SecretScalar::reduce_wide(group, &wide);
+        hedge.zeroize();
+        x_bytes.zeroize();
+        wide.zeroize();
+
+        k
+    }
+
+    /// Reduces `bytes`, which should be `WIDE_MARGIN_BYTES` longer than a
+    /// scalar of `group`, into `[0, q)` in constant time.
+    pub fn reduce_wide<G: Group<Scalar = S>>(
+        group: &G,
+        bytes: &[u8],
+    ) -> Result<SecretScalar<S>, CryptoError> {
+        Ok(SecretScalar::new(group.reduce_scalar(bytes)?))
+    }
+
+    pub fn try_clone(&self) -> Result<SecretScalar<S>, CryptoError> {
+        Ok(SecretScalar::new(self.0.try_clone()?))
+    }
+
+    /// Borrows the value for use in a group operation; don't copy it out.
+    pub fn expose(&self) -> &S {
+        &self.0
+    }
+}
+
+impl<S: GroupScalar> Drop for SecretScalar<S> {
+    fn drop(&mut self) {
+        self.0.wipe();
+    }
+}
+
+impl<S: GroupScalar> fmt::Debug for SecretScalar<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretScalar(<redacted>)")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "openssl")]
+    use openssl::{bn::BigNum, nid::Nid};
+
+    use super::*;
+    #[cfg(feature = "openssl")]
+    use crate::ec::params::EcParams;
+    use crate::error::CryptoError;
+    use crate::random::{OsRandom, SeededRandom};
+    use crate::ristretto::params::RistrettoParams;
+
+    #[cfg(feature = "openssl")]
+    #[test]
+    fn test_secret_scalar() -> Result<(), CryptoError> {
+        let secret = SecretScalar::new(BigNum::from_u32(0xdead_beef)?);
+
+        assert!(secret.expose().is_const_time(), "const-time flag not set");
+        assert_eq!(format!("{:?}", secret), "SecretScalar(<redacted>)");
+
+        let copy = secret.try_clone()?;
+        assert!(
+            copy.expose().is_const_time(),
+            "clone lost the const-time flag"
+        );
+        assert_eq!(copy.expose(), secret.expose());
+
+        Ok(())
+    }
+
+    fn check_hedged_nonce<G: Group>(group: &G) -> Result<(), CryptoError> {
+        let x = SecretScalar::random(&OsRandom, group)?;
+
+        let k1 = SecretScalar::hedged(&OsRandom, group, &x, b"alice")?;
+        let k2 = SecretScalar::hedged(&OsRandom, group, &x, b"alice")?;
+        assert!(k1.expose() != k2.expose(), "fresh randomness not mixed in");
+
+        // With a repeating source, the context still separates the nonces
+        let hedged = |context: &[u8]| {
+            SecretScalar::hedged(&SeededRandom::new(b"stuck")?, group, &x, context)
+        };
+        assert!(hedged(b"alice")?.expose() == hedged(b"alice")?.expose());
+        assert!(hedged(b"alice")?.expose() != hedged(b"bob")?.expose());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hedged_nonce() -> Result<(), CryptoError> {
+        #[cfg(feature = "openssl")]
+        {
+            let ec = EcParams::new(Nid::SECP256K1)?;
+            check_hedged_nonce(&ec)?;
+
+            let k =
+                SecretScalar::hedged(&OsRandom, &ec, &SecretScalar::random(&OsRandom, &ec)?, b"")?;
+            assert!(k.expose().is_const_time(), "const-time flag not set");
+            assert!(k.expose() < ec.order());
+        }
+
+        check_hedged_nonce(&RistrettoParams::new()?)
+    }
+}
@@ -0,0 +1,139 @@
+pub mod proof;
+pub mod prover;
+pub mod relation;
+pub mod verifier;
+
+#[cfg(test)]
+mod tests {
+    use std::error::Error;
+
+    #[cfg(feature = "openssl")]
+    use openssl::nid::Nid;
+
+    use super::{
+        prover::SigmaProver,
+        relation::{ElementVar, LinearRelation},
+        verifier::SigmaVerifier,
+    };
+    #[cfg(feature = "openssl")]
+    use crate::dl::{groups::NamedGroup, params::DlParams};
+    #[cfg(feature = "openssl")]
+    use crate::ec::params::EcParams;
+    use crate::error::SigmaError;
+    use crate::group::{Group, GroupScalar};
+    use crate::random::OsRandom;
+    use crate::ristretto::params::RistrettoParams;
+    use crate::secret::SecretScalar;
+    use crate::transcript::Transcript;
+
+    // y1 = g^x, y2 = h^x, y3 = g^a * h^b
+    fn relation<G: Group>(
+        group: &G,
+        y: [G::Element; 3],
+    ) -> Result<LinearRelation<G>, Box<dyn Error>> {
+        let mut relation = LinearRelation::new(group.clone());
+        let (x, a, b) = (
+            relation.scalar("x"),
+            relation.scalar("a"),
+            relation.scalar("b"),
+        );
+        let (g, h) = (relation.g(), relation.h());
+        let [y1, y2, y3] = y;
+        let y: Vec<ElementVar> = [("y1", y1), ("y2", y2), ("y3", y3)]
+            .into_iter()
+            .map(|(name, element)| relation.element(name, element))
+            .collect::<Result<_, _>>()?;
+
+        relation.constrain(y[0], &[(x, g)]);
+        relation.constrain(y[1], &[(x, h)]);
+        relation.constrain(y[2], &[(a, g), (b, h)]);
+
+        Ok(relation)
+    }
+
+    fn public_values<G: Group>(
+        group: &G,
+        witness: &[SecretScalar<G::Scalar>],
+    ) -> Result<[G::Element; 3], Box<dyn Error>> {
+        let [x, a, b] = witness else { unreachable!() };
+
+        let ga = group.exp_g(a.expose())?;
+        let hb = group.exp_h(b.expose())?;
+        let one = group.reduce_scalar(&[1])?;
+        Ok([
+            group.exp_g(x.expose())?,
+            group.exp_h(x.expose())?,
+            group.multi_exp(&[&ga, &hb], &[&one, &one])?,
+        ])
+    }
+
+    fn check_relation<G: Group>(group: G) -> Result<(), Box<dyn Error>> {
+        let witness = [
+            SecretScalar::random(&OsRandom, &group)?,
+            SecretScalar::random(&OsRandom, &group)?,
+            SecretScalar::random(&OsRandom, &group)?,
+        ];
+        let prover = SigmaProver::new(relation(&group, public_values(&group, &witness)?)?);
+        let verifier = SigmaVerifier::new(relation(&group, public_values(&group, &witness)?)?);
+
+        // Interactive
+        let (nonces, commitments) = prover.commit(&witness, &Transcript::new(b"session"))?;
+        let c = verifier.random()?;
+        let s = prover.respond(&nonces, &witness, &c)?;
+        assert!(
+            verifier.check(&commitments, &c, &s)?,
+            "interactive run failed"
+        );
+        let other_c = verifier.random()?;
+        assert!(!verifier.check(&commitments, &other_c, &s)?);
+
+        // Non-interactive, through the encoding
+        let proof = prover.prove(&witness, &Transcript::new(b"context"))?;
+        let decoded = verifier.decode_proof(&proof.encode(&group)?)?;
+        assert!(
+            verifier.verify(&decoded, &Transcript::new(b"context"))?,
+            "proof failed"
+        );
+        assert!(!verifier.verify(&decoded, &Transcript::new(b"other context"))?);
+
+        let mut tampered = verifier.decode_proof(&proof.encode(&group)?)?;
+        tampered.s[2] = group.scalar_add(&tampered.s[2], &group.reduce_scalar(&[1])?)?;
+        assert!(!verifier.verify(&tampered, &Transcript::new(b"context"))?);
+
+        // A witness that only satisfies the first two equations
+        let copy = |w: &SecretScalar<G::Scalar>| w.expose().try_clone().map(SecretScalar::new);
+        let wrong = [copy(&witness[0])?, copy(&witness[2])?, copy(&witness[1])?];
+        assert!(matches!(
+            prover.prove(&wrong, &Transcript::new(b"context")),
+            Err(SigmaError::Unsatisfied(2))
+        ));
+        assert!(matches!(
+            prover.prove(&witness[..2], &Transcript::new(b"context")),
+            Err(SigmaError::WitnessLength)
+        ));
+
+        let encoded = proof.encode(&group)?;
+        assert!(matches!(
+            verifier.decode_proof(&encoded[1..]),
+            Err(SigmaError::MalformedProof)
+        ));
+        let mut out_of_range = encoded.clone();
+        out_of_range[..group.scalar_len()].fill(0xff);
+        assert!(matches!(
+            verifier.decode_proof(&out_of_range),
+            Err(SigmaError::MalformedProof)
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_linear_relation() -> Result<(), Box<dyn Error>> {
+        #[cfg(feature = "openssl")]
+        {
+            check_relation(DlParams::from_named_group(NamedGroup::Ffdhe2048, b"seed")?)?;
+            check_relation(EcParams::new(Nid::X9_62_PRIME256V1)?)?;
+        }
+        check_relation(RistrettoParams::new()?)
+    }
+}
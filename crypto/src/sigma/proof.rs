@@ -0,0 +1,69 @@
+use super::relation::LinearRelation;
+use crate::error::{CryptoError, SigmaError};
+use crate::group::Group;
+use crate::transcript::Transcript;
+
+const PROTOCOL: &[u8] = b"sigma/v1";
+
+/// Non-interactive proof for a `LinearRelation`: the challenge and one
+/// response per secret scalar.
+pub struct SigmaProof<S> {
+    pub c: S,
+    pub s: Vec<S>,
+}
+
+impl<S> SigmaProof<S> {
+    /// `c` followed by each `s`, each encoded with `group.encode_scalar`.
+    pub fn encode<G: Group<Scalar = S>>(&self, group: &G) -> Result<Vec<u8>, CryptoError> {
+        let mut bytes = Vec::with_capacity((self.s.len() + 1) * group.scalar_len());
+        for value in [&self.c].into_iter().chain(&self.s) {
+            bytes.extend(group.encode_scalar(value)?);
+        }
+
+        Ok(bytes)
+    }
+
+    /// Inverse of `encode` for a relation with `scalars` secret scalars.
+    /// Values outside `[0, q)` are rejected, so proofs are not malleable.
+    pub fn decode<G: Group<Scalar = S>>(
+        group: &G,
+        scalars: usize,
+        data: &[u8],
+    ) -> Result<SigmaProof<S>, SigmaError> {
+        let len = group.scalar_len();
+        if data.len() != (scalars + 1) * len {
+            return Err(SigmaError::MalformedProof);
+        }
+
+        let mut values = data
+            .chunks(len)
+            .map(|chunk| {
+                group
+                    .decode_scalar(chunk)
+                    .map_err(|_| SigmaError::MalformedProof)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let c = values.remove(0);
+
+        Ok(SigmaProof { c, s: values })
+    }
+}
+
+/// Derives the challenge in `[0, q)` binding the relation and the commitments
+/// to `transcript`.
+pub(crate) fn challenge<G: Group>(
+    relation: &LinearRelation<G>,
+    commitments: &[G::Element],
+    transcript: &Transcript,
+) -> Result<G::Scalar, CryptoError> {
+    let mut transcript = transcript.clone();
+    transcript.append_message(b"protocol", PROTOCOL);
+    for part in relation.description()? {
+        transcript.append_message(b"relation", &part);
+    }
+    for commitment in commitments {
+        transcript.append_element(&relation.group, b"commitment", commitment)?;
+    }
+
+    transcript.challenge_scalar(b"c", &relation.group)
+}
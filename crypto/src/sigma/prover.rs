@@ -0,0 +1,116 @@
+use std::sync::Arc;
+
+use super::proof::{challenge, SigmaProof};
+use super::relation::LinearRelation;
+use crate::error::SigmaError;
+use crate::group::Group;
+use crate::random::{default_source, RandomSource};
+use crate::secret::SecretScalar;
+use crate::transcript::Transcript;
+
+/// The prover's side of the proof of knowledge for a `LinearRelation`. The
+/// witness is one secret per `LinearRelation::scalar`, in declaration order.
+pub struct SigmaProver<G: Group> {
+    pub relation: LinearRelation<G>,
+    /// Where the nonce hedges come from.
+    pub rng: Arc<dyn RandomSource>,
+}
+
+impl<G: Group> SigmaProver<G> {
+    pub fn new(relation: LinearRelation<G>) -> Self {
+        SigmaProver::with_rng(relation, default_source())
+    }
+
+    /// A prover drawing its randomness from `rng` instead of the default source.
+    pub fn with_rng(relation: LinearRelation<G>, rng: Arc<dyn RandomSource>) -> Self {
+        SigmaProver { relation, rng }
+    }
+
+    /// The commitment `prod base_i^k_i` for every equation, along with the
+    /// nonces `k`. Each nonce is hedged from its witness, fresh randomness,
+    /// the `transcript` digest and its position. Fails if the witness does not satisfy the
+    /// relation, rather than producing a proof that will not verify.
+    #[allow(clippy::type_complexity)]
+    pub fn commit(
+        &self,
+        witness: &[SecretScalar<G::Scalar>],
+        transcript: &Transcript,
+    ) -> Result<(Vec<SecretScalar<G::Scalar>>, Vec<G::Element>), SigmaError> {
+        self.check_witness(witness)?;
+
+        let group = &self.relation.group;
+        let digest = transcript.digest();
+        let nonces = witness
+            .iter()
+            .enumerate()
+            .map(|(index, w)| {
+                let context = [&digest[..], &(index as u32).to_be_bytes()].concat();
+                SecretScalar::hedged(&*self.rng, group, w, &context)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let commitments = self
+            .relation
+            .equations()
+            .iter()
+            .map(|equation| self.relation.evaluate_secret(equation, &nonces))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok((nonces, commitments))
+    }
+
+    /// The responses `s_i = k_i - c * w_i mod q`.
+    pub fn respond(
+        &self,
+        nonces: &[SecretScalar<G::Scalar>],
+        witness: &[SecretScalar<G::Scalar>],
+        c: &G::Scalar,
+    ) -> Result<Vec<G::Scalar>, SigmaError> {
+        if nonces.len() != witness.len() || witness.len() != self.relation.scalars().len() {
+            return Err(SigmaError::WitnessLength);
+        }
+
+        let group = &self.relation.group;
+        let s = nonces
+            .iter()
+            .zip(witness)
+            .map(|(k, w)| {
+                let cw = SecretScalar::new(group.scalar_mul(c, w.expose())?);
+                group.scalar_sub(k.expose(), cw.expose())
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(s)
+    }
+
+    /// Non-interactive (Fiat-Shamir) proof, bound to the relation and
+    /// everything appended to `transcript`.
+    pub fn prove(
+        &self,
+        witness: &[SecretScalar<G::Scalar>],
+        transcript: &Transcript,
+    ) -> Result<SigmaProof<G::Scalar>, SigmaError> {
+        let (nonces, commitments) = self.commit(witness, transcript)?;
+
+        let c = challenge(&self.relation, &commitments, transcript)?;
+        let s = self.respond(&nonces, witness, &c)?;
+
+        Ok(SigmaProof { c, s })
+    }
+
+    fn check_witness(&self, witness: &[SecretScalar<G::Scalar>]) -> Result<(), SigmaError> {
+        if witness.len() != self.relation.scalars().len() {
+            return Err(SigmaError::WitnessLength);
+        }
+
+        for (index, equation) in self.relation.equations().iter().enumerate() {
+            let image = self.relation.evaluate_secret(equation, witness)?;
+            let expected = self.relation.resolve(equation.image);
+            if !self.relation.group.element_eq(&image, expected)? {
+                return Err(SigmaError::Unsatisfied(index));
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,185 @@
+use crate::error::{CryptoError, ParamsError};
+use crate::group::Group;
+use crate::secret::SecretScalar;
+
+/// A secret scalar of a relation, such as `x`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ScalarVar(pub(crate) usize);
+
+/// A public element of a relation: one of the group's generators, or an
+/// element added with `LinearRelation::element`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ElementVar {
+    G,
+    H,
+    Public(usize),
+}
+
+impl ElementVar {
+    fn tag(self) -> u32 {
+        match self {
+            ElementVar::G => 0,
+            ElementVar::H => 1,
+            ElementVar::Public(index) => 2 + index as u32,
+        }
+    }
+}
+
+/// `image = prod base_i^scalar_i`.
+pub struct Equation {
+    pub image: ElementVar,
+    pub terms: Vec<(ScalarVar, ElementVar)>,
+}
+
+/// A conjunction of equations, each saying a public element is a product of
+/// powers of other public elements with secret exponents, e.g.
+///
+/// ```ignore
+/// let mut relation = LinearRelation::new(params);
+/// let (x, a, b) = (relation.scalar("x"), relation.scalar("a"), relation.scalar("b"));
+/// let (g, h) = (relation.g(), relation.h());
+/// let (y1, y2, y3) = (relation.element("y1", y1)?, relation.element("y2", y2)?, relation.element("y3", y3)?);
+///
+/// relation.constrain(y1, &[(x, g)]);
+/// relation.constrain(y2, &[(x, h)]);
+/// relation.constrain(y3, &[(a, g), (b, h)]);
+/// ```
+///
+/// `SigmaProver` and `SigmaVerifier` turn it into the matching proof of
+/// knowledge. Both sides must build the relation the same way, since its shape
+/// is bound into the Fiat-Shamir challenge.
+pub struct LinearRelation<G: Group> {
+    pub group: G,
+    scalars: Vec<&'static str>,
+    elements: Vec<(&'static str, G::Element)>,
+    equations: Vec<Equation>,
+}
+
+impl<G: Group> LinearRelation<G> {
+    pub fn new(group: G) -> Self {
+        LinearRelation {
+            group,
+            scalars: Vec::new(),
+            elements: Vec::new(),
+            equations: Vec::new(),
+        }
+    }
+
+    /// Declares a secret scalar; the witness lists values in declaration order.
+    pub fn scalar(&mut self, name: &'static str) -> ScalarVar {
+        self.scalars.push(name);
+        ScalarVar(self.scalars.len() - 1)
+    }
+
+    pub fn g(&self) -> ElementVar {
+        ElementVar::G
+    }
+
+    pub fn h(&self) -> ElementVar {
+        ElementVar::H
+    }
+
+    /// Adds a public element, after checking it is a non-identity element of
+    /// the prime-order subgroup.
+    pub fn element(
+        &mut self,
+        name: &'static str,
+        element: G::Element,
+    ) -> Result<ElementVar, ParamsError> {
+        self.group.check_element(name, &element)?;
+        self.elements.push((name, element));
+
+        Ok(ElementVar::Public(self.elements.len() - 1))
+    }
+
+    /// Adds the equation `image = prod base_i^scalar_i`.
+    pub fn constrain(&mut self, image: ElementVar, terms: &[(ScalarVar, ElementVar)]) {
+        self.equations.push(Equation {
+            image,
+            terms: terms.to_vec(),
+        });
+    }
+
+    pub fn scalars(&self) -> &[&'static str] {
+        &self.scalars
+    }
+
+    pub fn equations(&self) -> &[Equation] {
+        &self.equations
+    }
+
+    pub(crate) fn resolve(&self, var: ElementVar) -> &G::Element {
+        match var {
+            ElementVar::G => self.group.g(),
+            ElementVar::H => self.group.h(),
+            ElementVar::Public(index) => &self.elements[index].1,
+        }
+    }
+
+    /// `prod base_i^s_i * image^c` for the terms of `equation`, variable-time;
+    /// only for public values.
+    pub(crate) fn evaluate(
+        &self,
+        equation: &Equation,
+        s: &[&G::Scalar],
+        c: &G::Scalar,
+    ) -> Result<G::Element, CryptoError> {
+        let mut bases: Vec<&G::Element> = Vec::with_capacity(equation.terms.len() + 1);
+        let mut exponents: Vec<&G::Scalar> = Vec::with_capacity(equation.terms.len() + 1);
+        for (scalar, base) in &equation.terms {
+            bases.push(self.resolve(*base));
+            exponents.push(s[scalar.0]);
+        }
+        bases.push(self.resolve(equation.image));
+        exponents.push(c);
+
+        self.group.multi_exp(&bases, &exponents)
+    }
+
+    /// `prod base_i^values[scalar_i]` for the terms of `equation`, with each
+    /// power taken in constant time and only the public results multiplied.
+    pub(crate) fn evaluate_secret(
+        &self,
+        equation: &Equation,
+        values: &[SecretScalar<G::Scalar>],
+    ) -> Result<G::Element, CryptoError> {
+        let mut powers = Vec::with_capacity(equation.terms.len());
+        for (scalar, base) in &equation.terms {
+            let value = values[scalar.0].expose();
+            powers.push(match base {
+                ElementVar::G => self.group.exp_g(value)?,
+                ElementVar::H => self.group.exp_h(value)?,
+                ElementVar::Public(_) => self.group.exp(self.resolve(*base), value)?,
+            });
+        }
+
+        let one = self.group.reduce_scalar(&[1])?;
+        let bases: Vec<&G::Element> = powers.iter().collect();
+        let ones = vec![&one; bases.len()];
+        self.group.multi_exp(&bases, &ones)
+    }
+
+    /// The group, the shape of the equations and the public elements, bound
+    /// into every Fiat-Shamir challenge.
+    pub(crate) fn description(&self) -> Result<Vec<Vec<u8>>, CryptoError> {
+        let mut shape = Vec::new();
+        shape.extend((self.scalars.len() as u32).to_be_bytes());
+        shape.extend((self.equations.len() as u32).to_be_bytes());
+        for equation in &self.equations {
+            shape.extend(equation.image.tag().to_be_bytes());
+            shape.extend((equation.terms.len() as u32).to_be_bytes());
+            for (scalar, base) in &equation.terms {
+                shape.extend((scalar.0 as u32).to_be_bytes());
+                shape.extend(base.tag().to_be_bytes());
+            }
+        }
+
+        let mut parts = self.group.description()?;
+        parts.push(shape);
+        for (_, element) in &self.elements {
+            parts.push(self.group.encode_element(element)?);
+        }
+
+        Ok(parts)
+    }
+}
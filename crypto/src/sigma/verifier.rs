@@ -0,0 +1,88 @@
+use std::sync::Arc;
+
+use super::proof::{challenge, SigmaProof};
+use super::relation::LinearRelation;
+use crate::error::{CryptoError, SigmaError};
+use crate::group::Group;
+use crate::random::{default_source, RandomSource};
+use crate::transcript::Transcript;
+use crate::util::random_scalar;
+
+/// The verifier's side of the proof of knowledge for a `LinearRelation`.
+pub struct SigmaVerifier<G: Group> {
+    pub relation: LinearRelation<G>,
+    /// Where interactive challenges come from.
+    pub rng: Arc<dyn RandomSource>,
+}
+
+impl<G: Group> SigmaVerifier<G> {
+    pub fn new(relation: LinearRelation<G>) -> Self {
+        SigmaVerifier::with_rng(relation, default_source())
+    }
+
+    /// A verifier drawing its randomness from `rng` instead of the default source.
+    pub fn with_rng(relation: LinearRelation<G>, rng: Arc<dyn RandomSource>) -> Self {
+        SigmaVerifier { relation, rng }
+    }
+
+    /// Random challenge in `[0, q)` for the interactive protocol.
+    pub fn random(&self) -> Result<G::Scalar, CryptoError> {
+        random_scalar(&*self.rng, &self.relation.group)
+    }
+
+    /// Checks an interactive run: every commitment must equal
+    /// `prod base_i^s_i * image^c` for its equation.
+    pub fn check(
+        &self,
+        commitments: &[G::Element],
+        c: &G::Scalar,
+        s: &[G::Scalar],
+    ) -> Result<bool, SigmaError> {
+        let expected = self.expected_commitments(c, s)?;
+
+        for (commitment, expected) in commitments.iter().zip(&expected) {
+            if !self.relation.group.element_eq(commitment, expected)? {
+                return Ok(false);
+            }
+        }
+
+        Ok(commitments.len() == expected.len())
+    }
+
+    /// Checks a non-interactive proof produced for the same `transcript`.
+    pub fn verify(
+        &self,
+        proof: &SigmaProof<G::Scalar>,
+        transcript: &Transcript,
+    ) -> Result<bool, SigmaError> {
+        let commitments = self.expected_commitments(&proof.c, &proof.s)?;
+        let c = challenge(&self.relation, &commitments, transcript)?;
+
+        Ok(c == proof.c)
+    }
+
+    /// Decodes a proof made for this relation with `SigmaProof::encode`.
+    pub fn decode_proof(&self, data: &[u8]) -> Result<SigmaProof<G::Scalar>, SigmaError> {
+        SigmaProof::decode(&self.relation.group, self.relation.scalars().len(), data)
+    }
+
+    fn expected_commitments(
+        &self,
+        c: &G::Scalar,
+        s: &[G::Scalar],
+    ) -> Result<Vec<G::Element>, SigmaError> {
+        if s.len() != self.relation.scalars().len() {
+            return Err(SigmaError::MalformedProof);
+        }
+
+        let s: Vec<&G::Scalar> = s.iter().collect();
+        let commitments = self
+            .relation
+            .equations()
+            .iter()
+            .map(|equation| self.relation.evaluate(equation, &s, c))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(commitments)
+    }
+}
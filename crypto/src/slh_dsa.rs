@@ -0,0 +1,505 @@
+//! SLH-DSA (FIPS 205), the stateless hash-based signature scheme, with the
+//! SLH-DSA-SHA2-128f parameter set, and identification by signing a
+//! verifier's challenge with it. Its security rests only on SHA-256, so
+//! unlike every discrete-log protocol here it holds against quantum
+//! adversaries. The price is size: public keys are 32 bytes, but every
+//! signature is 17 KB.
+//!
+//! Messages are signed in the pure (non-prehashed) mode under a fixed
+//! context string, so a signature made here is never valid for another
+//! application sharing the key. Signing is hedged with fresh randomness, as
+//! the standard recommends.
+//!
+//! The scheme itself is OpenSSL's (3.5 and later), reached through the EVP
+//! interface; this module only fixes the parameter set and the context, and
+//! derives key pairs from seeds.
+
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int, c_void};
+use std::ptr;
+use std::sync::Arc;
+
+use foreign_types::{ForeignType, ForeignTypeRef};
+use openssl::error::ErrorStack;
+use openssl::md_ctx::MdCtx;
+use openssl::pkey::{PKey, PKeyRef, Private, Public};
+use openssl::pkey_ctx::{PkeyCtx, PkeyCtxRef};
+use openssl_sys::OSSL_PARAM;
+use zeroize::Zeroizing;
+
+use crate::error::{CryptoError, PasswordError};
+use crate::identification::{Challenge, IdentificationProver, IdentificationVerifier};
+use crate::password::PasswordKdf;
+use crate::random::{default_source, RandomSource};
+use crate::transcript::Transcript;
+use crate::util::challenge_nonce;
+
+extern "C" {
+    fn OSSL_PARAM_construct_int(key: *const c_char, buf: *mut c_int) -> OSSL_PARAM;
+}
+
+// OpenSSL's name for SLH-DSA-SHA2-128f and the security parameter n of it
+// (FIPS 205 table 2)
+const ALGORITHM: &CStr = c"SLH-DSA-SHA2-128f";
+const N: usize = 16;
+
+const CONTEXT: &[u8] = b"zkp_auth/slh-dsa/v1";
+const PROTOCOL: &[u8] = b"slh-dsa-identification/v1";
+const PASSWORD_DOMAIN: &[u8] = b"slh-dsa-sha2-128f";
+
+/// Size of an encoded public key: `PK.seed || PK.root`.
+pub const PUBLIC_KEY_LEN: usize = 2 * N;
+
+/// Size of the seed a key pair is generated from: `SK.seed || SK.prf || PK.seed`.
+pub const SEED_LEN: usize = 3 * N;
+
+/// Size of a signature: the randomizer, a FORS and a hypertree signature.
+pub const SIGNATURE_LEN: usize = 17_088;
+
+fn cvt(result: c_int) -> Result<(), ErrorStack> {
+    if result <= 0 {
+        Err(ErrorStack::get())
+    } else {
+        Ok(())
+    }
+}
+
+// Applies `params`, an `OSSL_PARAM_construct_end`-terminated array, to `ctx`
+fn set_params(ctx: &PkeyCtxRef<impl Sized>, params: &[OSSL_PARAM]) -> Result<(), ErrorStack> {
+    unsafe {
+        cvt(openssl_sys::EVP_PKEY_CTX_set_params(
+            ctx.as_ptr(),
+            params.as_ptr(),
+        ))
+    }
+}
+
+/// The public key: the seed every hash is keyed with and the hypertree's root.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifyingKey {
+    bytes: [u8; PUBLIC_KEY_LEN],
+}
+
+impl VerifyingKey {
+    pub fn to_bytes(&self) -> [u8; PUBLIC_KEY_LEN] {
+        self.bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CryptoError> {
+        let bytes = bytes.try_into().map_err(|_| CryptoError::InvalidEncoding)?;
+
+        Ok(VerifyingKey { bytes })
+    }
+
+    // The key as OpenSSL holds it
+    fn pkey(&self) -> Result<PKey<Public>, ErrorStack> {
+        unsafe {
+            openssl_sys::init();
+            let key = openssl_sys::EVP_PKEY_new_raw_public_key_ex(
+                ptr::null_mut(),
+                ALGORITHM.as_ptr(),
+                ptr::null(),
+                self.bytes.as_ptr(),
+                self.bytes.len(),
+            );
+            if key.is_null() {
+                return Err(ErrorStack::get());
+            }
+
+            Ok(PKey::from_ptr(key))
+        }
+    }
+
+    /// `slh_verify`: checks `signature` on `message`. Signatures of the wrong
+    /// length are rejected rather than an error.
+    pub fn verify(&self, message: &[u8], signature: &[u8]) -> bool {
+        if signature.len() != SIGNATURE_LEN {
+            return false;
+        }
+        let verify = || -> Result<bool, ErrorStack> {
+            let key = self.pkey()?;
+            let mut ctx = MdCtx::new()?;
+            ctx.digest_verify_init(None, &key)?
+                .set_context_string(CONTEXT)?;
+            ctx.digest_verify(message, signature)
+        };
+
+        verify().unwrap_or(false)
+    }
+}
+
+/// The private key, with the public key it belongs to.
+pub struct SigningKey {
+    key: PKey<Private>,
+    verifying_key: VerifyingKey,
+}
+
+impl SigningKey {
+    /// A fresh key pair from `rng`.
+    pub fn generate(rng: &dyn RandomSource) -> Result<Self, CryptoError> {
+        let mut seed = Zeroizing::new([0u8; SEED_LEN]);
+        rng.fill_bytes(&mut *seed)?;
+
+        SigningKey::from_seed(&seed)
+    }
+
+    /// `slh_keygen_internal`: the key pair `seed` determines, so a key
+    /// derived from a password can be recomputed anywhere.
+    pub fn from_seed(seed: &[u8; SEED_LEN]) -> Result<Self, CryptoError> {
+        let key = unsafe {
+            openssl_sys::init();
+            let ctx = openssl_sys::EVP_PKEY_CTX_new_from_name(
+                ptr::null_mut(),
+                ALGORITHM.as_ptr(),
+                ptr::null(),
+            );
+            if ctx.is_null() {
+                return Err(ErrorStack::get().into());
+            }
+            let mut ctx = PkeyCtx::<()>::from_ptr(ctx);
+            ctx.keygen_init()?;
+            // OpenSSL only reads the seed
+            set_params(
+                &ctx,
+                &[
+                    openssl_sys::OSSL_PARAM_construct_octet_string(
+                        c"seed".as_ptr(),
+                        seed.as_ptr() as *mut c_void,
+                        seed.len(),
+                    ),
+                    openssl_sys::OSSL_PARAM_construct_end(),
+                ],
+            )?;
+            ctx.keygen()?
+        };
+        let verifying_key = VerifyingKey::from_bytes(&key.raw_public_key()?)?;
+
+        Ok(SigningKey { key, verifying_key })
+    }
+
+    /// The key pair derived from a password, as `PasswordKdf::derive_secret`
+    /// derives x: the same user, password and salt always give the same key.
+    pub fn from_password(
+        kdf: &PasswordKdf,
+        user: &str,
+        password: &[u8],
+        salt: &[u8],
+    ) -> Result<Self, PasswordError> {
+        let mut seed = Zeroizing::new([0u8; SEED_LEN]);
+        kdf.derive_seed(PASSWORD_DOMAIN, user, password, salt, &mut *seed)?;
+
+        Ok(SigningKey::from_seed(&seed)?)
+    }
+
+    pub fn verifying_key(&self) -> &VerifyingKey {
+        &self.verifying_key
+    }
+
+    /// `slh_sign`: signs `message`, hedged with randomness from OpenSSL's
+    /// generator.
+    pub fn sign(&self, message: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        Ok(sign_with(&self.key, message, false)?)
+    }
+}
+
+// `slh_sign`, or with `deterministic` its deterministic variant, which
+// takes `PK.seed` in place of fresh randomness
+fn sign_with(
+    key: &PKeyRef<Private>,
+    message: &[u8],
+    deterministic: bool,
+) -> Result<Vec<u8>, ErrorStack> {
+    let mut ctx = MdCtx::new()?;
+    let pkey_ctx = ctx.digest_sign_init(None, key)?;
+    pkey_ctx.set_context_string(CONTEXT)?;
+    let mut deterministic = c_int::from(deterministic);
+    unsafe {
+        set_params(
+            pkey_ctx,
+            &[
+                OSSL_PARAM_construct_int(c"deterministic".as_ptr(), &mut deterministic),
+                openssl_sys::OSSL_PARAM_construct_end(),
+            ],
+        )?;
+    }
+
+    let mut signature = Vec::with_capacity(SIGNATURE_LEN);
+    ctx.digest_sign_to_vec(message, &mut signature)?;
+
+    Ok(signature)
+}
+
+/// What the prover signs to answer a challenge: the verifier's fresh
+/// `nonce` appended to the session `transcript`.
+pub fn login_message(nonce: &[u8], transcript: &Transcript) -> [u8; 64] {
+    let mut transcript = transcript.clone();
+    transcript.append_message(b"protocol", PROTOCOL);
+    transcript.append_message(b"verifier-nonce", nonce);
+
+    transcript.digest()
+}
+
+/// The prover's side of SLH-DSA identification.
+pub struct SlhDsaProver {
+    /// Where keys come from; OpenSSL draws the signing hedges itself.
+    pub rng: Arc<dyn RandomSource>,
+}
+
+impl Default for SlhDsaProver {
+    fn default() -> Self {
+        SlhDsaProver::with_rng(default_source())
+    }
+}
+
+impl SlhDsaProver {
+    pub fn new() -> Self {
+        SlhDsaProver::default()
+    }
+
+    /// A prover drawing its randomness from `rng` instead of the default source.
+    pub fn with_rng(rng: Arc<dyn RandomSource>) -> Self {
+        SlhDsaProver { rng }
+    }
+
+    pub fn generate_key(&self) -> Result<SigningKey, CryptoError> {
+        SigningKey::generate(&*self.rng)
+    }
+
+    /// Answers the challenge `nonce` in the session `transcript`.
+    pub fn challenge_response(
+        &self,
+        key: &SigningKey,
+        nonce: &[u8],
+        transcript: &Transcript,
+    ) -> Result<Vec<u8>, CryptoError> {
+        key.sign(&login_message(nonce, transcript))
+    }
+}
+
+/// The verifier's side of SLH-DSA identification.
+pub struct SlhDsaVerifier {
+    /// Where challenge nonces come from.
+    pub rng: Arc<dyn RandomSource>,
+}
+
+impl Default for SlhDsaVerifier {
+    fn default() -> Self {
+        SlhDsaVerifier::with_rng(default_source())
+    }
+}
+
+impl SlhDsaVerifier {
+    pub fn new() -> Self {
+        SlhDsaVerifier::default()
+    }
+
+    /// A verifier drawing its randomness from `rng` instead of the default source.
+    pub fn with_rng(rng: Arc<dyn RandomSource>) -> Self {
+        SlhDsaVerifier { rng }
+    }
+
+    /// Fresh randomness for a challenge; the prover signs it.
+    pub fn challenge_nonce(&self) -> Result<Vec<u8>, CryptoError> {
+        challenge_nonce(&*self.rng)
+    }
+
+    /// Checks the prover signed `nonce` in the session `transcript` under `key`.
+    pub fn check(
+        &self,
+        key: &VerifyingKey,
+        nonce: &[u8],
+        transcript: &Transcript,
+        signature: &[u8],
+    ) -> bool {
+        key.verify(&login_message(nonce, transcript), signature)
+    }
+}
+
+// Nothing to commit to: the signature on the verifier's nonce is the whole answer
+impl IdentificationProver for SlhDsaProver {
+    type Secret = SigningKey;
+    type PublicKey = VerifyingKey;
+    type Nonce = ();
+    type Commitment = ();
+    type Response = Vec<u8>;
+    type Scalar = ();
+
+    fn generate_secret(&self) -> Result<SigningKey, CryptoError> {
+        self.generate_key()
+    }
+
+    fn secret_from_password(
+        &self,
+        kdf: &PasswordKdf,
+        user: &str,
+        password: &[u8],
+        salt: &[u8],
+    ) -> Result<SigningKey, PasswordError> {
+        SigningKey::from_password(kdf, user, password, salt)
+    }
+
+    fn public_key(&self, key: &SigningKey) -> Result<VerifyingKey, CryptoError> {
+        Ok(key.verifying_key().clone())
+    }
+
+    fn commitment(&self, _key: &SigningKey, _context: &[u8]) -> Result<((), ()), CryptoError> {
+        Ok(((), ()))
+    }
+
+    // Any nonce is fine to sign, since the message binds the transcript; a
+    // `c` means the verifier runs another protocol
+    fn accepts_challenge(
+        &self,
+        _key: &VerifyingKey,
+        _commitment: &(),
+        challenge: &Challenge<()>,
+        _transcript: &Transcript,
+    ) -> Result<bool, CryptoError> {
+        Ok(challenge.c.is_none())
+    }
+
+    fn respond(
+        &self,
+        key: &SigningKey,
+        _k: &(),
+        challenge: &Challenge<()>,
+        transcript: &Transcript,
+    ) -> Result<Vec<u8>, CryptoError> {
+        self.challenge_response(key, &challenge.nonce, transcript)
+    }
+}
+
+impl IdentificationVerifier for SlhDsaVerifier {
+    type PublicKey = VerifyingKey;
+    type Commitment = ();
+    type Response = Vec<u8>;
+    type Scalar = ();
+
+    fn challenge(
+        &self,
+        _key: &VerifyingKey,
+        _commitment: &(),
+        _transcript: &Transcript,
+    ) -> Result<Challenge<()>, CryptoError> {
+        Ok(Challenge {
+            c: None,
+            nonce: self.challenge_nonce()?,
+        })
+    }
+
+    // There is no `c` to forge: a fresh nonce is all a real challenge is
+    fn decoy_challenge(&self, _transcript: &Transcript) -> Result<Challenge<()>, CryptoError> {
+        Ok(Challenge {
+            c: None,
+            nonce: self.challenge_nonce()?,
+        })
+    }
+
+    fn check(
+        &self,
+        key: &VerifyingKey,
+        _commitment: &(),
+        challenge: &Challenge<()>,
+        signature: &Vec<u8>,
+        transcript: &Transcript,
+    ) -> Result<bool, CryptoError> {
+        Ok(SlhDsaVerifier::check(
+            self,
+            key,
+            &challenge.nonce,
+            transcript,
+            signature,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use openssl::sha::sha256;
+
+    use super::*;
+    use crate::random::SeededRandom;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    #[test]
+    fn test_known_answer() -> Result<(), CryptoError> {
+        // SLH-DSA-SHA2-128f keyed with the seed 00 01 .. 2f, signing
+        // deterministically under our context string
+        let seed: [u8; SEED_LEN] = std::array::from_fn(|i| i as u8);
+        let key = SigningKey::from_seed(&seed)?;
+        assert_eq!(
+            hex(&key.verifying_key().to_bytes()),
+            "202122232425262728292a2b2c2d2e2f3b56e816847f000386aeec2e2bb9e1b5"
+        );
+
+        let signature = sign_with(&key.key, b"zkp_auth", true)?;
+        assert_eq!(signature.len(), SIGNATURE_LEN);
+        assert_eq!(
+            hex(&sha256(&signature)),
+            "ff734e0644aff3d86def4a90c064ebc56c09af6e01a24fca50c37f857d4511a3"
+        );
+        assert!(key.verifying_key().verify(b"zkp_auth", &signature));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_slh_dsa() -> Result<(), CryptoError> {
+        let rng = SeededRandom::new(b"slh-dsa")?;
+        let key = SigningKey::generate(&rng)?;
+        let verifying_key = key.verifying_key();
+        let encoded = verifying_key.to_bytes();
+        assert_eq!(&VerifyingKey::from_bytes(&encoded)?, verifying_key);
+        assert!(VerifyingKey::from_bytes(&encoded[1..]).is_err());
+
+        // Hedged: signing twice gives different, equally valid signatures
+        let signature = key.sign(b"payout 42 to alice")?;
+        let again = key.sign(b"payout 42 to alice")?;
+        assert_ne!(signature, again);
+        assert!(verifying_key.verify(b"payout 42 to alice", &signature));
+        assert!(verifying_key.verify(b"payout 42 to alice", &again));
+
+        // Wrong message, wrong key, wrong length
+        assert!(!verifying_key.verify(b"payout 43 to alice", &signature));
+        let other = SigningKey::generate(&rng)?;
+        assert!(!other
+            .verifying_key()
+            .verify(b"payout 42 to alice", &signature));
+        assert!(!verifying_key.verify(b"payout 42 to alice", &signature[1..]));
+
+        // A flipped bit in the randomizer, the FORS or the hypertree signature
+        for position in [0, N + 1, SIGNATURE_LEN - 1] {
+            let mut tampered = signature.clone();
+            tampered[position] ^= 1;
+            assert!(!verifying_key.verify(b"payout 42 to alice", &tampered));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_slh_dsa_identification() -> Result<(), CryptoError> {
+        let prover = SlhDsaProver::new();
+        let verifier = SlhDsaVerifier::new();
+        let session = Transcript::new(b"test");
+
+        let key = prover.generate_key()?;
+        let nonce = verifier.challenge_nonce()?;
+        let signature = prover.challenge_response(&key, &nonce, &session)?;
+        assert!(verifier.check(key.verifying_key(), &nonce, &session, &signature));
+
+        // Bound to the nonce and the session
+        let other_nonce = verifier.challenge_nonce()?;
+        assert!(!verifier.check(key.verifying_key(), &other_nonce, &session, &signature));
+        let other_session = Transcript::new(b"other");
+        assert!(!verifier.check(key.verifying_key(), &nonce, &other_session, &signature));
+
+        // An answer is not a signature on the nonce itself
+        assert!(!key.verifying_key().verify(&nonce, &signature));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,218 @@
+//! Merlin-style proof transcripts. Every message a proof depends on is
+//! absorbed under a label, and challenges are derived from everything absorbed
+//! so far. A proof made against one transcript does not verify against one
+//! that differs in any message, label or order, so binding a proof to a user,
+//! a session or a set of parameters is a matter of appending them.
+
+use sha2::{Digest, Sha512};
+
+use crate::error::CryptoError;
+use crate::group::Group;
+use crate::secret::WIDE_MARGIN_BYTES;
+use crate::util::expand_hash;
+
+const DOMAIN: &[u8] = b"zkp_auth/transcript/v1";
+const CHALLENGE_DOMAIN: &[u8] = b"zkp_auth/transcript/challenge/v1";
+const PARAMS_DOMAIN: &[u8] = b"zkp_auth/params-fingerprint/v1";
+
+// Operation tags, so an appended message can never collide with a challenge
+const MESSAGE: u8 = 0;
+const CHALLENGE: u8 = 1;
+
+/// Running hash of labeled messages. Cloning forks the transcript, which is
+/// how proofs extend a caller's session transcript without consuming it.
+#[derive(Clone)]
+pub struct Transcript {
+    hasher: Sha512,
+}
+
+impl Transcript {
+    /// Starts a transcript for the application protocol `label`.
+    pub fn new(label: &'static [u8]) -> Transcript {
+        let mut hasher = Sha512::new();
+        absorb(&mut hasher, DOMAIN);
+        absorb(&mut hasher, label);
+
+        Transcript { hasher }
+    }
+
+    pub fn append_message(&mut self, label: &'static [u8], message: &[u8]) {
+        self.hasher.update([MESSAGE]);
+        absorb(&mut self.hasher, label);
+        absorb(&mut self.hasher, message);
+    }
+
+    /// Appends the fingerprint of `group`'s parameters under `params`.
+    pub fn append_params<G: Group>(&mut self, group: &G) -> Result<(), CryptoError> {
+        let fingerprint = params_fingerprint(group)?;
+        self.append_message(b"params", &fingerprint);
+
+        Ok(())
+    }
+
+    /// Appends the canonical encoding of `element`.
+    pub fn append_element<G: Group>(
+        &mut self,
+        group: &G,
+        label: &'static [u8],
+        element: &G::Element,
+    ) -> Result<(), CryptoError> {
+        let encoded = group.encode_element(element)?;
+        self.append_message(label, &encoded);
+
+        Ok(())
+    }
+
+    /// `len` challenge bytes. The transcript absorbs the challenge, so later
+    /// challenges depend on it.
+    pub fn challenge_bytes(&mut self, label: &'static [u8], len: usize) -> Vec<u8> {
+        let seed = self.squeeze(label);
+        expand_hash(CHALLENGE_DOMAIN, &[&seed], len)
+    }
+
+    /// Challenge scalar of `group`, in `[0, q)`.
+    pub fn challenge_scalar<G: Group>(
+        &mut self,
+        label: &'static [u8],
+        group: &G,
+    ) -> Result<G::Scalar, CryptoError> {
+        let seed = self.squeeze(label);
+        let wide = expand_hash(
+            CHALLENGE_DOMAIN,
+            &[&seed],
+            group.scalar_len() + WIDE_MARGIN_BYTES,
+        );
+
+        group.reduce_scalar(&wide)
+    }
+
+    /// Digest of everything absorbed so far, without changing the transcript.
+    /// Provers hedge their nonces with it.
+    pub fn digest(&self) -> [u8; 64] {
+        self.hasher.clone().finalize().into()
+    }
+
+    // Derives a seed from the current state and ratchets the state past it
+    fn squeeze(&mut self, label: &'static [u8]) -> [u8; 64] {
+        let mut hasher = self.hasher.clone();
+        hasher.update([CHALLENGE]);
+        absorb(&mut hasher, label);
+        let seed: [u8; 64] = hasher.finalize().into();
+
+        self.hasher.update([CHALLENGE]);
+        absorb(&mut self.hasher, label);
+        absorb(&mut self.hasher, &seed);
+
+        seed
+    }
+}
+
+/// SHA-512 of the group's `description`: two parties agree on it iff they use
+/// the same group and generators.
+pub fn params_fingerprint<G: Group>(group: &G) -> Result<[u8; 64], CryptoError> {
+    let mut hasher = Sha512::new();
+    absorb(&mut hasher, PARAMS_DOMAIN);
+    for part in group.description()? {
+        absorb(&mut hasher, &part);
+    }
+
+    Ok(hasher.finalize().into())
+}
+
+fn absorb(hasher: &mut Sha512, data: &[u8]) {
+    hasher.update((data.len() as u64).to_be_bytes());
+    hasher.update(data);
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "openssl")]
+    use openssl::nid::Nid;
+
+    use super::*;
+    #[cfg(feature = "openssl")]
+    use crate::dl::{groups::NamedGroup, params::DlParams};
+    #[cfg(feature = "openssl")]
+    use crate::ec::params::EcParams;
+    use crate::ristretto::params::RistrettoParams;
+
+    fn transcript(user: &[u8], auth_id: &[u8]) -> Transcript {
+        let mut transcript = Transcript::new(b"test");
+        transcript.append_message(b"user", user);
+        transcript.append_message(b"auth_id", auth_id);
+        transcript
+    }
+
+    #[test]
+    fn test_transcript_binding() -> Result<(), CryptoError> {
+        let group = RistrettoParams::from_seed(b"seed");
+        let c = transcript(b"alice", b"1").challenge_scalar(b"c", &group)?;
+
+        // Deterministic, and sensitive to every message, label and boundary
+        assert_eq!(
+            c,
+            transcript(b"alice", b"1").challenge_scalar(b"c", &group)?
+        );
+        assert_ne!(c, transcript(b"bob", b"1").challenge_scalar(b"c", &group)?);
+        assert_ne!(
+            c,
+            transcript(b"alice", b"2").challenge_scalar(b"c", &group)?
+        );
+        assert_ne!(
+            c,
+            transcript(b"alice1", b"").challenge_scalar(b"c", &group)?
+        );
+        assert_ne!(
+            c,
+            transcript(b"alice", b"1").challenge_scalar(b"d", &group)?
+        );
+        let mut relabeled = Transcript::new(b"test");
+        relabeled.append_message(b"auth_id", b"alice");
+        relabeled.append_message(b"user", b"1");
+        assert_ne!(c, relabeled.challenge_scalar(b"c", &group)?);
+        let mut other_protocol = Transcript::new(b"other");
+        other_protocol.append_message(b"user", b"alice");
+        other_protocol.append_message(b"auth_id", b"1");
+        assert_ne!(c, other_protocol.challenge_scalar(b"c", &group)?);
+
+        // Challenges ratchet the state, forks don't affect the original
+        let mut t = transcript(b"alice", b"1");
+        let digest = t.digest();
+        let mut fork = t.clone();
+        fork.append_message(b"extra", b"data");
+        assert_eq!(digest, t.digest());
+        let first = t.challenge_bytes(b"c", 32);
+        let second = t.challenge_bytes(b"c", 32);
+        assert_ne!(first, second);
+        assert_ne!(digest, t.digest());
+
+        Ok(())
+    }
+
+    #[cfg(feature = "openssl")]
+    #[test]
+    fn test_params_fingerprint() -> Result<(), CryptoError> {
+        let dl = DlParams::from_named_group(NamedGroup::Ffdhe2048, b"seed")?;
+
+        // Any difference in the group or in a generator changes the fingerprint
+        let fingerprints = [
+            params_fingerprint(&dl)?,
+            params_fingerprint(&DlParams::from_named_group(
+                NamedGroup::Ffdhe2048,
+                b"other",
+            )?)?,
+            params_fingerprint(&DlParams::from_named_group(NamedGroup::Modp2048, b"seed")?)?,
+            params_fingerprint(&EcParams::new(Nid::SECP256K1)?)?,
+            params_fingerprint(&EcParams::new(Nid::X9_62_PRIME256V1)?)?,
+            params_fingerprint(&RistrettoParams::from_seed(b"seed"))?,
+        ];
+        for (i, a) in fingerprints.iter().enumerate() {
+            for b in &fingerprints[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+        assert_eq!(fingerprints[0], params_fingerprint(&dl.clone())?);
+
+        Ok(())
+    }
+}
@@ -1,8 +1,21 @@
+use hmac::{Hmac, Mac};
+#[cfg(feature = "openssl")]
 use openssl::{
     bn::{BigNum, BigNumContext},
     error::ErrorStack,
 };
+use sha2::{Digest, Sha256, Sha512};
 
+#[cfg(feature = "openssl")]
+use crate::context::with_bn_ctx;
+use crate::error::CryptoError;
+#[cfg(feature = "openssl")]
+use crate::error::ParamsError;
+use crate::group::Group;
+use crate::random::RandomSource;
+use crate::secret::WIDE_MARGIN_BYTES;
+
+#[cfg(feature = "openssl")]
 pub fn mod_sub(
     a: &BigNum,
     b: &BigNum,
@@ -14,6 +27,7 @@ pub fn mod_sub(
     Ok(result)
 }
 
+#[cfg(feature = "openssl")]
 pub fn mod_mul(
     a: &BigNum,
     b: &BigNum,
@@ -25,9 +39,196 @@ pub fn mod_mul(
     Ok(result)
 }
 
-pub fn rng(p: &BigNum) -> Result<BigNum, ErrorStack> {
-    let mut random = BigNum::new().unwrap();
-    p.rand_range(&mut random)?;
+#[cfg(feature = "openssl")]
+pub fn mod_add(
+    a: &BigNum,
+    b: &BigNum,
+    m: &BigNum,
+    ctx: &mut BigNumContext,
+) -> Result<BigNum, ErrorStack> {
+    let mut result = BigNum::new()?;
+    result.mod_add(a, b, m, ctx)?;
+    Ok(result)
+}
+
+/// Big-endian `bytes` reduced mod `q`, at most twice the width of `q`. The
+/// wide value is flagged constant-time since it may be secret.
+#[cfg(feature = "openssl")]
+pub(crate) fn reduce_mod(bytes: &[u8], q: &BigNum) -> Result<BigNum, CryptoError> {
+    if bytes.len() > 2 * q.num_bytes() as usize {
+        return Err(CryptoError::InvalidEncoding);
+    }
+    let mut wide = BigNum::from_slice(bytes)?;
+    wide.set_const_time();
+
+    let mut reduced = BigNum::new()?;
+    let result = with_bn_ctx(|ctx| reduced.nnmod(&wide, q, ctx));
+    wide.clear();
+    result?;
+
+    Ok(reduced)
+}
+
+/// `value` as exactly `q.num_bytes()` big-endian bytes.
+#[cfg(feature = "openssl")]
+pub(crate) fn encode_mod(value: &BigNum, q: &BigNum) -> Result<Vec<u8>, CryptoError> {
+    Ok(value.to_vec_padded(q.num_bytes())?)
+}
+
+/// Inverse of `encode_mod`, rejecting any other length and values not below `q`.
+#[cfg(feature = "openssl")]
+pub(crate) fn decode_mod(data: &[u8], q: &BigNum) -> Result<BigNum, CryptoError> {
+    if data.len() != q.num_bytes() as usize {
+        return Err(CryptoError::InvalidEncoding);
+    }
+    let value = BigNum::from_slice(data)?;
+    if &value >= q {
+        return Err(CryptoError::InvalidEncoding);
+    }
+
+    Ok(value)
+}
+
+/// `value^-1 mod q`, or `None` for zero.
+#[cfg(feature = "openssl")]
+pub(crate) fn invert_mod(value: &BigNum, q: &BigNum) -> Result<Option<BigNum>, CryptoError> {
+    if value.num_bits() == 0 {
+        return Ok(None);
+    }
+    let mut inverse = BigNum::new()?;
+    with_bn_ctx(|ctx| inverse.mod_inverse(value, q, ctx))?;
+
+    Ok(Some(inverse))
+}
+
+// Size of the random weights in batch verification; a bad batch passes with probability 2^-128
+const BATCH_WEIGHT_BYTES: usize = 16;
+
+// Extra bytes drawn beyond the size of the modulus so the reduction bias is negligible
+#[cfg(feature = "openssl")]
+const RANGE_MARGIN_BYTES: usize = 16;
+
+// Verifier randomness in each interactive challenge
+const CHALLENGE_NONCE_BYTES: usize = 32;
+
+/// Random weight for combining verification equations in a batch.
+pub fn batch_weight<G: Group>(
+    source: &dyn RandomSource,
+    group: &G,
+) -> Result<G::Scalar, CryptoError> {
+    let mut bytes = [0u8; BATCH_WEIGHT_BYTES];
+    source.fill_bytes(&mut bytes)?;
+
+    group.reduce_scalar(&bytes)
+}
+
+/// Fresh verifier randomness for an interactive challenge.
+pub fn challenge_nonce(source: &dyn RandomSource) -> Result<Vec<u8>, CryptoError> {
+    let mut nonce = vec![0u8; CHALLENGE_NONCE_BYTES];
+    source.fill_bytes(&mut nonce)?;
+
+    Ok(nonce)
+}
+
+/// Random scalar of `group`, statistically close to uniform; for public
+/// values such as challenges.
+pub fn random_scalar<G: Group>(
+    source: &dyn RandomSource,
+    group: &G,
+) -> Result<G::Scalar, CryptoError> {
+    let mut bytes = vec![0u8; group.scalar_len() + WIDE_MARGIN_BYTES];
+    source.fill_bytes(&mut bytes)?;
+
+    group.reduce_scalar(&bytes)
+}
+
+/// Random value in `[0, p)`, statistically close to uniform.
+#[cfg(feature = "openssl")]
+pub fn rng(source: &dyn RandomSource, p: &BigNum) -> Result<BigNum, CryptoError> {
+    let mut bytes = vec![0u8; p.num_bytes() as usize + RANGE_MARGIN_BYTES];
+    source.fill_bytes(&mut bytes)?;
+    let wide = BigNum::from_slice(&bytes)?;
+
+    let mut random = BigNum::new()?;
+    with_bn_ctx(|ctx| random.nnmod(&wide, p, ctx))?;
 
     Ok(random)
 }
+
+/// Checks that `value` is in `[1, order)`.
+#[cfg(feature = "openssl")]
+pub fn check_scalar(name: &'static str, value: &BigNum, order: &BigNum) -> Result<(), ParamsError> {
+    if value.is_negative() || value.num_bits() == 0 || value >= order {
+        return Err(ParamsError::ScalarOutOfRange(name));
+    }
+
+    Ok(())
+}
+
+// Extra bits drawn beyond the size of the modulus so the reduction bias is negligible
+#[cfg(feature = "openssl")]
+const SECURITY_MARGIN_BITS: i32 = 128;
+
+/// Hashes `parts` under `domain` to an integer in `[0, m)`.
+#[cfg(feature = "openssl")]
+pub fn hash_to_range(
+    domain: &[u8],
+    parts: &[&[u8]],
+    m: &BigNum,
+    ctx: &mut BigNumContext,
+) -> Result<BigNum, ErrorStack> {
+    let len = ((m.num_bits() + SECURITY_MARGIN_BITS + 7) / 8) as usize;
+    let wide = BigNum::from_slice(&expand_hash(domain, parts, len))?;
+
+    let mut result = BigNum::new()?;
+    result.nnmod(&wide, m, ctx)?;
+
+    Ok(result)
+}
+
+/// Hashes the length-prefixed `parts` under `domain` and expands the digest
+/// with SHA-512 in counter mode to exactly `len` bytes.
+pub fn expand_hash(domain: &[u8], parts: &[&[u8]], len: usize) -> Vec<u8> {
+    let mut hasher = Sha512::new();
+    hasher.update((domain.len() as u64).to_be_bytes());
+    hasher.update(domain);
+    for part in parts {
+        hasher.update((part.len() as u64).to_be_bytes());
+        hasher.update(part);
+    }
+    let seed = hasher.finalize();
+
+    let mut expanded = Vec::with_capacity(len + 64);
+    let mut counter: u32 = 0;
+    while expanded.len() < len {
+        let mut block = Sha512::new();
+        block.update(counter.to_be_bytes());
+        block.update(seed);
+        expanded.extend_from_slice(&block.finalize());
+        counter += 1;
+    }
+    expanded.truncate(len);
+
+    expanded
+}
+
+/// HMAC-SHA-512 of the concatenated `parts` under `key`.
+pub fn hmac_sha512(key: &[u8], parts: &[&[u8]]) -> [u8; 64] {
+    // HMAC takes keys of any length
+    let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC accepts any key length");
+    for part in parts {
+        mac.update(part);
+    }
+
+    mac.finalize().into_bytes().into()
+}
+
+/// HMAC-SHA-256 of the concatenated `parts` under `key`.
+pub fn hmac_sha256(key: &[u8], parts: &[&[u8]]) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts any key length");
+    for part in parts {
+        mac.update(part);
+    }
+
+    mac.finalize().into_bytes().into()
+}
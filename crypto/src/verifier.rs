@@ -1,17 +1,276 @@
-use openssl::{bn::BigNum, error::ErrorStack};
+use std::sync::Arc;
 
-pub trait Verifier<Params, Element> {
-    fn new(params: Params) -> Result<Self, ErrorStack>
+use crate::error::CryptoError;
+use crate::fiat_shamir::{self, NonInteractiveProof};
+use crate::group::Group;
+use crate::identification::{self, Challenge, IdentificationVerifier};
+use crate::prover::{ProverCommit, ProverPublicKeys};
+use crate::random::{default_source, RandomSource};
+use crate::transcript::Transcript;
+use crate::util::{batch_weight, challenge_nonce, random_scalar};
+
+/// One interactive run of the protocol, as seen by the verifier.
+pub struct ProofTranscript<'a, Element, Scalar> {
+    pub y1: &'a Element,
+    pub y2: &'a Element,
+    pub r1: &'a Element,
+    pub r2: &'a Element,
+    pub c: &'a Scalar,
+    pub s: &'a Scalar,
+}
+
+pub trait Verifier<G: Group> {
+    fn new(params: G) -> Result<Self, CryptoError>
     where
         Self: Sized;
-    fn random(&self) -> Result<BigNum, ErrorStack>;
+    fn random(&self) -> Result<G::Scalar, CryptoError>;
+    /// Fresh randomness for an interactive challenge, sent to the prover with `c`.
+    fn challenge_nonce(&self) -> Result<Vec<u8>, CryptoError>;
+    /// Challenge for an interactive run (`fiat_shamir::interactive_challenge`).
+    /// The `nonce` makes `c` unpredictable to the prover, while `transcript`
+    /// binds it to the session.
+    fn challenge(
+        &self,
+        y1: &G::Element,
+        y2: &G::Element,
+        r1: &G::Element,
+        r2: &G::Element,
+        nonce: &[u8],
+        transcript: &Transcript,
+    ) -> Result<G::Scalar, CryptoError>;
+    fn check(
+        &self,
+        y1: &G::Element,
+        y2: &G::Element,
+        r1: &G::Element,
+        r2: &G::Element,
+        c: &G::Scalar,
+        s: &G::Scalar,
+    ) -> Result<bool, CryptoError>;
+    /// Checks a non-interactive (Fiat-Shamir) proof produced for the same `transcript`.
+    fn verify(
+        &self,
+        y1: &G::Element,
+        y2: &G::Element,
+        proof: &NonInteractiveProof<G::Scalar>,
+        transcript: &Transcript,
+    ) -> Result<bool, CryptoError>;
+    /// Checks a random linear combination of the verification equations of all
+    /// `transcripts`. Holds iff every transcript passes `check`, except with
+    /// probability 2^-128. The elements must already be known to lie in the
+    /// prime-order subgroup (`check_element`), or small-order components could
+    /// cancel out.
+    fn check_combined(
+        &self,
+        transcripts: &[ProofTranscript<G::Element, G::Scalar>],
+    ) -> Result<bool, CryptoError>;
+
+    /// Verifies many transcripts at once, returning one verdict per transcript.
+    /// When the combined check fails, the batch is split in halves until the
+    /// failing transcripts are isolated.
+    fn check_batch(
+        &self,
+        transcripts: &[ProofTranscript<G::Element, G::Scalar>],
+    ) -> Result<Vec<bool>, CryptoError> {
+        let mut verdicts = Vec::with_capacity(transcripts.len());
+        let mut pending = vec![transcripts];
+
+        // Depth-first, left half first, so verdicts come out in input order
+        while let Some(batch) = pending.pop() {
+            match batch {
+                [] => {}
+                [t] => verdicts.push(self.check(t.y1, t.y2, t.r1, t.r2, t.c, t.s)?),
+                _ if self.check_combined(batch)? => verdicts.extend(batch.iter().map(|_| true)),
+                _ => {
+                    let (left, right) = batch.split_at(batch.len() / 2);
+                    pending.push(right);
+                    pending.push(left);
+                }
+            }
+        }
+
+        Ok(verdicts)
+    }
+}
+
+/// The verifier's side of Chaum-Pedersen over any `Group`.
+pub struct ChaumPedersenVerifier<G: Group> {
+    pub params: G,
+    /// Where challenges and batch weights come from.
+    pub rng: Arc<dyn RandomSource>,
+}
+
+impl<G: Group> ChaumPedersenVerifier<G> {
+    /// A verifier drawing its randomness from `rng` instead of the default source.
+    pub fn with_rng(params: G, rng: Arc<dyn RandomSource>) -> ChaumPedersenVerifier<G> {
+        ChaumPedersenVerifier { params, rng }
+    }
+
+    // Recomputes the commitment (g^s * y1^c, h^s * y2^c) an honest prover must have sent
+    fn expected_commit(
+        &self,
+        y1: &G::Element,
+        y2: &G::Element,
+        c: &G::Scalar,
+        s: &G::Scalar,
+    ) -> Result<ProverCommit<G::Element>, CryptoError> {
+        let r1 = self.params.exp_g_mul(s, y1, c)?;
+        let r2 = self.params.exp_h_mul(s, y2, c)?;
+
+        Ok(ProverCommit { r1, r2 })
+    }
+}
+
+impl<G: Group> Verifier<G> for ChaumPedersenVerifier<G> {
+    fn new(params: G) -> Result<ChaumPedersenVerifier<G>, CryptoError> {
+        Ok(ChaumPedersenVerifier::with_rng(params, default_source()))
+    }
+
+    fn random(&self) -> Result<G::Scalar, CryptoError> {
+        random_scalar(&*self.rng, &self.params)
+    }
+
+    fn challenge_nonce(&self) -> Result<Vec<u8>, CryptoError> {
+        challenge_nonce(&*self.rng)
+    }
+
+    fn challenge(
+        &self,
+        y1: &G::Element,
+        y2: &G::Element,
+        r1: &G::Element,
+        r2: &G::Element,
+        nonce: &[u8],
+        transcript: &Transcript,
+    ) -> Result<G::Scalar, CryptoError> {
+        fiat_shamir::interactive_challenge(
+            &self.params,
+            &ProverPublicKeys { y1, y2 },
+            &ProverCommit { r1, r2 },
+            nonce,
+            transcript,
+        )
+    }
+
+    fn check(
+        &self,
+        y1: &G::Element,
+        y2: &G::Element,
+        r1: &G::Element,
+        r2: &G::Element,
+        c: &G::Scalar,
+        s: &G::Scalar,
+    ) -> Result<bool, CryptoError> {
+        let expected = self.expected_commit(y1, y2, c, s)?;
+
+        Ok(
+            self.params.element_eq(&expected.r1, r1)?
+                && self.params.element_eq(&expected.r2, r2)?,
+        )
+    }
+
+    fn verify(
+        &self,
+        y1: &G::Element,
+        y2: &G::Element,
+        proof: &NonInteractiveProof<G::Scalar>,
+        transcript: &Transcript,
+    ) -> Result<bool, CryptoError> {
+        let commit = self.expected_commit(y1, y2, &proof.c, &proof.s)?;
+        let c = fiat_shamir::group_challenge(
+            &self.params,
+            &ProverPublicKeys { y1, y2 },
+            &ProverCommit {
+                r1: &commit.r1,
+                r2: &commit.r2,
+            },
+            transcript,
+        )?;
+
+        Ok(c == proof.c)
+    }
+
+    fn check_combined(
+        &self,
+        transcripts: &[ProofTranscript<G::Element, G::Scalar>],
+    ) -> Result<bool, CryptoError> {
+        let group = &self.params;
+
+        // With weights a_i, b_i, checks
+        //   prod r1_i^a_i * r2_i^b_i == g^(sum a_i s_i) * h^(sum b_i s_i) * prod y1_i^(a_i c_i) * y2_i^(b_i c_i)
+        // with both sides computed as one multi-exponentiation each
+        let mut lhs_bases = Vec::with_capacity(2 * transcripts.len());
+        let mut lhs_exponents = Vec::with_capacity(2 * transcripts.len());
+        let mut rhs_bases = Vec::with_capacity(2 * transcripts.len());
+        let mut rhs_exponents = Vec::with_capacity(2 * transcripts.len());
+        // Both sums start at zero
+        let mut g_exp = group.reduce_scalar(&[])?;
+        let mut h_exp = group.reduce_scalar(&[])?;
+
+        for t in transcripts {
+            let a = batch_weight(&*self.rng, group)?;
+            let b = batch_weight(&*self.rng, group)?;
+
+            g_exp = group.scalar_add(&g_exp, &group.scalar_mul(&a, t.s)?)?;
+            h_exp = group.scalar_add(&h_exp, &group.scalar_mul(&b, t.s)?)?;
+            rhs_bases.extend([t.y1, t.y2]);
+            rhs_exponents.extend([group.scalar_mul(&a, t.c)?, group.scalar_mul(&b, t.c)?]);
+            lhs_bases.extend([t.r1, t.r2]);
+            lhs_exponents.extend([a, b]);
+        }
+
+        let lhs_exponents: Vec<&G::Scalar> = lhs_exponents.iter().collect();
+        let rhs_exponents: Vec<&G::Scalar> = rhs_exponents.iter().collect();
+        let lhs = group.multi_exp(&lhs_bases, &lhs_exponents)?;
+        let rhs = group.multi_exp_gh(&g_exp, &h_exp, &rhs_bases, &rhs_exponents)?;
+
+        group.element_eq(&lhs, &rhs)
+    }
+}
+
+impl<G: Group> IdentificationVerifier for ChaumPedersenVerifier<G> {
+    type PublicKey = ProverPublicKeys<G::Element>;
+    type Commitment = ProverCommit<G::Element>;
+    type Response = G::Scalar;
+    type Scalar = G::Scalar;
+
+    fn challenge(
+        &self,
+        keys: &ProverPublicKeys<G::Element>,
+        commit: &ProverCommit<G::Element>,
+        transcript: &Transcript,
+    ) -> Result<Challenge<G::Scalar>, CryptoError> {
+        let nonce = self.challenge_nonce()?;
+        let c = Verifier::challenge(
+            self, &keys.y1, &keys.y2, &commit.r1, &commit.r2, &nonce, transcript,
+        )?;
+
+        Ok(Challenge { c: Some(c), nonce })
+    }
+
+    fn decoy_challenge(
+        &self,
+        transcript: &Transcript,
+    ) -> Result<Challenge<G::Scalar>, CryptoError> {
+        identification::decoy_challenge(&*self.rng, &self.params, transcript)
+    }
+
     fn check(
         &self,
-        y1: &Element,
-        y2: &Element,
-        r1: &Element,
-        r2: &Element,
-        c: &BigNum,
-        s: &BigNum,
-    ) -> Result<bool, ErrorStack>;
+        keys: &ProverPublicKeys<G::Element>,
+        commit: &ProverCommit<G::Element>,
+        challenge: &Challenge<G::Scalar>,
+        s: &G::Scalar,
+        _transcript: &Transcript,
+    ) -> Result<bool, CryptoError> {
+        Verifier::check(
+            self,
+            &keys.y1,
+            &keys.y2,
+            &commit.r1,
+            &commit.r2,
+            challenge.scalar()?,
+            s,
+        )
+    }
 }
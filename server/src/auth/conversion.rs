@@ -1,44 +1,424 @@
 use super::types::{
-    AuthenticationAnswerRequest, AuthenticationAnswerResponse, AuthenticationChallengeRequest,
-    AuthenticationChallengeResponse, RegisterRequest, RegisterResponse,
+    ActionChallengeRequest, ActionChallengeResponse, AuthenticationAnswerResponse,
+    AuthenticationChallengeRequest, AuthenticationChallengeResponse, GetSaltRequest,
+    GetSaltResponse, MembershipAnswerRequest, MembershipAnswerResponse, OpaqueLoginFinishRequest,
+    OpaqueLoginFinishResponse, OpaqueLoginStartRequest, OpaqueLoginStartResponse,
+    OpaqueRegistrationFinishRequest, OpaqueRegistrationFinishResponse,
+    OpaqueRegistrationStartRequest, OpaqueRegistrationStartResponse, RegisterRequest,
+    RegisterResponse, SignedActionRequest, SignedActionResponse,
 };
-use openssl::bn::BigNum;
-use openssl::ec::EcPoint;
+use crypto::ec::curves::NamedCurve;
+use crypto::error::{CryptoError, OpaqueError};
+use crypto::group::Group;
+use crypto::hybrid::{HybridPublicKey, HybridResponse, HybridVerifier};
+use crypto::identification::IdentificationVerifier;
+use crypto::membership::{MembershipProof, MembershipResponse};
+use crypto::okamoto::{OkamotoResponse, OkamotoVerifier};
+use crypto::opaque::messages::{Ke1, Ke3, RegistrationRecord, RegistrationRequest};
+use crypto::prover::{ProverCommit, ProverPublicKeys};
+use crypto::schnorr::{SchnorrSignature, SchnorrVerifier};
+use crypto::slh_dsa::{self, SlhDsaVerifier, VerifyingKey};
+use crypto::verifier::ChaumPedersenVerifier;
 use proto::zkp_auth::{
+    ActionChallengeRequest as ProtoActionChallengeRequest,
+    ActionChallengeResponse as ProtoActionChallengeResponse,
     AuthenticationAnswerRequest as ProtoAuthenticationAnswerRequest,
     AuthenticationAnswerResponse as ProtoAuthenticationAnswerResponse,
     AuthenticationChallengeRequest as ProtoAuthenticationChallengeRequest,
-    AuthenticationChallengeResponse as ProtoAuthenticationChallengeResponse,
+    AuthenticationChallengeResponse as ProtoAuthenticationChallengeResponse, EcCurve,
+    GetSaltRequest as ProtoGetSaltRequest, GetSaltResponse as ProtoGetSaltResponse,
+    MembershipAnswerRequest as ProtoMembershipAnswerRequest,
+    MembershipAnswerResponse as ProtoMembershipAnswerResponse,
+    OpaqueLoginFinishRequest as ProtoOpaqueLoginFinishRequest,
+    OpaqueLoginFinishResponse as ProtoOpaqueLoginFinishResponse,
+    OpaqueLoginStartRequest as ProtoOpaqueLoginStartRequest,
+    OpaqueLoginStartResponse as ProtoOpaqueLoginStartResponse,
+    OpaqueRegistrationFinishRequest as ProtoOpaqueRegistrationFinishRequest,
+    OpaqueRegistrationFinishResponse as ProtoOpaqueRegistrationFinishResponse,
+    OpaqueRegistrationStartRequest as ProtoOpaqueRegistrationStartRequest,
+    OpaqueRegistrationStartResponse as ProtoOpaqueRegistrationStartResponse,
     RegisterRequest as ProtoRegisterRequest, RegisterResponse as ProtoRegisterResponse,
+    SignedActionRequest as ProtoSignedActionRequest,
+    SignedActionResponse as ProtoSignedActionResponse,
 };
 use tonic::Status;
-use util::codec::Codec;
+
+// Longest salt accepted; the client makes 16-byte ones
+const MAX_SALT_LEN: usize = 64;
 
 pub trait FromProto<T>: Sized {
-    #[allow(clippy::wrong_self_convention)]
+    #[allow(clippy::wrong_self_convention, clippy::result_large_err)]
     fn from_proto(self) -> Result<T, Status>;
 }
 
+/// Like `FromProto`, for messages carrying group elements that can only be
+/// decoded once the group is known.
+pub trait DecodeProto<T, G>: Sized {
+    #[allow(clippy::result_large_err)]
+    fn decode_proto(self, group: &G) -> Result<T, Status>;
+}
+
 pub trait ToProto<T> {
+    #[allow(clippy::result_large_err)]
     fn to_proto(self) -> Result<T, Status>;
 }
 
-impl FromProto<RegisterRequest<EcPoint>> for ProtoRegisterRequest {
-    fn from_proto(self) -> Result<RegisterRequest<EcPoint>, Status> {
-        Ok(RegisterRequest {
-            user: self.user,
-            y1: EcPoint::decode(&self.y1).map_err(|_| Status::invalid_argument("Invalid y1"))?,
-            y2: EcPoint::decode(&self.y2).map_err(|_| Status::invalid_argument("Invalid y2"))?,
+/// Like `ToProto`, for messages carrying scalars that can only be encoded
+/// once the group is known.
+pub trait EncodeProto<T, G> {
+    #[allow(clippy::result_large_err)]
+    fn encode_proto(self, group: &G) -> Result<T, Status>;
+}
+
+/// The status a crypto error is reported with. Rejected input is the
+/// client's fault and says why; local failures don't leak details.
+pub fn crypto_status(err: CryptoError) -> Status {
+    match err {
+        CryptoError::InvalidEncoding => Status::invalid_argument("Invalid encoding"),
+        CryptoError::InvalidInput(err) => Status::invalid_argument(err.to_string()),
+        CryptoError::InvalidParams(_) => Status::failed_precondition("Invalid group parameters"),
+        CryptoError::Rng(_) | CryptoError::Entropy(_) => {
+            Status::unavailable("Random number generator failed")
+        }
+        CryptoError::Backend(_) => Status::internal("Cryptographic backend failure"),
+    }
+}
+
+/// The status an OPAQUE error is reported with. A client that fails to
+/// authenticate gets the same answer as in the other protocols.
+pub fn opaque_status(err: OpaqueError) -> Status {
+    match err {
+        OpaqueError::InvalidMessage => Status::invalid_argument("Invalid OPAQUE message"),
+        OpaqueError::ClientAuthentication => Status::unauthenticated("Authentication failed"),
+        OpaqueError::Crypto(err) => crypto_status(err),
+        _ => Status::internal("OPAQUE failure"),
+    }
+}
+
+// Decodes an OPAQUE message, naming the field if it doesn't deserialize
+#[allow(clippy::result_large_err)]
+fn decode_opaque<T>(
+    name: &'static str,
+    data: &[u8],
+    deserialize: fn(&[u8]) -> Result<T, OpaqueError>,
+) -> Result<T, Status> {
+    deserialize(data).map_err(|_| Status::invalid_argument(format!("Invalid {}", name)))
+}
+
+// Decodes `data` and checks it is a non-identity element of the prime-order subgroup
+#[allow(clippy::result_large_err)]
+fn decode_element<G: Group>(
+    group: &G,
+    name: &'static str,
+    data: &[u8],
+) -> Result<G::Element, Status> {
+    let element = group.decode_element(data).map_err(|err| match err {
+        CryptoError::InvalidEncoding => Status::invalid_argument(format!("Invalid {}", name)),
+        err => crypto_status(err),
+    })?;
+
+    group
+        .check_element(name, &element)
+        .map_err(|err| crypto_status(err.into()))?;
+
+    Ok(element)
+}
+
+// Elements and responses a protocol has no use for must be left out
+#[allow(clippy::result_large_err)]
+fn check_empty(name: &'static str, data: &[u8]) -> Result<(), Status> {
+    match data.is_empty() {
+        true => Ok(()),
+        false => Err(Status::invalid_argument(format!("Unexpected {}", name))),
+    }
+}
+
+#[allow(clippy::result_large_err)]
+fn decode_pq_key(data: &[u8]) -> Result<VerifyingKey, Status> {
+    VerifyingKey::from_bytes(data).map_err(|_| Status::invalid_argument("Invalid pq_public_key"))
+}
+
+// Only the length is checked here; anything else is for verification to reject
+#[allow(clippy::result_large_err)]
+fn check_signature(signature: &[u8]) -> Result<Vec<u8>, Status> {
+    match signature.len() {
+        slh_dsa::SIGNATURE_LEN => Ok(signature.to_vec()),
+        _ => Err(Status::invalid_argument("Invalid signature")),
+    }
+}
+
+#[allow(clippy::result_large_err)]
+fn check_salt(salt: Vec<u8>) -> Result<Vec<u8>, Status> {
+    match salt.len() {
+        0..=MAX_SALT_LEN => Ok(salt),
+        _ => Err(Status::invalid_argument("Invalid salt")),
+    }
+}
+
+/// Decoding of the fields a protocol's public keys, commitments and
+/// responses are carried in; any it has no use for must be left empty.
+pub trait DecodeIdentification: IdentificationVerifier {
+    #[allow(clippy::result_large_err)]
+    fn decode_key(&self, request: &ProtoRegisterRequest) -> Result<Self::PublicKey, Status>;
+    #[allow(clippy::result_large_err)]
+    fn decode_commitment(
+        &self,
+        request: &ProtoAuthenticationChallengeRequest,
+    ) -> Result<Self::Commitment, Status>;
+    /// Checks the fields of an answer that need no parameters, so malformed
+    /// ones can be rejected before their login is looked up.
+    #[allow(clippy::result_large_err)]
+    fn check_response(request: &ProtoAuthenticationAnswerRequest) -> Result<(), Status>;
+    /// Decodes an answer `check_response` let through, with the parameters
+    /// of the login it answers.
+    #[allow(clippy::result_large_err)]
+    fn decode_response(
+        &self,
+        request: &ProtoAuthenticationAnswerRequest,
+    ) -> Result<Self::Response, Status>;
+    #[allow(clippy::result_large_err)]
+    fn encode_challenge(&self, c: &Self::Scalar) -> Result<Vec<u8>, Status>;
+}
+
+#[allow(clippy::result_large_err)]
+fn decode_keys<G: Group>(
+    group: &G,
+    request: &ProtoRegisterRequest,
+) -> Result<ProverPublicKeys<G::Element>, Status> {
+    Ok(ProverPublicKeys {
+        y1: decode_element(group, "y1", &request.y1)?,
+        y2: decode_element(group, "y2", &request.y2)?,
+    })
+}
+
+#[allow(clippy::result_large_err)]
+fn decode_commit<G: Group>(
+    group: &G,
+    request: &ProtoAuthenticationChallengeRequest,
+) -> Result<ProverCommit<G::Element>, Status> {
+    Ok(ProverCommit {
+        r1: decode_element(group, "r1", &request.r1)?,
+        r2: decode_element(group, "r2", &request.r2)?,
+    })
+}
+
+// Schnorr and Okamoto register only y1 and commit only r1
+#[allow(clippy::result_large_err)]
+fn decode_single_key<G: Group>(
+    group: &G,
+    request: &ProtoRegisterRequest,
+) -> Result<G::Element, Status> {
+    check_empty("y2", &request.y2)?;
+    check_empty("pq_public_key", &request.pq_public_key)?;
+
+    decode_element(group, "y1", &request.y1)
+}
+
+#[allow(clippy::result_large_err)]
+fn decode_single_commit<G: Group>(
+    group: &G,
+    request: &ProtoAuthenticationChallengeRequest,
+) -> Result<G::Element, Status> {
+    check_empty("r2", &request.r2)?;
+
+    decode_element(group, "r1", &request.r1)
+}
+
+// Decodes a scalar in `[0, q)`; larger values would make proofs malleable
+#[allow(clippy::result_large_err)]
+fn decode_scalar<G: Group>(
+    group: &G,
+    name: &'static str,
+    data: &[u8],
+) -> Result<G::Scalar, Status> {
+    group.decode_scalar(data).map_err(|err| match err {
+        CryptoError::InvalidEncoding => Status::invalid_argument(format!("Invalid {}", name)),
+        err => crypto_status(err),
+    })
+}
+
+impl<G: Group> DecodeIdentification for ChaumPedersenVerifier<G> {
+    fn decode_key(
+        &self,
+        request: &ProtoRegisterRequest,
+    ) -> Result<ProverPublicKeys<G::Element>, Status> {
+        check_empty("pq_public_key", &request.pq_public_key)?;
+
+        decode_keys(&self.params, request)
+    }
+
+    fn decode_commitment(
+        &self,
+        request: &ProtoAuthenticationChallengeRequest,
+    ) -> Result<ProverCommit<G::Element>, Status> {
+        decode_commit(&self.params, request)
+    }
+
+    fn check_response(request: &ProtoAuthenticationAnswerRequest) -> Result<(), Status> {
+        check_empty("s2", &request.s2)?;
+        check_empty("signature", &request.signature)
+    }
+
+    fn decode_response(
+        &self,
+        request: &ProtoAuthenticationAnswerRequest,
+    ) -> Result<G::Scalar, Status> {
+        decode_scalar(&self.params, "s", &request.s)
+    }
+
+    fn encode_challenge(&self, c: &G::Scalar) -> Result<Vec<u8>, Status> {
+        self.params.encode_scalar(c).map_err(crypto_status)
+    }
+}
+
+impl<G: Group> DecodeIdentification for SchnorrVerifier<G> {
+    fn decode_key(&self, request: &ProtoRegisterRequest) -> Result<G::Element, Status> {
+        decode_single_key(&self.params, request)
+    }
+
+    fn decode_commitment(
+        &self,
+        request: &ProtoAuthenticationChallengeRequest,
+    ) -> Result<G::Element, Status> {
+        decode_single_commit(&self.params, request)
+    }
+
+    fn check_response(request: &ProtoAuthenticationAnswerRequest) -> Result<(), Status> {
+        check_empty("s2", &request.s2)?;
+        check_empty("signature", &request.signature)
+    }
+
+    fn decode_response(
+        &self,
+        request: &ProtoAuthenticationAnswerRequest,
+    ) -> Result<G::Scalar, Status> {
+        decode_scalar(&self.params, "s", &request.s)
+    }
+
+    fn encode_challenge(&self, c: &G::Scalar) -> Result<Vec<u8>, Status> {
+        self.params.encode_scalar(c).map_err(crypto_status)
+    }
+}
+
+impl<G: Group> DecodeIdentification for OkamotoVerifier<G> {
+    fn decode_key(&self, request: &ProtoRegisterRequest) -> Result<G::Element, Status> {
+        decode_single_key(&self.params, request)
+    }
+
+    fn decode_commitment(
+        &self,
+        request: &ProtoAuthenticationChallengeRequest,
+    ) -> Result<G::Element, Status> {
+        decode_single_commit(&self.params, request)
+    }
+
+    fn check_response(request: &ProtoAuthenticationAnswerRequest) -> Result<(), Status> {
+        check_empty("signature", &request.signature)
+    }
+
+    fn decode_response(
+        &self,
+        request: &ProtoAuthenticationAnswerRequest,
+    ) -> Result<OkamotoResponse<G::Scalar>, Status> {
+        Ok(OkamotoResponse {
+            s1: decode_scalar(&self.params, "s", &request.s)?,
+            s2: decode_scalar(&self.params, "s2", &request.s2)?,
+        })
+    }
+
+    fn encode_challenge(&self, c: &G::Scalar) -> Result<Vec<u8>, Status> {
+        self.params.encode_scalar(c).map_err(crypto_status)
+    }
+}
+
+impl DecodeIdentification for SlhDsaVerifier {
+    fn decode_key(&self, request: &ProtoRegisterRequest) -> Result<VerifyingKey, Status> {
+        check_empty("y1", &request.y1)?;
+        check_empty("y2", &request.y2)?;
+
+        decode_pq_key(&request.pq_public_key)
+    }
+
+    fn decode_commitment(
+        &self,
+        request: &ProtoAuthenticationChallengeRequest,
+    ) -> Result<(), Status> {
+        check_empty("r1", &request.r1)?;
+        check_empty("r2", &request.r2)
+    }
+
+    fn check_response(request: &ProtoAuthenticationAnswerRequest) -> Result<(), Status> {
+        check_empty("s", &request.s)?;
+        check_empty("s2", &request.s2)?;
+        check_signature(&request.signature)?;
+
+        Ok(())
+    }
+
+    fn decode_response(
+        &self,
+        request: &ProtoAuthenticationAnswerRequest,
+    ) -> Result<Vec<u8>, Status> {
+        check_signature(&request.signature)
+    }
+
+    // SLH-DSA challenges are a nonce alone
+    fn encode_challenge(&self, _c: &()) -> Result<Vec<u8>, Status> {
+        Ok(Vec::new())
+    }
+}
+
+impl<G: Group> DecodeIdentification for HybridVerifier<G> {
+    fn decode_key(
+        &self,
+        request: &ProtoRegisterRequest,
+    ) -> Result<HybridPublicKey<G::Element>, Status> {
+        Ok(HybridPublicKey {
+            keys: decode_keys(&self.classical.params, request)?,
+            pq_key: decode_pq_key(&request.pq_public_key)?,
+        })
+    }
+
+    fn decode_commitment(
+        &self,
+        request: &ProtoAuthenticationChallengeRequest,
+    ) -> Result<ProverCommit<G::Element>, Status> {
+        decode_commit(&self.classical.params, request)
+    }
+
+    fn check_response(request: &ProtoAuthenticationAnswerRequest) -> Result<(), Status> {
+        check_empty("s2", &request.s2)?;
+        check_signature(&request.signature)?;
+
+        Ok(())
+    }
+
+    fn decode_response(
+        &self,
+        request: &ProtoAuthenticationAnswerRequest,
+    ) -> Result<HybridResponse<G::Scalar>, Status> {
+        Ok(HybridResponse {
+            s: decode_scalar(&self.classical.params, "s", &request.s)?,
+            signature: check_signature(&request.signature)?,
         })
     }
+
+    fn encode_challenge(&self, c: &G::Scalar) -> Result<Vec<u8>, Status> {
+        self.classical
+            .params
+            .encode_scalar(c)
+            .map_err(crypto_status)
+    }
 }
 
-impl FromProto<RegisterRequest<BigNum>> for ProtoRegisterRequest {
-    fn from_proto(self) -> Result<RegisterRequest<BigNum>, Status> {
+impl<P: DecodeIdentification> DecodeProto<RegisterRequest<P::PublicKey>, P>
+    for ProtoRegisterRequest
+{
+    fn decode_proto(self, verifier: &P) -> Result<RegisterRequest<P::PublicKey>, Status> {
         Ok(RegisterRequest {
+            key: verifier.decode_key(&self)?,
             user: self.user,
-            y1: BigNum::decode(&self.y1).map_err(|_| Status::invalid_argument("Invalid y1"))?,
-            y2: BigNum::decode(&self.y2).map_err(|_| Status::invalid_argument("Invalid y2"))?,
+            salt: check_salt(self.salt)?,
         })
     }
 }
@@ -49,51 +429,206 @@ impl ToProto<ProtoRegisterResponse> for RegisterResponse {
     }
 }
 
-impl FromProto<AuthenticationChallengeRequest<EcPoint>> for ProtoAuthenticationChallengeRequest {
-    fn from_proto(self) -> Result<AuthenticationChallengeRequest<EcPoint>, Status> {
+impl FromProto<GetSaltRequest> for ProtoGetSaltRequest {
+    fn from_proto(self) -> Result<GetSaltRequest, Status> {
+        Ok(GetSaltRequest { user: self.user })
+    }
+}
+
+impl ToProto<ProtoGetSaltResponse> for GetSaltResponse {
+    fn to_proto(self) -> Result<ProtoGetSaltResponse, Status> {
+        Ok(ProtoGetSaltResponse { salt: self.salt })
+    }
+}
+
+impl<P: DecodeIdentification> DecodeProto<AuthenticationChallengeRequest<P::Commitment>, P>
+    for ProtoAuthenticationChallengeRequest
+{
+    fn decode_proto(
+        self,
+        verifier: &P,
+    ) -> Result<AuthenticationChallengeRequest<P::Commitment>, Status> {
         Ok(AuthenticationChallengeRequest {
+            commitment: verifier.decode_commitment(&self)?,
             user: self.user,
-            r1: EcPoint::decode(&self.r1).map_err(|_| Status::invalid_argument("Invalid r1"))?,
-            r2: EcPoint::decode(&self.r2).map_err(|_| Status::invalid_argument("Invalid r2"))?,
         })
     }
 }
 
-impl FromProto<AuthenticationChallengeRequest<BigNum>> for ProtoAuthenticationChallengeRequest {
-    fn from_proto(self) -> Result<AuthenticationChallengeRequest<BigNum>, Status> {
-        Ok(AuthenticationChallengeRequest {
+// Protocols without a `c` send it empty
+impl<P: DecodeIdentification> EncodeProto<ProtoAuthenticationChallengeResponse, P>
+    for AuthenticationChallengeResponse<P::Scalar>
+{
+    fn encode_proto(self, verifier: &P) -> Result<ProtoAuthenticationChallengeResponse, Status> {
+        Ok(ProtoAuthenticationChallengeResponse {
+            auth_id: self.auth_id,
+            c: match &self.challenge.c {
+                Some(c) => verifier.encode_challenge(c)?,
+                None => vec![],
+            },
+            nonce: self.challenge.nonce,
+        })
+    }
+}
+
+impl ToProto<ProtoAuthenticationAnswerResponse> for AuthenticationAnswerResponse {
+    fn to_proto(self) -> Result<ProtoAuthenticationAnswerResponse, Status> {
+        Ok(ProtoAuthenticationAnswerResponse {
+            session_id: self.session_id,
+        })
+    }
+}
+
+impl<G: Group> DecodeProto<MembershipAnswerRequest<G::Scalar>, G> for ProtoMembershipAnswerRequest {
+    fn decode_proto(self, group: &G) -> Result<MembershipAnswerRequest<G::Scalar>, Status> {
+        if self.c.len() != self.s.len() {
+            return Err(Status::invalid_argument("Mismatched c and s"));
+        }
+
+        let mut responses = Vec::with_capacity(self.c.len());
+        for (c, s) in self.c.iter().zip(&self.s) {
+            responses.push(MembershipResponse {
+                c: decode_scalar(group, "c", c)?,
+                s: decode_scalar(group, "s", s)?,
+            });
+        }
+
+        Ok(MembershipAnswerRequest {
+            auth_id: self.auth_id,
+            proof: MembershipProof { responses },
+        })
+    }
+}
+
+impl ToProto<ProtoMembershipAnswerResponse> for MembershipAnswerResponse {
+    fn to_proto(self) -> Result<ProtoMembershipAnswerResponse, Status> {
+        Ok(ProtoMembershipAnswerResponse {
+            session_id: self.session_id,
+            anonymity_set_size: self.anonymity_set_size,
+        })
+    }
+}
+
+impl FromProto<ActionChallengeRequest> for ProtoActionChallengeRequest {
+    fn from_proto(self) -> Result<ActionChallengeRequest, Status> {
+        Ok(ActionChallengeRequest { user: self.user })
+    }
+}
+
+impl ToProto<ProtoActionChallengeResponse> for ActionChallengeResponse {
+    fn to_proto(self) -> Result<ProtoActionChallengeResponse, Status> {
+        Ok(ProtoActionChallengeResponse {
+            action_id: self.action_id,
+        })
+    }
+}
+
+impl<G: Group> DecodeProto<SignedActionRequest<G::Scalar>, G> for ProtoSignedActionRequest {
+    fn decode_proto(self, group: &G) -> Result<SignedActionRequest<G::Scalar>, Status> {
+        Ok(SignedActionRequest {
             user: self.user,
-            r1: BigNum::decode(&self.r1).map_err(|_| Status::invalid_argument("Invalid r1"))?,
-            r2: BigNum::decode(&self.r2).map_err(|_| Status::invalid_argument("Invalid r2"))?,
+            action: self.action,
+            signature: SchnorrSignature {
+                c: decode_scalar(group, "c", &self.c)?,
+                s: decode_scalar(group, "s", &self.s)?,
+            },
+            action_id: self.action_id,
         })
     }
 }
 
-impl ToProto<ProtoAuthenticationChallengeResponse> for AuthenticationChallengeResponse {
-    fn to_proto(self) -> Result<ProtoAuthenticationChallengeResponse, Status> {
-        Ok(ProtoAuthenticationChallengeResponse {
+impl ToProto<ProtoSignedActionResponse> for SignedActionResponse {
+    fn to_proto(self) -> Result<ProtoSignedActionResponse, Status> {
+        Ok(ProtoSignedActionResponse {
+            action_id: self.action_id,
+        })
+    }
+}
+
+impl FromProto<OpaqueRegistrationStartRequest> for ProtoOpaqueRegistrationStartRequest {
+    fn from_proto(self) -> Result<OpaqueRegistrationStartRequest, Status> {
+        Ok(OpaqueRegistrationStartRequest {
+            user: self.user,
+            request: decode_opaque(
+                "registration_request",
+                &self.registration_request,
+                RegistrationRequest::deserialize,
+            )?,
+        })
+    }
+}
+
+impl ToProto<ProtoOpaqueRegistrationStartResponse> for OpaqueRegistrationStartResponse {
+    fn to_proto(self) -> Result<ProtoOpaqueRegistrationStartResponse, Status> {
+        Ok(ProtoOpaqueRegistrationStartResponse {
+            registration_response: self.response.serialize(),
+            registration_id: self.registration_id,
+        })
+    }
+}
+
+impl FromProto<OpaqueRegistrationFinishRequest> for ProtoOpaqueRegistrationFinishRequest {
+    fn from_proto(self) -> Result<OpaqueRegistrationFinishRequest, Status> {
+        Ok(OpaqueRegistrationFinishRequest {
+            registration_id: self.registration_id,
+            user: self.user,
+            record: decode_opaque(
+                "registration_record",
+                &self.registration_record,
+                RegistrationRecord::deserialize,
+            )?,
+        })
+    }
+}
+
+impl ToProto<ProtoOpaqueRegistrationFinishResponse> for OpaqueRegistrationFinishResponse {
+    fn to_proto(self) -> Result<ProtoOpaqueRegistrationFinishResponse, Status> {
+        Ok(ProtoOpaqueRegistrationFinishResponse {})
+    }
+}
+
+impl FromProto<OpaqueLoginStartRequest> for ProtoOpaqueLoginStartRequest {
+    fn from_proto(self) -> Result<OpaqueLoginStartRequest, Status> {
+        Ok(OpaqueLoginStartRequest {
+            user: self.user,
+            ke1: decode_opaque("ke1", &self.ke1, Ke1::deserialize)?,
+        })
+    }
+}
+
+impl ToProto<ProtoOpaqueLoginStartResponse> for OpaqueLoginStartResponse {
+    fn to_proto(self) -> Result<ProtoOpaqueLoginStartResponse, Status> {
+        Ok(ProtoOpaqueLoginStartResponse {
             auth_id: self.auth_id,
-            c: self
-                .c
-                .encode()
-                .map_err(|_| Status::invalid_argument("Invalid c"))?,
+            ke2: self.ke2.serialize(),
         })
     }
 }
 
-impl FromProto<AuthenticationAnswerRequest> for ProtoAuthenticationAnswerRequest {
-    fn from_proto(self) -> Result<AuthenticationAnswerRequest, Status> {
-        Ok(AuthenticationAnswerRequest {
+impl FromProto<OpaqueLoginFinishRequest> for ProtoOpaqueLoginFinishRequest {
+    fn from_proto(self) -> Result<OpaqueLoginFinishRequest, Status> {
+        Ok(OpaqueLoginFinishRequest {
             auth_id: self.auth_id,
-            s: BigNum::decode(&self.s).map_err(|_| Status::invalid_argument("Invalid s"))?,
+            ke3: decode_opaque("ke3", &self.ke3, Ke3::deserialize)?,
         })
     }
 }
 
-impl ToProto<ProtoAuthenticationAnswerResponse> for AuthenticationAnswerResponse {
-    fn to_proto(self) -> Result<ProtoAuthenticationAnswerResponse, Status> {
-        Ok(ProtoAuthenticationAnswerResponse {
+impl ToProto<ProtoOpaqueLoginFinishResponse> for OpaqueLoginFinishResponse {
+    fn to_proto(self) -> Result<ProtoOpaqueLoginFinishResponse, Status> {
+        Ok(ProtoOpaqueLoginFinishResponse {
             session_id: self.session_id,
         })
     }
 }
+
+impl FromProto<NamedCurve> for EcCurve {
+    fn from_proto(self) -> Result<NamedCurve, Status> {
+        Ok(match self {
+            EcCurve::Secp256k1 => NamedCurve::Secp256k1,
+            EcCurve::P256 => NamedCurve::P256,
+            EcCurve::P384 => NamedCurve::P384,
+            EcCurve::P521 => NamedCurve::P521,
+        })
+    }
+}
@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+use super::conversion::{crypto_status, DecodeProto, ToProto};
+use super::identification::IdentificationServer;
+use super::types::{MembershipAnswerRequest, MembershipAnswerResponse, MembershipState};
+use crypto::dl::params::DlParams;
+use crypto::ec::curves::NamedCurve;
+use crypto::ec::params::EcParams;
+use crypto::group::Group;
+use crypto::membership::MembershipVerifier;
+use crypto::prover::ProverPublicKeys;
+use crypto::ristretto::params::RistrettoParams;
+use crypto::verifier::ChaumPedersenVerifier;
+use dashmap::DashMap;
+use tonic::Status;
+use util::transcript::membership_transcript;
+use uuid::Uuid;
+
+use proto::zkp_auth::{
+    MemberKeys, MembershipAnswerRequest as ProtoMembershipAnswerRequest,
+    MembershipAnswerResponse as ProtoMembershipAnswerResponse,
+    MembershipChallengeRequest as ProtoMembershipChallengeRequest,
+    MembershipChallengeResponse as ProtoMembershipChallengeResponse,
+};
+
+// Fewer members than this and a membership login would identify the user
+const MIN_ANONYMITY_SET: usize = 2;
+
+/// Most members a configured group may have; proofs grow linearly with it.
+pub const MAX_ANONYMITY_SET: usize = 256;
+
+// How long a membership challenge can be answered, and how many may be
+// waiting for an answer at once
+const MEMBERSHIP_CHALLENGE_TTL: Duration = Duration::from_secs(300);
+const MAX_PENDING_CHALLENGES: usize = 10_000;
+
+pub type DlMembershipServer = MembershipServer<DlParams>;
+pub type EcMembershipServer = MembershipServer<EcParams, NamedCurve>;
+pub type RistrettoMembershipServer = MembershipServer<RistrettoParams>;
+
+/// Anonymous logins over the Chaum-Pedersen keys registered with an
+/// `IdentificationServer` of the same group.
+pub struct MembershipServer<G: Group, V = ()> {
+    verifiers: HashMap<V, MembershipVerifier<G>>,
+    states: DashMap<String, MembershipState<V>>,
+}
+
+impl<G: Group, V: Copy + Eq + Hash> MembershipServer<G, V> {
+    pub fn new(auth: &IdentificationServer<ChaumPedersenVerifier<G>, V>) -> Self {
+        let verifiers = auth
+            .verifiers()
+            .iter()
+            .map(|(variant, verifier)| {
+                let membership =
+                    MembershipVerifier::with_rng(verifier.params.clone(), verifier.rng.clone());
+                (*variant, membership)
+            })
+            .collect();
+
+        MembershipServer {
+            verifiers,
+            states: DashMap::new(),
+        }
+    }
+
+    /// Starts an anonymous login among `group`, a set the server was
+    /// configured with. The anonymity set is the members registered with
+    /// the same parameters, sorted by name, and the proof must be bound to
+    /// the returned `auth_id`. Password-derived keys are left out: together
+    /// with the salt anyone can fetch, they would allow guessing the password
+    /// offline.
+    pub async fn create_membership_challenge(
+        &self,
+        auth: &IdentificationServer<ChaumPedersenVerifier<G>, V>,
+        variant: V,
+        group: &[String],
+        _request: ProtoMembershipChallengeRequest,
+    ) -> Result<ProtoMembershipChallengeResponse, Status> {
+        let verifier = auth.verifier(variant)?;
+
+        let mut members = Vec::new();
+        for user in group {
+            let Some(registration) = auth.registrations().get(user) else {
+                continue;
+            };
+            if registration.variant != variant || !registration.salt.is_empty() {
+                continue;
+            }
+
+            let keys = &registration.key;
+            let encode = |element| verifier.params.encode_element(element);
+            let member = encode(&keys.y1)
+                .and_then(|y1| {
+                    Ok(MemberKeys {
+                        y1,
+                        y2: encode(&keys.y2)?,
+                    })
+                })
+                .map_err(crypto_status)?;
+            members.push((user.clone(), member));
+        }
+        if members.len() < MIN_ANONYMITY_SET {
+            return Err(Status::failed_precondition("Not enough registered members"));
+        }
+        members.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        self.states
+            .retain(|_, state| state.issued.elapsed() < MEMBERSHIP_CHALLENGE_TTL);
+        if self.states.len() >= MAX_PENDING_CHALLENGES {
+            return Err(Status::resource_exhausted(
+                "Too many pending membership logins",
+            ));
+        }
+
+        let auth_id = Uuid::new_v4().to_string();
+        let (users, members) = members.into_iter().unzip();
+        self.states.insert(
+            auth_id.clone(),
+            MembershipState {
+                variant,
+                members: users,
+                issued: Instant::now(),
+            },
+        );
+
+        Ok(ProtoMembershipChallengeResponse { auth_id, members })
+    }
+
+    /// Checks a membership proof. Each challenge can be answered once, before
+    /// it expires; a member who re-registered since it was issued has to
+    /// start over.
+    pub async fn verify_membership(
+        &self,
+        auth: &IdentificationServer<ChaumPedersenVerifier<G>, V>,
+        request: ProtoMembershipAnswerRequest,
+    ) -> Result<ProtoMembershipAnswerResponse, Status> {
+        let (auth_id, state) = self
+            .states
+            .remove(&request.auth_id)
+            .filter(|(_, state)| state.issued.elapsed() < MEMBERSHIP_CHALLENGE_TTL)
+            .ok_or_else(|| Status::unauthenticated("Authentication failed"))?;
+        let verifier = auth.verifier(state.variant)?;
+        let membership_verifier = &self.verifiers[&state.variant];
+        let request: MembershipAnswerRequest<G::Scalar> = request.decode_proto(&verifier.params)?;
+
+        let registrations = state
+            .members
+            .iter()
+            .map(|user| auth.registrations().get(user))
+            .collect::<Option<Vec<_>>>()
+            .filter(|registrations| registrations.iter().all(|r| r.variant == state.variant))
+            .ok_or_else(|| Status::unauthenticated("Authentication failed"))?;
+        let keys: Vec<_> = registrations
+            .iter()
+            .map(|registration| ProverPublicKeys {
+                y1: &registration.key.y1,
+                y2: &registration.key.y2,
+            })
+            .collect();
+
+        let verified = membership_verifier
+            .verify(
+                &keys,
+                &request.proof,
+                &membership_transcript(auth.identity(), &auth_id),
+            )
+            .unwrap_or(false);
+        if !verified {
+            return Err(Status::unauthenticated("Authentication failed"));
+        }
+
+        let response = MembershipAnswerResponse {
+            session_id: Uuid::new_v4().to_string(),
+            anonymity_set_size: keys.len() as u32,
+        };
+        response.to_proto()
+    }
+}
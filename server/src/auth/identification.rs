@@ -0,0 +1,343 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::time::{Duration, Instant};
+
+use super::conversion::{
+    crypto_status, DecodeIdentification, DecodeProto, EncodeProto, FromProto, ToProto,
+};
+use super::types::{
+    ActionChallengeResponse, ActionState, AuthenticationAnswerResponse,
+    AuthenticationChallengeRequest, AuthenticationChallengeResponse, AuthenticationState,
+    GetSaltResponse, RegisterRequest, Registration, SignedActionRequest, SignedActionResponse,
+};
+use crypto::dl::params::DlParams;
+use crypto::ec::curves::NamedCurve;
+use crypto::ec::params::EcParams;
+use crypto::hybrid::HybridVerifier;
+use crypto::identification::IdentificationVerifier;
+use crypto::okamoto::OkamotoVerifier;
+use crypto::password::SALT_LEN;
+use crypto::ristretto::params::RistrettoParams;
+use crypto::schnorr::{SchnorrKey, SchnorrSignatureVerifier, SchnorrVerifier};
+use crypto::slh_dsa::SlhDsaVerifier;
+use crypto::util::expand_hash;
+use crypto::verifier::ChaumPedersenVerifier;
+use dashmap::DashMap;
+use openssl::error::ErrorStack;
+use openssl::rand::rand_bytes;
+use tonic::Status;
+use util::transcript::{action_transcript, login_transcript};
+use uuid::Uuid;
+
+use proto::zkp_auth::{
+    ActionChallengeRequest as ProtoActionChallengeRequest,
+    ActionChallengeResponse as ProtoActionChallengeResponse,
+    AuthenticationAnswerRequest as ProtoAuthenticationAnswerRequest,
+    AuthenticationAnswerResponse as ProtoAuthenticationAnswerResponse,
+    AuthenticationChallengeRequest as ProtoAuthenticationChallengeRequest,
+    AuthenticationChallengeResponse as ProtoAuthenticationChallengeResponse,
+    GetSaltRequest as ProtoGetSaltRequest, GetSaltResponse as ProtoGetSaltResponse,
+    RegisterRequest as ProtoRegisterRequest, RegisterResponse as ProtoRegisterResponse,
+    SignedActionRequest as ProtoSignedActionRequest,
+    SignedActionResponse as ProtoSignedActionResponse,
+};
+
+const DECOY_SALT_DOMAIN: &[u8] = b"zkp_auth/server/decoy-salt/v1";
+
+// How long a login challenge can be answered, and how many may be waiting
+// for an answer at once
+const LOGIN_CHALLENGE_TTL: Duration = Duration::from_secs(300);
+const MAX_PENDING_LOGINS: usize = 10_000;
+
+// How long an action challenge can be signed, and how many may be waiting
+// for a signature at once
+const ACTION_CHALLENGE_TTL: Duration = Duration::from_secs(300);
+const MAX_PENDING_ACTIONS: usize = 10_000;
+
+/// Salt served for `user` when they have none: keyed, so it can't be told
+/// apart from a real one, and stable, so asking twice doesn't give it away.
+pub fn decoy_salt(key: &[u8], user: &str) -> Vec<u8> {
+    expand_hash(DECOY_SALT_DOMAIN, &[key, user.as_bytes()], SALT_LEN)
+}
+
+pub type DlAuthServer = IdentificationServer<ChaumPedersenVerifier<DlParams>>;
+pub type EcAuthServer = IdentificationServer<ChaumPedersenVerifier<EcParams>, NamedCurve>;
+pub type RistrettoAuthServer = IdentificationServer<ChaumPedersenVerifier<RistrettoParams>>;
+pub type SchnorrDlAuthServer = IdentificationServer<SchnorrVerifier<DlParams>>;
+pub type SchnorrEcAuthServer = IdentificationServer<SchnorrVerifier<EcParams>, NamedCurve>;
+pub type OkamotoDlAuthServer = IdentificationServer<OkamotoVerifier<DlParams>>;
+pub type OkamotoEcAuthServer = IdentificationServer<OkamotoVerifier<EcParams>, NamedCurve>;
+pub type SlhDsaAuthServer = IdentificationServer<SlhDsaVerifier>;
+pub type HybridDlAuthServer = IdentificationServer<HybridVerifier<DlParams>>;
+pub type HybridEcAuthServer = IdentificationServer<HybridVerifier<EcParams>, NamedCurve>;
+
+/// Auth server for one identification protocol. `V` selects one of several
+/// parameter sets, such as the EC curve, and is `()` when there is only one.
+pub struct IdentificationServer<P: IdentificationVerifier, V = ()> {
+    /// Name the server's transcripts are bound to, as clients dial it.
+    identity: String,
+    verifiers: HashMap<V, P>,
+    registrations: DashMap<String, Registration<V, P::PublicKey>>,
+    authentication_states: DashMap<String, AuthenticationState<P::Commitment, P::Scalar>>,
+    action_states: DashMap<String, ActionState<V>>,
+    /// Key the decoy salts served for unknown users are derived from.
+    decoy_salt_key: [u8; 32],
+}
+
+impl<P: DecodeIdentification> IdentificationServer<P> {
+    /// A server with a single parameter set.
+    pub fn single(identity: &str, verifier: P) -> Result<Self, ErrorStack> {
+        IdentificationServer::new(identity, HashMap::from([((), verifier)]))
+    }
+}
+
+impl<P: DecodeIdentification, V: Copy + Eq + Hash> IdentificationServer<P, V> {
+    pub fn new(identity: &str, verifiers: HashMap<V, P>) -> Result<Self, ErrorStack> {
+        let mut decoy_salt_key = [0u8; 32];
+        rand_bytes(&mut decoy_salt_key)?;
+
+        Ok(IdentificationServer {
+            identity: identity.to_string(),
+            verifiers,
+            registrations: DashMap::new(),
+            authentication_states: DashMap::new(),
+            action_states: DashMap::new(),
+            decoy_salt_key,
+        })
+    }
+
+    pub fn identity(&self) -> &str {
+        &self.identity
+    }
+
+    pub fn verifiers(&self) -> &HashMap<V, P> {
+        &self.verifiers
+    }
+
+    pub fn registrations(&self) -> &DashMap<String, Registration<V, P::PublicKey>> {
+        &self.registrations
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub fn verifier(&self, variant: V) -> Result<&P, Status> {
+        self.verifiers
+            .get(&variant)
+            .ok_or_else(|| Status::invalid_argument("Unsupported group parameters"))
+    }
+
+    pub async fn register(
+        &self,
+        variant: V,
+        request: ProtoRegisterRequest,
+    ) -> Result<ProtoRegisterResponse, Status> {
+        let verifier = self.verifier(variant)?;
+        let request: RegisterRequest<P::PublicKey> = request.decode_proto(verifier)?;
+
+        self.registrations.insert(
+            request.user,
+            Registration {
+                variant,
+                key: request.key,
+                salt: request.salt,
+            },
+        );
+
+        Ok(ProtoRegisterResponse {})
+    }
+
+    /// The salt `user` registered a password-derived secret with. Anyone
+    /// else, registered or not, gets a decoy that is stable across calls, so
+    /// the response can't be used to enumerate users.
+    pub async fn get_salt(
+        &self,
+        request: ProtoGetSaltRequest,
+    ) -> Result<ProtoGetSaltResponse, Status> {
+        let request = request.from_proto()?;
+
+        let salt = match self.registrations.get(&request.user) {
+            Some(registration) if !registration.salt.is_empty() => registration.salt.clone(),
+            _ => decoy_salt(&self.decoy_salt_key, &request.user),
+        };
+
+        GetSaltResponse { salt }.to_proto()
+    }
+
+    /// Challenges `user` to answer for `commitment`, once and before it
+    /// expires. Whether they are registered only shows in whether the answer
+    /// is accepted.
+    pub async fn create_authentication_challenge(
+        &self,
+        variant: V,
+        request: ProtoAuthenticationChallengeRequest,
+    ) -> Result<ProtoAuthenticationChallengeResponse, Status> {
+        let verifier = self.verifier(variant)?;
+        let request: AuthenticationChallengeRequest<P::Commitment> =
+            request.decode_proto(verifier)?;
+
+        // Checked for decoys as well, so hitting the cap doesn't tell
+        // registered users apart
+        self.authentication_states
+            .retain(|_, state| state.issued.elapsed() < LOGIN_CHALLENGE_TTL);
+        if self.authentication_states.len() >= MAX_PENDING_LOGINS {
+            return Err(Status::resource_exhausted("Too many pending logins"));
+        }
+
+        let auth_id = Uuid::new_v4().to_string();
+        let transcript = login_transcript(&self.identity, &request.user, &auth_id);
+        let challenge = match self.registrations.get(&request.user) {
+            Some(registration) if registration.variant == variant => {
+                let challenge = verifier
+                    .challenge(&registration.key, &request.commitment, &transcript)
+                    .map_err(crypto_status)?;
+                self.authentication_states.insert(
+                    auth_id.clone(),
+                    AuthenticationState {
+                        commitment: request.commitment,
+                        challenge: challenge.try_clone().map_err(crypto_status)?,
+                        user: request.user,
+                        issued: Instant::now(),
+                    },
+                );
+                challenge
+            }
+            // Unknown users, and users registered on other parameters, get a
+            // challenge that looks the same but that no answer passes
+            _ => verifier
+                .decoy_challenge(&transcript)
+                .map_err(crypto_status)?,
+        };
+
+        AuthenticationChallengeResponse { auth_id, challenge }.encode_proto(verifier)
+    }
+
+    pub async fn verify_authentication(
+        &self,
+        request: ProtoAuthenticationAnswerRequest,
+    ) -> Result<ProtoAuthenticationAnswerResponse, Status> {
+        P::check_response(&request)?;
+
+        // The challenge is used up whether or not the answer passes, so each
+        // one gets a single guess
+        let verified = self
+            .authentication_states
+            .remove(&request.auth_id)
+            .filter(|(_, state)| state.issued.elapsed() < LOGIN_CHALLENGE_TTL)
+            .is_some_and(|(auth_id, state)| {
+                let Some(registration) = self.registrations.get(&state.user) else {
+                    return false;
+                };
+                let Some(verifier) = self.verifiers.get(&registration.variant) else {
+                    return false;
+                };
+                // The scalars are only decoded with the login's parameters,
+                // so a bad one fails like a wrong one, registered user or not
+                let Ok(response) = verifier.decode_response(&request) else {
+                    return false;
+                };
+
+                verifier
+                    .check(
+                        &registration.key,
+                        &state.commitment,
+                        &state.challenge,
+                        &response,
+                        &login_transcript(&self.identity, &state.user, &auth_id),
+                    )
+                    .unwrap_or(false)
+            });
+
+        if !verified {
+            return Err(Status::unauthenticated("Authentication failed"));
+        }
+
+        let response = AuthenticationAnswerResponse {
+            session_id: Uuid::new_v4().to_string(),
+        };
+        response.to_proto()
+    }
+}
+
+impl<P: DecodeIdentification + SchnorrKey, V: Copy + Eq + Hash> IdentificationServer<P, V> {
+    /// Issues the `action_id` `user` signs their next action with. It is
+    /// issued whether or not they are registered, and accepted once.
+    pub async fn create_action_challenge(
+        &self,
+        variant: V,
+        request: ProtoActionChallengeRequest,
+    ) -> Result<ProtoActionChallengeResponse, Status> {
+        self.verifier(variant)?;
+        let request = request.from_proto()?;
+
+        self.action_states
+            .retain(|_, state| state.issued.elapsed() < ACTION_CHALLENGE_TTL);
+        if self.action_states.len() >= MAX_PENDING_ACTIONS {
+            return Err(Status::resource_exhausted("Too many pending actions"));
+        }
+
+        let action_id = Uuid::new_v4().to_string();
+        self.action_states.insert(
+            action_id.clone(),
+            ActionState {
+                variant,
+                user: request.user,
+                issued: Instant::now(),
+            },
+        );
+
+        ActionChallengeResponse { action_id }.to_proto()
+    }
+
+    /// Checks that `user` approved `action` with a Schnorr signature under
+    /// the `y1` they registered, the same key they log in with. The
+    /// signature must be bound to an unexpired action challenge issued to
+    /// `user`, which it uses up, so it can't be replayed.
+    pub async fn signed_action(
+        &self,
+        variant: V,
+        request: ProtoSignedActionRequest,
+    ) -> Result<ProtoSignedActionResponse, Status> {
+        let verifier = self.verifier(variant)?;
+        let request: SignedActionRequest<_> = request.decode_proto(verifier.group())?;
+
+        self.action_states
+            .remove(&request.action_id)
+            .filter(|(_, state)| {
+                state.issued.elapsed() < ACTION_CHALLENGE_TTL
+                    && state.user == request.user
+                    && state.variant == variant
+            })
+            .ok_or_else(|| Status::unauthenticated("Unknown or expired action challenge"))?;
+
+        let registration = match self.registrations.get(&request.user) {
+            None => return Err(Status::not_found("User not registered")),
+            Some(registration) if registration.variant != variant => {
+                return Err(Status::invalid_argument(
+                    "Group parameters do not match the registration",
+                ))
+            }
+            Some(registration) => registration,
+        };
+
+        let transcript = action_transcript(
+            &self.identity,
+            &request.user,
+            &request.action_id,
+            &request.action,
+        );
+        let verified = SchnorrSignatureVerifier::new(verifier.group().clone())
+            .verify(
+                P::schnorr_key(&registration.key),
+                &transcript,
+                &request.signature,
+            )
+            .map_err(crypto_status)?;
+        if !verified {
+            return Err(Status::unauthenticated("Invalid signature"));
+        }
+
+        let response = SignedActionResponse {
+            action_id: request.action_id,
+        };
+        response.to_proto()
+    }
+}
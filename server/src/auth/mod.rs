@@ -1,5 +1,6 @@
 pub mod conversion;
-pub mod dl;
-pub mod ec;
+pub mod group;
+pub mod identification;
+pub mod opaque;
 pub mod server;
 pub mod types;
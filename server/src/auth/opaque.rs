@@ -0,0 +1,185 @@
+use std::time::{Duration, Instant};
+
+use super::conversion::{opaque_status, FromProto, ToProto};
+use super::types::{
+    OpaqueLoginFinishRequest, OpaqueLoginFinishResponse, OpaqueLoginStartRequest,
+    OpaqueLoginStartResponse, OpaqueLoginState, OpaqueRegistrationFinishRequest,
+    OpaqueRegistrationFinishResponse, OpaqueRegistrationStartRequest,
+    OpaqueRegistrationStartResponse, OpaqueRegistrationState, OpaqueSession,
+};
+use crypto::opaque::messages::RegistrationRecord;
+use crypto::opaque::server::OpaqueServer;
+use crypto::opaque::SessionKey;
+use dashmap::DashMap;
+use tonic::Status;
+use uuid::Uuid;
+
+use proto::zkp_auth::{
+    OpaqueLoginFinishRequest as ProtoOpaqueLoginFinishRequest,
+    OpaqueLoginFinishResponse as ProtoOpaqueLoginFinishResponse,
+    OpaqueLoginStartRequest as ProtoOpaqueLoginStartRequest,
+    OpaqueLoginStartResponse as ProtoOpaqueLoginStartResponse,
+    OpaqueRegistrationFinishRequest as ProtoOpaqueRegistrationFinishRequest,
+    OpaqueRegistrationFinishResponse as ProtoOpaqueRegistrationFinishResponse,
+    OpaqueRegistrationStartRequest as ProtoOpaqueRegistrationStartRequest,
+    OpaqueRegistrationStartResponse as ProtoOpaqueRegistrationStartResponse,
+};
+
+// How long a registration or login can be finished, and a session key
+// claimed, and how many registrations and logins may be pending at once
+const OPAQUE_TTL: Duration = Duration::from_secs(300);
+const MAX_PENDING: usize = 10_000;
+
+/// Auth server for OPAQUE password logins. It stores one registration
+/// record per user, never the password or anything an offline guess could
+/// be checked against, and every login ends with a session key shared with
+/// the client.
+pub struct OpaqueAuthServer {
+    opaque: OpaqueServer,
+    records: DashMap<String, RegistrationRecord>,
+    registration_states: DashMap<String, OpaqueRegistrationState>,
+    login_states: DashMap<String, OpaqueLoginState>,
+    sessions: DashMap<String, OpaqueSession>,
+}
+
+impl OpaqueAuthServer {
+    pub fn new(opaque: OpaqueServer) -> Self {
+        OpaqueAuthServer {
+            opaque,
+            records: DashMap::new(),
+            registration_states: DashMap::new(),
+            login_states: DashMap::new(),
+            sessions: DashMap::new(),
+        }
+    }
+
+    /// Hands over the session key the login that returned `session_id` ended
+    /// with. It can be taken once, within five minutes of the login.
+    pub fn take_session_key(&self, session_id: &str) -> Option<SessionKey> {
+        self.sessions
+            .remove(session_id)
+            .filter(|(_, session)| session.issued.elapsed() < OPAQUE_TTL)
+            .map(|(_, session)| session.session_key)
+    }
+
+    /// Evaluates the OPRF on the user's blinded password. No record is stored
+    /// until the client finishes with the returned `registration_id`.
+    pub async fn registration_start(
+        &self,
+        request: ProtoOpaqueRegistrationStartRequest,
+    ) -> Result<ProtoOpaqueRegistrationStartResponse, Status> {
+        let request: OpaqueRegistrationStartRequest = request.from_proto()?;
+
+        let response = self
+            .opaque
+            .registration_response(&request.request, request.user.as_bytes())
+            .map_err(opaque_status)?;
+
+        self.registration_states
+            .retain(|_, state| state.issued.elapsed() < OPAQUE_TTL);
+        if self.registration_states.len() >= MAX_PENDING {
+            return Err(Status::resource_exhausted("Too many pending registrations"));
+        }
+
+        let registration_id = Uuid::new_v4().to_string();
+        self.registration_states.insert(
+            registration_id.clone(),
+            OpaqueRegistrationState {
+                user: request.user,
+                issued: Instant::now(),
+            },
+        );
+
+        OpaqueRegistrationStartResponse {
+            registration_id,
+            response,
+        }
+        .to_proto()
+    }
+
+    /// Stores the record of a registration this server started for the same
+    /// user, which it finishes.
+    pub async fn registration_finish(
+        &self,
+        request: ProtoOpaqueRegistrationFinishRequest,
+    ) -> Result<ProtoOpaqueRegistrationFinishResponse, Status> {
+        let request: OpaqueRegistrationFinishRequest = request.from_proto()?;
+
+        self.registration_states
+            .remove(&request.registration_id)
+            .filter(|(_, state)| state.issued.elapsed() < OPAQUE_TTL && state.user == request.user)
+            .ok_or_else(|| Status::failed_precondition("Unknown or expired registration"))?;
+        self.records.insert(request.user, request.record);
+
+        OpaqueRegistrationFinishResponse {}.to_proto()
+    }
+
+    /// Answers `ke1`. Unknown users get a response from a fake record, and
+    /// only fail once their `ke3` doesn't verify.
+    pub async fn login_start(
+        &self,
+        request: ProtoOpaqueLoginStartRequest,
+    ) -> Result<ProtoOpaqueLoginStartResponse, Status> {
+        let request: OpaqueLoginStartRequest = request.from_proto()?;
+
+        let record = self.records.get(&request.user);
+        let (ke2, state) = self
+            .opaque
+            .login_start(record.as_deref(), request.user.as_bytes(), &request.ke1)
+            .map_err(opaque_status)?;
+        drop(record);
+
+        self.login_states
+            .retain(|_, login| login.issued.elapsed() < OPAQUE_TTL);
+        if self.login_states.len() >= MAX_PENDING {
+            return Err(Status::resource_exhausted("Too many pending logins"));
+        }
+
+        let auth_id = Uuid::new_v4().to_string();
+        self.login_states.insert(
+            auth_id.clone(),
+            OpaqueLoginState {
+                state,
+                user: request.user,
+                issued: Instant::now(),
+            },
+        );
+
+        OpaqueLoginStartResponse { auth_id, ke2 }.to_proto()
+    }
+
+    /// Checks the client's MAC. Each `ke2` gets one answer, within five
+    /// minutes: the pending login is gone whether or not it verifies.
+    pub async fn login_finish(
+        &self,
+        request: ProtoOpaqueLoginFinishRequest,
+    ) -> Result<ProtoOpaqueLoginFinishResponse, Status> {
+        let request: OpaqueLoginFinishRequest = request.from_proto()?;
+
+        let Some((_, login)) = self
+            .login_states
+            .remove(&request.auth_id)
+            .filter(|(_, login)| login.issued.elapsed() < OPAQUE_TTL)
+        else {
+            return Err(Status::unauthenticated("Authentication failed"));
+        };
+        let session_key = self
+            .opaque
+            .login_finish(login.state, &request.ke3)
+            .map_err(opaque_status)?;
+
+        // Keys nobody claimed in time are dropped
+        self.sessions
+            .retain(|_, session| session.issued.elapsed() < OPAQUE_TTL);
+        let session_id = Uuid::new_v4().to_string();
+        self.sessions.insert(
+            session_id.clone(),
+            OpaqueSession {
+                session_key,
+                issued: Instant::now(),
+            },
+        );
+
+        OpaqueLoginFinishResponse { session_id }.to_proto()
+    }
+}
@@ -1,33 +1,193 @@
-use super::dl::DlAuthServer;
-use super::ec::EcAuthServer;
-use super::types::AuthServer;
+use super::conversion::FromProto;
+use super::group::{
+    DlMembershipServer, EcMembershipServer, RistrettoMembershipServer, MAX_ANONYMITY_SET,
+};
+use super::identification::{
+    DlAuthServer, EcAuthServer, HybridDlAuthServer, HybridEcAuthServer, OkamotoDlAuthServer,
+    OkamotoEcAuthServer, RistrettoAuthServer, SchnorrDlAuthServer, SchnorrEcAuthServer,
+    SlhDsaAuthServer,
+};
+use super::opaque::OpaqueAuthServer;
 use crypto::dl::verifier::DlVerifier;
+use crypto::ec::curves::NamedCurve;
 use crypto::ec::verifier::EcVerifier;
-use crypto::verifier::Verifier;
+use crypto::hybrid::HybridVerifier;
+use crypto::okamoto::OkamotoVerifier;
+use crypto::opaque::server::{OpaqueServer, ServerSetup};
+use crypto::random::{HmacDrbg, OsRandom, RandomSource, DEFAULT_RESEED_INTERVAL};
+use crypto::ristretto::verifier::RistrettoVerifier;
+use crypto::schnorr::SchnorrVerifier;
+use crypto::slh_dsa::SlhDsaVerifier;
+use dashmap::mapref::one::Ref;
+use dashmap::DashMap;
 use proto::zkp_auth::auth_server::Auth;
 use proto::zkp_auth::{
-    AuthAlgo, AuthenticationAnswerRequest as ProtoAuthenticationAnswerRequest,
+    ActionChallengeRequest as ProtoActionChallengeRequest,
+    ActionChallengeResponse as ProtoActionChallengeResponse, AuthAlgo,
+    AuthenticationAnswerRequest as ProtoAuthenticationAnswerRequest,
     AuthenticationAnswerResponse as ProtoAuthenticationAnswerResponse,
     AuthenticationChallengeRequest as ProtoAuthenticationChallengeRequest,
-    AuthenticationChallengeResponse as ProtoAuthenticationChallengeResponse,
+    AuthenticationChallengeResponse as ProtoAuthenticationChallengeResponse, EcCurve,
+    GetSaltRequest as ProtoGetSaltRequest, GetSaltResponse as ProtoGetSaltResponse,
+    MembershipAnswerRequest as ProtoMembershipAnswerRequest,
+    MembershipAnswerResponse as ProtoMembershipAnswerResponse,
+    MembershipChallengeRequest as ProtoMembershipChallengeRequest,
+    MembershipChallengeResponse as ProtoMembershipChallengeResponse,
+    OpaqueLoginFinishRequest as ProtoOpaqueLoginFinishRequest,
+    OpaqueLoginFinishResponse as ProtoOpaqueLoginFinishResponse,
+    OpaqueLoginStartRequest as ProtoOpaqueLoginStartRequest,
+    OpaqueLoginStartResponse as ProtoOpaqueLoginStartResponse,
+    OpaqueRegistrationFinishRequest as ProtoOpaqueRegistrationFinishRequest,
+    OpaqueRegistrationFinishResponse as ProtoOpaqueRegistrationFinishResponse,
+    OpaqueRegistrationStartRequest as ProtoOpaqueRegistrationStartRequest,
+    OpaqueRegistrationStartResponse as ProtoOpaqueRegistrationStartResponse,
     RegisterRequest as ProtoRegisterRequest, RegisterResponse as ProtoRegisterResponse,
+    SignedActionRequest as ProtoSignedActionRequest,
+    SignedActionResponse as ProtoSignedActionResponse,
 };
+use std::collections::HashMap;
 use std::error::Error;
+use std::sync::Arc;
 use tonic::{Request, Response, Status};
-use util::params::{dl_params, ec_params};
+use util::params::{dl_params, ec_params, ristretto_params};
 
 pub struct Server {
     pub ec_server: EcAuthServer,
     pub dl_server: DlAuthServer,
+    pub ristretto_server: RistrettoAuthServer,
+    pub schnorr_ec_server: SchnorrEcAuthServer,
+    pub schnorr_dl_server: SchnorrDlAuthServer,
+    pub okamoto_ec_server: OkamotoEcAuthServer,
+    pub okamoto_dl_server: OkamotoDlAuthServer,
+    pub opaque_server: OpaqueAuthServer,
+    pub slh_dsa_server: SlhDsaAuthServer,
+    pub hybrid_ec_server: HybridEcAuthServer,
+    pub hybrid_dl_server: HybridDlAuthServer,
+    pub ec_membership: EcMembershipServer,
+    pub dl_membership: DlMembershipServer,
+    pub ristretto_membership: RistrettoMembershipServer,
+    /// The anonymity sets membership logins can be made in, by name.
+    membership_groups: DashMap<String, Vec<String>>,
 }
 
+// Separates this DRBG's output from any other instance fed by the same entropy
+const DRBG_PERSONALIZATION: &[u8] = b"zkp_auth/server/drbg/v1";
+
 impl Server {
-    pub fn new() -> Result<Self, Box<dyn Error>> {
+    /// A server whose transcripts are bound to `identity`, the `host:port`
+    /// clients dial it as.
+    pub fn new(identity: &str) -> Result<Self, Box<dyn Error>> {
+        // Challenges come from our own DRBG, reseeded from the OS rather than
+        // on OpenSSL's schedule
+        let rng: Arc<dyn RandomSource> = Arc::new(HmacDrbg::new(
+            Arc::new(OsRandom),
+            DRBG_PERSONALIZATION,
+            DEFAULT_RESEED_INTERVAL,
+        )?);
+
+        let mut ec_verifiers = HashMap::new();
+        let mut schnorr_ec_verifiers = HashMap::new();
+        let mut okamoto_ec_verifiers = HashMap::new();
+        let mut hybrid_ec_verifiers = HashMap::new();
+        for curve in NamedCurve::ALL {
+            let mut params = ec_params(curve)?;
+            params.precompute()?;
+            schnorr_ec_verifiers.insert(
+                curve,
+                SchnorrVerifier::with_rng(params.clone(), rng.clone()),
+            );
+            okamoto_ec_verifiers.insert(
+                curve,
+                OkamotoVerifier::with_rng(params.clone(), rng.clone()),
+            );
+            hybrid_ec_verifiers.insert(
+                curve,
+                HybridVerifier {
+                    classical: EcVerifier::with_rng(params.clone(), rng.clone()),
+                    post_quantum: SlhDsaVerifier::with_rng(rng.clone()),
+                },
+            );
+            ec_verifiers.insert(curve, EcVerifier::with_rng(params, rng.clone()));
+        }
+        let mut dl_params = dl_params()?;
+        dl_params.precompute()?;
+        let mut ristretto_params = ristretto_params()?;
+        ristretto_params.precompute();
+        let opaque_setup = ServerSetup::generate(&*rng)?;
+
+        let ec_server = EcAuthServer::new(identity, ec_verifiers)?;
+        let dl_server = DlAuthServer::single(
+            identity,
+            DlVerifier::with_rng(dl_params.clone(), rng.clone()),
+        )?;
+        let ristretto_server = RistrettoAuthServer::single(
+            identity,
+            RistrettoVerifier::with_rng(ristretto_params, rng.clone()),
+        )?;
+
         Ok(Server {
-            ec_server: EcAuthServer::new(EcVerifier::new(ec_params()?)?),
-            dl_server: DlAuthServer::new(DlVerifier::new(dl_params()?)?),
+            ec_membership: EcMembershipServer::new(&ec_server),
+            dl_membership: DlMembershipServer::new(&dl_server),
+            ristretto_membership: RistrettoMembershipServer::new(&ristretto_server),
+            ec_server,
+            dl_server,
+            ristretto_server,
+            schnorr_ec_server: SchnorrEcAuthServer::new(identity, schnorr_ec_verifiers)?,
+            schnorr_dl_server: SchnorrDlAuthServer::single(
+                identity,
+                SchnorrVerifier::with_rng(dl_params.clone(), rng.clone()),
+            )?,
+            okamoto_ec_server: OkamotoEcAuthServer::new(identity, okamoto_ec_verifiers)?,
+            okamoto_dl_server: OkamotoDlAuthServer::single(
+                identity,
+                OkamotoVerifier::with_rng(dl_params.clone(), rng.clone()),
+            )?,
+            opaque_server: OpaqueAuthServer::new(OpaqueServer::with_rng(opaque_setup, rng.clone())),
+            slh_dsa_server: SlhDsaAuthServer::single(
+                identity,
+                SlhDsaVerifier::with_rng(rng.clone()),
+            )?,
+            hybrid_ec_server: HybridEcAuthServer::new(identity, hybrid_ec_verifiers)?,
+            hybrid_dl_server: HybridDlAuthServer::single(
+                identity,
+                HybridVerifier {
+                    classical: DlVerifier::with_rng(dl_params, rng.clone()),
+                    post_quantum: SlhDsaVerifier::with_rng(rng),
+                },
+            )?,
+            membership_groups: DashMap::new(),
         })
     }
+
+    /// Lets the users in `members` log in anonymously as one of `name`.
+    pub fn set_membership_group(
+        &self,
+        name: &str,
+        members: Vec<String>,
+    ) -> Result<(), Box<dyn Error>> {
+        if members.len() > MAX_ANONYMITY_SET {
+            return Err(
+                format!("Membership groups have at most {MAX_ANONYMITY_SET} members").into(),
+            );
+        }
+
+        self.membership_groups.insert(name.to_string(), members);
+        Ok(())
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn membership_group(&self, name: &str) -> Result<Ref<'_, String, Vec<String>>, Status> {
+        self.membership_groups
+            .get(name)
+            .ok_or_else(|| Status::not_found("Unknown membership group"))
+    }
+}
+
+#[allow(clippy::result_large_err)]
+fn ec_curve(ec_curve: i32) -> Result<NamedCurve, Status> {
+    EcCurve::try_from(ec_curve)
+        .map_err(|_| Status::invalid_argument("Invalid ec_curve"))?
+        .from_proto()
 }
 
 #[tonic::async_trait]
@@ -39,11 +199,105 @@ impl Auth for Server {
         let req = request.into_inner();
         match AuthAlgo::try_from(req.auth_algo) {
             Ok(AuthAlgo::Ec) => {
-                let response = self.ec_server.register(req).await?;
+                let response = self
+                    .ec_server
+                    .register(ec_curve(req.ec_curve)?, req)
+                    .await?;
                 Ok(Response::new(response))
             }
             Ok(AuthAlgo::Dl) => {
-                let response = self.dl_server.register(req).await?;
+                let response = self.dl_server.register((), req).await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::Ristretto) => {
+                let response = self.ristretto_server.register((), req).await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::SchnorrEc) => {
+                let response = self
+                    .schnorr_ec_server
+                    .register(ec_curve(req.ec_curve)?, req)
+                    .await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::SchnorrDl) => {
+                let response = self.schnorr_dl_server.register((), req).await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::OkamotoEc) => {
+                let response = self
+                    .okamoto_ec_server
+                    .register(ec_curve(req.ec_curve)?, req)
+                    .await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::OkamotoDl) => {
+                let response = self.okamoto_dl_server.register((), req).await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::SlhDsa) => {
+                let response = self.slh_dsa_server.register((), req).await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::HybridEc) => {
+                let response = self
+                    .hybrid_ec_server
+                    .register(ec_curve(req.ec_curve)?, req)
+                    .await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::HybridDl) => {
+                let response = self.hybrid_dl_server.register((), req).await?;
+                Ok(Response::new(response))
+            }
+            _ => Err(Status::invalid_argument("Invalid auth_algo")),
+        }
+    }
+
+    async fn get_salt(
+        &self,
+        request: Request<ProtoGetSaltRequest>,
+    ) -> Result<Response<ProtoGetSaltResponse>, Status> {
+        let req = request.into_inner();
+        match AuthAlgo::try_from(req.auth_algo) {
+            Ok(AuthAlgo::Ec) => {
+                let response = self.ec_server.get_salt(req).await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::Dl) => {
+                let response = self.dl_server.get_salt(req).await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::Ristretto) => {
+                let response = self.ristretto_server.get_salt(req).await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::SchnorrEc) => {
+                let response = self.schnorr_ec_server.get_salt(req).await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::SchnorrDl) => {
+                let response = self.schnorr_dl_server.get_salt(req).await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::OkamotoEc) => {
+                let response = self.okamoto_ec_server.get_salt(req).await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::OkamotoDl) => {
+                let response = self.okamoto_dl_server.get_salt(req).await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::SlhDsa) => {
+                let response = self.slh_dsa_server.get_salt(req).await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::HybridEc) => {
+                let response = self.hybrid_ec_server.get_salt(req).await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::HybridDl) => {
+                let response = self.hybrid_dl_server.get_salt(req).await?;
                 Ok(Response::new(response))
             }
             _ => Err(Status::invalid_argument("Invalid auth_algo")),
@@ -57,11 +311,77 @@ impl Auth for Server {
         let req = request.into_inner();
         match AuthAlgo::try_from(req.auth_algo) {
             Ok(AuthAlgo::Ec) => {
-                let response = self.ec_server.create_authentication_challenge(req).await?;
+                let curve = ec_curve(req.ec_curve)?;
+                let response = self
+                    .ec_server
+                    .create_authentication_challenge(curve, req)
+                    .await?;
                 Ok(Response::new(response))
             }
             Ok(AuthAlgo::Dl) => {
-                let response = self.dl_server.create_authentication_challenge(req).await?;
+                let response = self
+                    .dl_server
+                    .create_authentication_challenge((), req)
+                    .await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::Ristretto) => {
+                let response = self
+                    .ristretto_server
+                    .create_authentication_challenge((), req)
+                    .await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::SchnorrEc) => {
+                let curve = ec_curve(req.ec_curve)?;
+                let response = self
+                    .schnorr_ec_server
+                    .create_authentication_challenge(curve, req)
+                    .await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::SchnorrDl) => {
+                let response = self
+                    .schnorr_dl_server
+                    .create_authentication_challenge((), req)
+                    .await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::OkamotoEc) => {
+                let curve = ec_curve(req.ec_curve)?;
+                let response = self
+                    .okamoto_ec_server
+                    .create_authentication_challenge(curve, req)
+                    .await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::OkamotoDl) => {
+                let response = self
+                    .okamoto_dl_server
+                    .create_authentication_challenge((), req)
+                    .await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::SlhDsa) => {
+                let response = self
+                    .slh_dsa_server
+                    .create_authentication_challenge((), req)
+                    .await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::HybridEc) => {
+                let curve = ec_curve(req.ec_curve)?;
+                let response = self
+                    .hybrid_ec_server
+                    .create_authentication_challenge(curve, req)
+                    .await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::HybridDl) => {
+                let response = self
+                    .hybrid_dl_server
+                    .create_authentication_challenge((), req)
+                    .await?;
                 Ok(Response::new(response))
             }
             _ => Err(Status::invalid_argument("Invalid auth_algo")),
@@ -82,6 +402,233 @@ impl Auth for Server {
                 let response = self.dl_server.verify_authentication(req).await?;
                 Ok(Response::new(response))
             }
+            Ok(AuthAlgo::Ristretto) => {
+                let response = self.ristretto_server.verify_authentication(req).await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::SchnorrEc) => {
+                let response = self.schnorr_ec_server.verify_authentication(req).await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::SchnorrDl) => {
+                let response = self.schnorr_dl_server.verify_authentication(req).await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::OkamotoEc) => {
+                let response = self.okamoto_ec_server.verify_authentication(req).await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::OkamotoDl) => {
+                let response = self.okamoto_dl_server.verify_authentication(req).await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::SlhDsa) => {
+                let response = self.slh_dsa_server.verify_authentication(req).await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::HybridEc) => {
+                let response = self.hybrid_ec_server.verify_authentication(req).await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::HybridDl) => {
+                let response = self.hybrid_dl_server.verify_authentication(req).await?;
+                Ok(Response::new(response))
+            }
+            _ => Err(Status::invalid_argument("Invalid auth_algo")),
+        }
+    }
+
+    async fn create_membership_challenge(
+        &self,
+        request: Request<ProtoMembershipChallengeRequest>,
+    ) -> Result<Response<ProtoMembershipChallengeResponse>, Status> {
+        let req = request.into_inner();
+        match AuthAlgo::try_from(req.auth_algo) {
+            Ok(AuthAlgo::Ec) => {
+                let curve = ec_curve(req.ec_curve)?;
+                let group = self.membership_group(&req.group)?;
+                let response = self
+                    .ec_membership
+                    .create_membership_challenge(&self.ec_server, curve, &group, req)
+                    .await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::Dl) => {
+                let group = self.membership_group(&req.group)?;
+                let response = self
+                    .dl_membership
+                    .create_membership_challenge(&self.dl_server, (), &group, req)
+                    .await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::Ristretto) => {
+                let group = self.membership_group(&req.group)?;
+                let response = self
+                    .ristretto_membership
+                    .create_membership_challenge(&self.ristretto_server, (), &group, req)
+                    .await?;
+                Ok(Response::new(response))
+            }
+            _ => Err(Status::invalid_argument("Invalid auth_algo")),
+        }
+    }
+
+    async fn verify_membership(
+        &self,
+        request: Request<ProtoMembershipAnswerRequest>,
+    ) -> Result<Response<ProtoMembershipAnswerResponse>, Status> {
+        let req = request.into_inner();
+        match AuthAlgo::try_from(req.auth_algo) {
+            Ok(AuthAlgo::Ec) => {
+                let response = self
+                    .ec_membership
+                    .verify_membership(&self.ec_server, req)
+                    .await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::Dl) => {
+                let response = self
+                    .dl_membership
+                    .verify_membership(&self.dl_server, req)
+                    .await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::Ristretto) => {
+                let response = self
+                    .ristretto_membership
+                    .verify_membership(&self.ristretto_server, req)
+                    .await?;
+                Ok(Response::new(response))
+            }
+            _ => Err(Status::invalid_argument("Invalid auth_algo")),
+        }
+    }
+
+    async fn create_action_challenge(
+        &self,
+        request: Request<ProtoActionChallengeRequest>,
+    ) -> Result<Response<ProtoActionChallengeResponse>, Status> {
+        let req = request.into_inner();
+        match AuthAlgo::try_from(req.auth_algo) {
+            Ok(AuthAlgo::Ec) => {
+                let curve = ec_curve(req.ec_curve)?;
+                let response = self.ec_server.create_action_challenge(curve, req).await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::Dl) => {
+                let response = self.dl_server.create_action_challenge((), req).await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::Ristretto) => {
+                let response = self
+                    .ristretto_server
+                    .create_action_challenge((), req)
+                    .await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::SchnorrEc) => {
+                let curve = ec_curve(req.ec_curve)?;
+                let response = self
+                    .schnorr_ec_server
+                    .create_action_challenge(curve, req)
+                    .await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::SchnorrDl) => {
+                let response = self
+                    .schnorr_dl_server
+                    .create_action_challenge((), req)
+                    .await?;
+                Ok(Response::new(response))
+            }
+            _ => Err(Status::invalid_argument("Invalid auth_algo")),
+        }
+    }
+
+    async fn signed_action(
+        &self,
+        request: Request<ProtoSignedActionRequest>,
+    ) -> Result<Response<ProtoSignedActionResponse>, Status> {
+        let req = request.into_inner();
+        match AuthAlgo::try_from(req.auth_algo) {
+            Ok(AuthAlgo::Ec) => {
+                let curve = ec_curve(req.ec_curve)?;
+                let response = self.ec_server.signed_action(curve, req).await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::Dl) => {
+                let response = self.dl_server.signed_action((), req).await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::Ristretto) => {
+                let response = self.ristretto_server.signed_action((), req).await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::SchnorrEc) => {
+                let curve = ec_curve(req.ec_curve)?;
+                let response = self.schnorr_ec_server.signed_action(curve, req).await?;
+                Ok(Response::new(response))
+            }
+            Ok(AuthAlgo::SchnorrDl) => {
+                let response = self.schnorr_dl_server.signed_action((), req).await?;
+                Ok(Response::new(response))
+            }
+            _ => Err(Status::invalid_argument("Invalid auth_algo")),
+        }
+    }
+
+    async fn opaque_registration_start(
+        &self,
+        request: Request<ProtoOpaqueRegistrationStartRequest>,
+    ) -> Result<Response<ProtoOpaqueRegistrationStartResponse>, Status> {
+        let req = request.into_inner();
+        match AuthAlgo::try_from(req.auth_algo) {
+            Ok(AuthAlgo::Opaque) => {
+                let response = self.opaque_server.registration_start(req).await?;
+                Ok(Response::new(response))
+            }
+            _ => Err(Status::invalid_argument("Invalid auth_algo")),
+        }
+    }
+
+    async fn opaque_registration_finish(
+        &self,
+        request: Request<ProtoOpaqueRegistrationFinishRequest>,
+    ) -> Result<Response<ProtoOpaqueRegistrationFinishResponse>, Status> {
+        let req = request.into_inner();
+        match AuthAlgo::try_from(req.auth_algo) {
+            Ok(AuthAlgo::Opaque) => {
+                let response = self.opaque_server.registration_finish(req).await?;
+                Ok(Response::new(response))
+            }
+            _ => Err(Status::invalid_argument("Invalid auth_algo")),
+        }
+    }
+
+    async fn opaque_login_start(
+        &self,
+        request: Request<ProtoOpaqueLoginStartRequest>,
+    ) -> Result<Response<ProtoOpaqueLoginStartResponse>, Status> {
+        let req = request.into_inner();
+        match AuthAlgo::try_from(req.auth_algo) {
+            Ok(AuthAlgo::Opaque) => {
+                let response = self.opaque_server.login_start(req).await?;
+                Ok(Response::new(response))
+            }
+            _ => Err(Status::invalid_argument("Invalid auth_algo")),
+        }
+    }
+
+    async fn opaque_login_finish(
+        &self,
+        request: Request<ProtoOpaqueLoginFinishRequest>,
+    ) -> Result<Response<ProtoOpaqueLoginFinishResponse>, Status> {
+        let req = request.into_inner();
+        match AuthAlgo::try_from(req.auth_algo) {
+            Ok(AuthAlgo::Opaque) => {
+                let response = self.opaque_server.login_finish(req).await?;
+                Ok(Response::new(response))
+            }
             _ => Err(Status::invalid_argument("Invalid auth_algo")),
         }
     }
@@ -1,66 +1,156 @@
-use openssl::bn::BigNum;
-use tonic::Status;
-
-use proto::zkp_auth::{
-    AuthenticationAnswerRequest as ProtoAuthenticationAnswerRequest,
-    AuthenticationAnswerResponse as ProtoAuthenticationAnswerResponse,
-    AuthenticationChallengeRequest as ProtoAuthenticationChallengeRequest,
-    AuthenticationChallengeResponse as ProtoAuthenticationChallengeResponse,
-    RegisterRequest as ProtoRegisterRequest, RegisterResponse as ProtoRegisterResponse,
+use std::time::Instant;
+
+use crypto::identification::Challenge;
+use crypto::membership::MembershipProof;
+use crypto::opaque::messages::{
+    Ke1, Ke2, Ke3, RegistrationRecord, RegistrationRequest, RegistrationResponse,
 };
+use crypto::opaque::server::ServerLoginState;
+use crypto::opaque::SessionKey;
+use crypto::schnorr::SchnorrSignature;
 
-pub struct RegisterRequest<T> {
+pub struct RegisterRequest<K> {
     pub user: String,
-    pub y1: T,
-    pub y2: T,
+    pub key: K,
+    pub salt: Vec<u8>,
 }
 
 pub struct RegisterResponse {}
 
-pub struct AuthenticationChallengeRequest<T> {
+pub struct GetSaltRequest {
     pub user: String,
-    pub r1: T,
-    pub r2: T,
 }
 
-pub struct AuthenticationChallengeResponse {
-    pub auth_id: String,
-    pub c: BigNum,
+pub struct GetSaltResponse {
+    pub salt: Vec<u8>,
 }
 
-pub struct AuthenticationAnswerRequest {
+pub struct AuthenticationChallengeRequest<C> {
+    pub user: String,
+    pub commitment: C,
+}
+
+pub struct AuthenticationChallengeResponse<S> {
     pub auth_id: String,
-    pub s: BigNum,
+    pub challenge: Challenge<S>,
 }
 
 pub struct AuthenticationAnswerResponse {
     pub session_id: String,
 }
 
-pub struct Registration<T> {
-    pub y1: T,
-    pub y2: T,
+pub struct MembershipAnswerRequest<S> {
+    pub auth_id: String,
+    pub proof: MembershipProof<S>,
+}
+
+pub struct MembershipAnswerResponse {
+    pub session_id: String,
+    pub anonymity_set_size: u32,
+}
+
+pub struct ActionChallengeRequest {
+    pub user: String,
+}
+
+pub struct ActionChallengeResponse {
+    pub action_id: String,
+}
+
+pub struct SignedActionRequest<S> {
+    pub user: String,
+    pub action: Vec<u8>,
+    pub signature: SchnorrSignature<S>,
+    pub action_id: String,
+}
+
+pub struct SignedActionResponse {
+    pub action_id: String,
+}
+
+pub struct OpaqueRegistrationStartRequest {
+    pub user: String,
+    pub request: RegistrationRequest,
+}
+
+pub struct OpaqueRegistrationStartResponse {
+    pub registration_id: String,
+    pub response: RegistrationResponse,
+}
+
+pub struct OpaqueRegistrationFinishRequest {
+    pub registration_id: String,
+    pub user: String,
+    pub record: RegistrationRecord,
+}
+
+pub struct OpaqueRegistrationFinishResponse {}
+
+pub struct OpaqueLoginStartRequest {
+    pub user: String,
+    pub ke1: Ke1,
+}
+
+pub struct OpaqueLoginStartResponse {
+    pub auth_id: String,
+    pub ke2: Ke2,
+}
+
+pub struct OpaqueLoginFinishRequest {
+    pub auth_id: String,
+    pub ke3: Ke3,
+}
+
+pub struct OpaqueLoginFinishResponse {
+    pub session_id: String,
+}
+
+/// A registration, along with the parameter set its public key lives on.
+pub struct Registration<V, K> {
+    pub variant: V,
+    pub key: K,
+    /// Salt of a password-derived secret, served back to the user; empty otherwise.
+    pub salt: Vec<u8>,
+}
+
+pub struct AuthenticationState<C, S> {
+    pub commitment: C,
+    pub challenge: Challenge<S>,
+    pub user: String,
+    pub issued: Instant,
+}
+
+/// A pending membership login: the users whose keys form the anonymity set.
+pub struct MembershipState<V> {
+    pub variant: V,
+    pub members: Vec<String>,
+    pub issued: Instant,
+}
+
+/// An action challenge waiting for `user` to sign an action with it.
+pub struct ActionState<V> {
+    pub variant: V,
+    pub user: String,
+    pub issued: Instant,
+}
+
+/// A pending OPAQUE login, waiting for the client's `Ke3`.
+pub struct OpaqueLoginState {
+    pub state: ServerLoginState,
+    pub user: String,
+    pub issued: Instant,
 }
 
-pub struct AuthenticationState<T> {
-    pub r1: T,
-    pub r2: T,
-    pub c: BigNum,
+/// An OPAQUE registration the server evaluated the OPRF for, waiting for
+/// `user`'s record.
+pub struct OpaqueRegistrationState {
     pub user: String,
+    pub issued: Instant,
 }
 
-#[tonic::async_trait]
-pub trait AuthServer: Send + Sync + 'static {
-    async fn register(
-        &self,
-        request: ProtoRegisterRequest,
-    ) -> Result<ProtoRegisterResponse, Status>;
-    async fn create_authentication_challenge(
-        &self,
-        request: ProtoAuthenticationChallengeRequest,
-    ) -> Result<ProtoAuthenticationChallengeResponse, Status>;
-    async fn verify_authentication(
-        &self,
-        request: ProtoAuthenticationAnswerRequest,
-    ) -> Result<ProtoAuthenticationAnswerResponse, Status>;
+/// The key a finished OPAQUE login left the server with, until the
+/// application claims it.
+pub struct OpaqueSession {
+    pub session_key: SessionKey,
+    pub issued: Instant,
 }
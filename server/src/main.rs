@@ -16,7 +16,20 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let port = &args[2];
 
     let addr = format!("{}:{}", host, port).parse()?;
-    let server = ZkpServer::new()?;
+    // What clients dial the server as; login transcripts are bound to it
+    let identity = env::var("ZKP_SERVER_IDENTITY").unwrap_or_else(|_| format!("{}:{}", host, port));
+    let server = ZkpServer::new(&identity)?;
+
+    // Anonymity sets for membership logins, as `name=alice,bob;other=carol,dave`
+    if let Ok(groups) = env::var("ZKP_MEMBERSHIP_GROUPS") {
+        for group in groups.split(';').filter(|group| !group.is_empty()) {
+            let (name, members) = group
+                .split_once('=')
+                .ok_or("ZKP_MEMBERSHIP_GROUPS entries look like name=alice,bob")?;
+            let members = members.split(',').map(str::to_string).collect();
+            server.set_membership_group(name, members)?;
+        }
+    }
 
     println!("ZKP Auth Server listening on {}", addr);
 
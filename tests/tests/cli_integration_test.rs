@@ -6,14 +6,14 @@ use std::time::Duration;
 fn start_server(host: &str, port: u16) -> Child {
     let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
     Command::new(cargo)
-        .args(&[
+        .args([
             "run",
             "--package",
             "server",
             "--bin",
             "server",
             "--",
-            &host,
+            host,
             &port.to_string(),
         ])
         .spawn()
@@ -28,7 +28,7 @@ fn run_client(
 ) -> std::io::Result<()> {
     let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
     let output = Command::new(cargo)
-        .args(&[
+        .args([
             "run",
             "--package",
             "client",
@@ -37,19 +37,16 @@ fn run_client(
             "--",
             username,
             algo,
-            &server_host.to_string(),
+            server_host,
             &server_port.to_string(),
         ])
         .output()?;
 
     if !output.status.success() {
-        return Err(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            format!(
-                "Client command failed: {}",
-                String::from_utf8_lossy(&output.stderr)
-            ),
-        ));
+        return Err(std::io::Error::other(format!(
+            "Client command failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        )));
     }
 
     println!("Client output: {}", String::from_utf8_lossy(&output.stdout));
@@ -73,6 +70,7 @@ fn test_ec_authentication_flow() {
     );
 
     server.kill().expect("Failed to kill server process");
+    server.wait().expect("Failed to reap server process");
 }
 
 #[test]
@@ -92,4 +90,5 @@ fn test_dl_authentication_flow() {
     );
 
     server.kill().expect("Failed to kill server process");
+    server.wait().expect("Failed to reap server process");
 }
@@ -1,19 +1,44 @@
 use client::auth::client::Client;
+use crypto::ec::curves::NamedCurve;
+use crypto::group::Group;
+use crypto::opaque::client::OpaqueClient;
+use crypto::opaque::messages::RegistrationResponse;
+use crypto::opaque::IdentityKsf;
+use crypto::prover::Prover;
+use crypto::ristretto::prover::RistrettoProver;
+use crypto::schnorr::SchnorrSigner;
+use crypto::slh_dsa;
+use proto::zkp_auth::auth_client::AuthClient;
 use proto::zkp_auth::auth_server::AuthServer;
-use proto::zkp_auth::AuthAlgo;
+use proto::zkp_auth::{
+    ActionChallengeRequest, AuthAlgo, AuthenticationAnswerRequest, AuthenticationChallengeRequest,
+    GetSaltRequest, MembershipAnswerRequest, MembershipChallengeRequest, OpaqueLoginFinishRequest,
+    OpaqueRegistrationFinishRequest, OpaqueRegistrationStartRequest, RegisterRequest,
+    SignedActionRequest,
+};
 use server::auth::server::Server as ZkpServer;
 use std::net::SocketAddr;
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::time::sleep;
 use tonic::transport::Server;
+use tonic::{Code, Status};
+use util::params::ristretto_params;
+use util::transcript::action_transcript;
 
-async fn start_server(port: u16) {
+async fn start_server(port: u16) -> Arc<ZkpServer> {
+    start_server_as(port, &format!("[::1]:{}", port)).await
+}
+
+// A server whose transcripts are bound to `identity` rather than its address
+async fn start_server_as(port: u16, identity: &str) -> Arc<ZkpServer> {
     let addr = format!("[::1]:{}", port).parse::<SocketAddr>().unwrap();
-    let server = ZkpServer::new().unwrap();
+    let server = Arc::new(ZkpServer::new(identity).unwrap());
 
+    let service = AuthServer::from_arc(server.clone());
     tokio::spawn(async move {
         Server::builder()
-            .add_service(AuthServer::new(server))
+            .add_service(service)
             .serve(addr)
             .await
             .unwrap();
@@ -21,6 +46,7 @@ async fn start_server(port: u16) {
 
     // Give the server a moment to start
     sleep(Duration::from_millis(100)).await;
+    server
 }
 
 async fn run_client_flow(
@@ -28,8 +54,15 @@ async fn run_client_flow(
     username: &str,
     algo: AuthAlgo,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    let mut client = Client::new(format!("http://[::1]:{}", port)).await?;
+    let client = Client::new(format!("http://[::1]:{}", port)).await?;
+    run_flow(client, username, algo).await
+}
 
+async fn run_flow(
+    mut client: Client,
+    username: &str,
+    algo: AuthAlgo,
+) -> Result<(), Box<dyn std::error::Error>> {
     // Register
     client.register(username, algo).await?;
 
@@ -72,3 +105,777 @@ async fn test_dl_authentication_flow() {
         result.err()
     );
 }
+
+#[tokio::test]
+async fn test_ristretto_authentication_flow() {
+    let port = 50056;
+    start_server(port).await;
+
+    let result = run_client_flow(port, "test_user_ristretto", AuthAlgo::Ristretto).await;
+    assert!(
+        result.is_ok(),
+        "Ristretto authentication flow failed: {:?}",
+        result.err()
+    );
+}
+
+#[tokio::test]
+async fn test_ec_authentication_flow_on_nist_curves() {
+    let port = 50054;
+    start_server(port).await;
+
+    for curve in [NamedCurve::P256, NamedCurve::P384] {
+        let client = Client::with_curve(format!("http://[::1]:{}", port), curve)
+            .await
+            .unwrap();
+        let username = format!("test_user_{}", curve.name());
+
+        let result = run_flow(client, &username, AuthAlgo::Ec).await;
+        assert!(
+            result.is_ok(),
+            "EC authentication flow on {} failed: {:?}",
+            curve.name(),
+            result.err()
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_degenerate_elements_are_rejected() {
+    let port = 50055;
+    start_server(port).await;
+
+    let mut client = AuthClient::connect(format!("http://[::1]:{}", port))
+        .await
+        .unwrap();
+
+    // 0 and 1 as DL public keys, the point at infinity as an EC public key
+    let cases = [
+        (AuthAlgo::Dl, vec![0], "y1 is not in the range [1, p)"),
+        (AuthAlgo::Dl, vec![1], "y1 is the identity element"),
+        (AuthAlgo::Ec, vec![0], "y1 is the identity element"),
+        (
+            AuthAlgo::Ristretto,
+            vec![0; 32],
+            "y1 is the identity element",
+        ),
+    ];
+    for (algo, y1, message) in cases {
+        let request = RegisterRequest {
+            user: "test_user_degenerate".to_string(),
+            y1,
+            y2: vec![2],
+            auth_algo: algo as i32,
+            ..Default::default()
+        };
+
+        let status = client.register(request).await.unwrap_err();
+        assert_eq!(status.code(), Code::InvalidArgument);
+        assert_eq!(status.message(), message);
+    }
+}
+
+#[tokio::test]
+async fn test_password_authentication_from_another_device() {
+    let port = 50057;
+    start_server(port).await;
+    let addr = format!("http://[::1]:{}", port);
+    let username = "test_user_password";
+
+    for algo in [AuthAlgo::Ec, AuthAlgo::Ristretto] {
+        let mut laptop = Client::new(addr.clone()).await.unwrap();
+        laptop
+            .register_with_password(username, "correct horse", algo)
+            .await
+            .unwrap();
+
+        // A fresh client only has the password; x comes back from the server's salt
+        let mut phone = Client::new(addr.clone()).await.unwrap();
+        phone
+            .restore_with_password(username, "correct horse", algo)
+            .await
+            .unwrap();
+        let auth_id = phone
+            .create_authentication_challenge(username, algo)
+            .await
+            .unwrap();
+        let result = phone.verify_authentication(&auth_id, algo).await;
+        assert!(
+            result.is_ok(),
+            "password authentication failed: {:?}",
+            result.err()
+        );
+
+        let mut attacker = Client::new(addr.clone()).await.unwrap();
+        attacker
+            .restore_with_password(username, "battery staple", algo)
+            .await
+            .unwrap();
+        // The server binds c to the registered keys, so the attacker's client
+        // already fails to re-derive it and never answers
+        let err = attacker
+            .create_authentication_challenge(username, algo)
+            .await
+            .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("Challenge does not match the login transcript"));
+    }
+}
+
+#[tokio::test]
+async fn test_salt_does_not_reveal_registered_users() {
+    let port = 50058;
+    start_server(port).await;
+
+    let client = AuthClient::connect(format!("http://[::1]:{}", port))
+        .await
+        .unwrap();
+    let get_salt = |user: &str| {
+        let request = GetSaltRequest {
+            user: user.to_string(),
+            auth_algo: AuthAlgo::Dl as i32,
+        };
+        let mut client = client.clone();
+        async move { client.get_salt(request).await.unwrap().into_inner().salt }
+    };
+
+    let salt = get_salt("nobody").await;
+    assert_eq!(salt.len(), 16);
+    assert_eq!(salt, get_salt("nobody").await, "decoy salt is not stable");
+    assert_ne!(salt, get_salt("somebody").await);
+}
+
+#[tokio::test]
+async fn test_challenge_does_not_reveal_registered_users() {
+    let port = 50065;
+    start_server(port).await;
+    let mut client = Client::new(format!("http://[::1]:{}", port)).await.unwrap();
+    client.register("alice", AuthAlgo::Ristretto).await.unwrap();
+
+    let params = ristretto_params().unwrap();
+    let prover = RistrettoProver::new(params.clone()).unwrap();
+    let k = prover.random().unwrap();
+    let commit = prover.commit(&k).unwrap();
+    let mut raw = AuthClient::connect(format!("http://[::1]:{}", port))
+        .await
+        .unwrap();
+
+    // Both get a challenge of the same shape, and both answers are rejected alike
+    for user in ["alice", "nobody"] {
+        let challenge = raw
+            .create_authentication_challenge(AuthenticationChallengeRequest {
+                user: user.to_string(),
+                r1: params.encode_element(&commit.r1).unwrap(),
+                r2: params.encode_element(&commit.r2).unwrap(),
+                auth_algo: AuthAlgo::Ristretto as i32,
+                ..Default::default()
+            })
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(!challenge.c.is_empty());
+        assert_eq!(challenge.nonce.len(), 32);
+
+        let status = raw
+            .verify_authentication(AuthenticationAnswerRequest {
+                auth_id: challenge.auth_id,
+                s: vec![1],
+                auth_algo: AuthAlgo::Ristretto as i32,
+                ..Default::default()
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::Unauthenticated);
+        assert_eq!(status.message(), "Authentication failed");
+    }
+}
+
+#[tokio::test]
+async fn test_login_is_bound_to_the_dialed_server() {
+    let port = 50066;
+    // As if a relay at [::1] were forwarding logins meant for another server
+    start_server_as(port, "auth.example.com:50051").await;
+    let mut client = Client::new(format!("http://[::1]:{}", port)).await.unwrap();
+
+    for algo in [AuthAlgo::Ristretto, AuthAlgo::SchnorrEc] {
+        client.register("alice", algo).await.unwrap();
+        let err = client
+            .create_authentication_challenge("alice", algo)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Challenge does not match the login transcript"
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_anonymous_membership_login() {
+    let port = 50059;
+    let server = start_server(port).await;
+    let team = ["alice", "bob", "carol", "dave"].map(String::from).to_vec();
+    server.set_membership_group("team", team).unwrap();
+    assert!(server
+        .set_membership_group("everyone", vec![String::new(); 257])
+        .is_err());
+    let mut client = Client::new(format!("http://[::1]:{}", port)).await.unwrap();
+
+    for algo in [AuthAlgo::Ec, AuthAlgo::Ristretto] {
+        client.register("alice", algo).await.unwrap();
+
+        // A set of one would give the user away
+        let status = client
+            .prove_membership("alice", "team", algo)
+            .await
+            .unwrap_err()
+            .downcast::<Status>()
+            .unwrap();
+        assert_eq!(status.code(), Code::FailedPrecondition);
+
+        for user in ["bob", "carol"] {
+            client.register(user, algo).await.unwrap();
+        }
+        for user in ["alice", "bob", "carol"] {
+            let result = client.prove_membership(user, "team", algo).await;
+            assert!(
+                result.is_ok(),
+                "membership login failed: {:?}",
+                result.err()
+            );
+        }
+
+        // Password-derived keys are never handed out, and only configured
+        // groups can be asked for
+        client
+            .register_with_password("dave", "correct horse", algo)
+            .await
+            .unwrap();
+        let error = client
+            .prove_membership("dave", "team", algo)
+            .await
+            .unwrap_err();
+        assert_eq!(error.to_string(), "User is not in the anonymity set");
+        let status = client
+            .prove_membership("alice", "everyone", algo)
+            .await
+            .unwrap_err()
+            .downcast::<Status>()
+            .unwrap();
+        assert_eq!(status.code(), Code::NotFound);
+    }
+
+    // Made-up answers are rejected
+    let params = ristretto_params().unwrap();
+    let one = params
+        .encode_scalar(&params.reduce_scalar(&[1]).unwrap())
+        .unwrap();
+    let mut raw = AuthClient::connect(format!("http://[::1]:{}", port))
+        .await
+        .unwrap();
+    let challenge = raw
+        .create_membership_challenge(MembershipChallengeRequest {
+            auth_algo: AuthAlgo::Ristretto as i32,
+            group: "team".to_string(),
+            ..Default::default()
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    let members = challenge.members.len();
+    let status = raw
+        .verify_membership(MembershipAnswerRequest {
+            auth_id: challenge.auth_id,
+            c: vec![one.clone(); members],
+            s: vec![one; members],
+            auth_algo: AuthAlgo::Ristretto as i32,
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+}
+
+#[tokio::test]
+async fn test_signed_action() {
+    let port = 50060;
+    start_server(port).await;
+    let mut client = Client::new(format!("http://[::1]:{}", port)).await.unwrap();
+
+    for algo in [AuthAlgo::Ec, AuthAlgo::Dl, AuthAlgo::Ristretto] {
+        client.register("alice", algo).await.unwrap();
+        let result = client.sign_action("alice", b"payout 42 to bob", algo).await;
+        assert!(result.is_ok(), "signed action failed: {:?}", result.err());
+    }
+
+    // Sign with a login key we hold outside the client
+    let prover = RistrettoProver::new(ristretto_params().unwrap()).unwrap();
+    let x = prover.random().unwrap();
+    let keys = prover.public_keys(&x).unwrap();
+    let mut raw = AuthClient::connect(format!("http://[::1]:{}", port))
+        .await
+        .unwrap();
+    raw.register(RegisterRequest {
+        user: "mallory".to_string(),
+        y1: prover.params.encode_element(&keys.y1).unwrap(),
+        y2: prover.params.encode_element(&keys.y2).unwrap(),
+        auth_algo: AuthAlgo::Ristretto as i32,
+        ..Default::default()
+    })
+    .await
+    .unwrap();
+    let server = format!("[::1]:{}", port);
+    let signer = SchnorrSigner::new(prover.params.clone());
+    let issuer = raw.clone();
+    let challenge = |user: &str| {
+        let request = ActionChallengeRequest {
+            user: user.to_string(),
+            auth_algo: AuthAlgo::Ristretto as i32,
+            ..Default::default()
+        };
+        let mut issuer = issuer.clone();
+        async move {
+            issuer
+                .create_action_challenge(request)
+                .await
+                .unwrap()
+                .into_inner()
+                .action_id
+        }
+    };
+    let request = |user: &str, action_id: &str, signed: &[u8], action: &[u8]| {
+        let transcript = action_transcript(&server, "mallory", action_id, signed);
+        let signature = signer.sign(&x, &transcript).unwrap();
+        SignedActionRequest {
+            user: user.to_string(),
+            action: action.to_vec(),
+            c: prover.params.encode_scalar(&signature.c).unwrap(),
+            s: prover.params.encode_scalar(&signature.s).unwrap(),
+            auth_algo: AuthAlgo::Ristretto as i32,
+            action_id: action_id.to_string(),
+            ..Default::default()
+        }
+    };
+
+    let action_id = challenge("mallory").await;
+    let signed = request(
+        "mallory",
+        &action_id,
+        b"payout 1 to mallory",
+        b"payout 1 to mallory",
+    );
+    raw.signed_action(signed.clone()).await.unwrap();
+
+    // The action challenge is used up, so the same request is not accepted twice
+    let status = raw.signed_action(signed).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+
+    // Neither another action, another user's key, another user's challenge
+    // nor a challenge the server never issued verifies the signature
+    let cases = [
+        (
+            "mallory",
+            "mallory",
+            &b"payout 100 to mallory"[..],
+            Code::Unauthenticated,
+        ),
+        (
+            "alice",
+            "alice",
+            &b"payout 1 to mallory"[..],
+            Code::Unauthenticated,
+        ),
+        (
+            "alice",
+            "mallory",
+            &b"payout 1 to mallory"[..],
+            Code::Unauthenticated,
+        ),
+        (
+            "nobody",
+            "nobody",
+            &b"payout 1 to mallory"[..],
+            Code::NotFound,
+        ),
+    ];
+    for (challenged, user, action, code) in cases {
+        let action_id = challenge(challenged).await;
+        let status = raw
+            .signed_action(request(user, &action_id, b"payout 1 to mallory", action))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), code);
+    }
+    let forged = request(
+        "mallory",
+        "not-issued",
+        b"payout 1 to mallory",
+        b"payout 1 to mallory",
+    );
+    let status = raw.signed_action(forged).await.unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+}
+
+#[tokio::test]
+async fn test_schnorr_authentication_flow() {
+    let port = 50061;
+    start_server(port).await;
+    let addr = format!("http://[::1]:{}", port);
+
+    for algo in [AuthAlgo::SchnorrEc, AuthAlgo::SchnorrDl] {
+        let result = run_client_flow(port, "test_user_schnorr", algo).await;
+        assert!(result.is_ok(), "Schnorr flow failed: {:?}", result.err());
+    }
+    let client = Client::with_curve(addr.clone(), NamedCurve::P256)
+        .await
+        .unwrap();
+    let result = run_flow(client, "test_user_schnorr_p256", AuthAlgo::SchnorrEc).await;
+    assert!(
+        result.is_ok(),
+        "Schnorr P-256 flow failed: {:?}",
+        result.err()
+    );
+
+    // Passwords and signed actions work as with Chaum-Pedersen, membership doesn't
+    let mut laptop = Client::new(addr.clone()).await.unwrap();
+    laptop
+        .register_with_password("carol", "correct horse", AuthAlgo::SchnorrDl)
+        .await
+        .unwrap();
+    let mut phone = Client::new(addr.clone()).await.unwrap();
+    phone
+        .restore_with_password("carol", "correct horse", AuthAlgo::SchnorrDl)
+        .await
+        .unwrap();
+    let auth_id = phone
+        .create_authentication_challenge("carol", AuthAlgo::SchnorrDl)
+        .await
+        .unwrap();
+    phone
+        .verify_authentication(&auth_id, AuthAlgo::SchnorrDl)
+        .await
+        .unwrap();
+    phone
+        .sign_action("carol", b"payout 42 to bob", AuthAlgo::SchnorrDl)
+        .await
+        .unwrap();
+    assert!(phone
+        .prove_membership("carol", "team", AuthAlgo::SchnorrDl)
+        .await
+        .is_err());
+
+    // Schnorr registrations carry no y2, and there are no membership logins
+    let mut raw = AuthClient::connect(addr).await.unwrap();
+    let request = RegisterRequest {
+        user: "carol".to_string(),
+        y1: vec![2],
+        y2: vec![2],
+        auth_algo: AuthAlgo::SchnorrDl as i32,
+        ..Default::default()
+    };
+    let status = raw.register(request).await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+    assert_eq!(status.message(), "Unexpected y2");
+    let status = raw
+        .create_membership_challenge(MembershipChallengeRequest {
+            auth_algo: AuthAlgo::SchnorrEc as i32,
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+}
+
+#[tokio::test]
+async fn test_okamoto_authentication_flow() {
+    let port = 50062;
+    start_server(port).await;
+    let addr = format!("http://[::1]:{}", port);
+
+    for algo in [AuthAlgo::OkamotoEc, AuthAlgo::OkamotoDl] {
+        let result = run_client_flow(port, "test_user_okamoto", algo).await;
+        assert!(result.is_ok(), "Okamoto flow failed: {:?}", result.err());
+    }
+    let client = Client::with_curve(addr.clone(), NamedCurve::P384)
+        .await
+        .unwrap();
+    let result = run_flow(client, "test_user_okamoto_p384", AuthAlgo::OkamotoEc).await;
+    assert!(
+        result.is_ok(),
+        "Okamoto P-384 flow failed: {:?}",
+        result.err()
+    );
+
+    // Password-derived secrets are split into (a, b) the same way on every device
+    let mut laptop = Client::new(addr.clone()).await.unwrap();
+    laptop
+        .register_with_password("dave", "correct horse", AuthAlgo::OkamotoEc)
+        .await
+        .unwrap();
+    let mut phone = Client::new(addr.clone()).await.unwrap();
+    phone
+        .restore_with_password("dave", "correct horse", AuthAlgo::OkamotoEc)
+        .await
+        .unwrap();
+    let auth_id = phone
+        .create_authentication_challenge("dave", AuthAlgo::OkamotoEc)
+        .await
+        .unwrap();
+    phone
+        .verify_authentication(&auth_id, AuthAlgo::OkamotoEc)
+        .await
+        .unwrap();
+
+    // Okamoto keys are not Schnorr keys
+    assert!(phone
+        .sign_action("dave", b"payout 42 to bob", AuthAlgo::OkamotoEc)
+        .await
+        .is_err());
+    let mut raw = AuthClient::connect(addr).await.unwrap();
+    let status = raw
+        .signed_action(SignedActionRequest {
+            user: "dave".to_string(),
+            auth_algo: AuthAlgo::OkamotoEc as i32,
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+
+    // Only Okamoto answers carry s2
+    let status = raw
+        .verify_authentication(AuthenticationAnswerRequest {
+            auth_id: "unknown".to_string(),
+            s: vec![1],
+            s2: vec![1],
+            auth_algo: AuthAlgo::Ec as i32,
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+    assert_eq!(status.message(), "Unexpected s2");
+}
+
+#[tokio::test]
+async fn test_opaque_login() {
+    let port = 50063;
+    let server = start_server(port).await;
+    let addr = format!("http://[::1]:{}", port);
+
+    let mut laptop = Client::new(addr.clone()).await.unwrap();
+    laptop
+        .register_with_password("erin", "correct horse", AuthAlgo::Opaque)
+        .await
+        .unwrap();
+    let laptop_session = laptop
+        .login_with_password("erin", "correct horse", AuthAlgo::Opaque)
+        .await
+        .unwrap();
+
+    // The login ends with a key only the client and the server hold
+    let session = laptop.opaque_session(&laptop_session).unwrap();
+    let server_key = server
+        .opaque_server
+        .take_session_key(&laptop_session)
+        .unwrap();
+    assert_eq!(*session.session_key, *server_key);
+
+    // The server hands its copy over once
+    assert!(server
+        .opaque_server
+        .take_session_key(&laptop_session)
+        .is_none());
+
+    // Another device needs nothing but the password, and recovers the same export key
+    let mut phone = Client::new(addr.clone()).await.unwrap();
+    let phone_session = phone
+        .login_with_password("erin", "correct horse", AuthAlgo::Opaque)
+        .await
+        .unwrap();
+    let phone_keys = phone.opaque_session(&phone_session).unwrap();
+    assert_eq!(*phone_keys.export_key, *session.export_key);
+    assert_ne!(*phone_keys.session_key, *session.session_key);
+
+    // Wrong passwords and unknown users both fail, and get no session
+    assert!(phone
+        .login_with_password("erin", "wrong horse", AuthAlgo::Opaque)
+        .await
+        .is_err());
+    assert!(phone
+        .login_with_password("mallory", "correct horse", AuthAlgo::Opaque)
+        .await
+        .is_err());
+
+    // OPAQUE users have no key for the proof-based RPCs
+    assert!(phone.register("frank", AuthAlgo::Opaque).await.is_err());
+    let mut raw = AuthClient::connect(addr).await.unwrap();
+    let status = raw
+        .register(RegisterRequest {
+            user: "frank".to_string(),
+            auth_algo: AuthAlgo::Opaque as i32,
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+
+    // ...and the OPAQUE RPCs only speak OPAQUE
+    let status = raw
+        .opaque_registration_start(OpaqueRegistrationStartRequest {
+            user: "frank".to_string(),
+            registration_request: vec![0u8; 32],
+            auth_algo: AuthAlgo::Ec as i32,
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+
+    // A record is only stored for the user whose registration the server started
+    let opaque = OpaqueClient::new(IdentityKsf);
+    let (request, state) = opaque.registration_start(b"mallory's password").unwrap();
+    let started = raw
+        .opaque_registration_start(OpaqueRegistrationStartRequest {
+            user: "mallory".to_string(),
+            registration_request: request.serialize(),
+            auth_algo: AuthAlgo::Opaque as i32,
+        })
+        .await
+        .unwrap()
+        .into_inner();
+    let response = RegistrationResponse::deserialize(&started.registration_response).unwrap();
+    let record = opaque
+        .registration_finish(state, &response)
+        .unwrap()
+        .record
+        .serialize();
+    for registration_id in [started.registration_id, "unknown".to_string()] {
+        let status = raw
+            .opaque_registration_finish(OpaqueRegistrationFinishRequest {
+                user: "erin".to_string(),
+                registration_record: record.clone(),
+                auth_algo: AuthAlgo::Opaque as i32,
+                registration_id,
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), Code::FailedPrecondition);
+    }
+    laptop
+        .login_with_password("erin", "correct horse", AuthAlgo::Opaque)
+        .await
+        .unwrap();
+
+    // A ke3 for a login that was never started is refused
+    let status = raw
+        .opaque_login_finish(OpaqueLoginFinishRequest {
+            auth_id: "unknown".to_string(),
+            ke3: vec![0u8; 64],
+            auth_algo: AuthAlgo::Opaque as i32,
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+}
+
+#[tokio::test]
+async fn test_post_quantum_authentication_flow() {
+    let port = 50064;
+    start_server(port).await;
+    let addr = format!("http://[::1]:{}", port);
+
+    for algo in [AuthAlgo::SlhDsa, AuthAlgo::HybridEc, AuthAlgo::HybridDl] {
+        let result = run_client_flow(port, "test_user_pq", algo).await;
+        assert!(result.is_ok(), "{:?} flow failed: {:?}", algo, result.err());
+    }
+
+    // Both keys of a hybrid account come from the password
+    let mut laptop = Client::new(addr.clone()).await.unwrap();
+    let mut phone = Client::new(addr.clone()).await.unwrap();
+    for algo in [AuthAlgo::SlhDsa, AuthAlgo::HybridEc] {
+        laptop
+            .register_with_password("grace", "correct horse", algo)
+            .await
+            .unwrap();
+        phone
+            .login_with_password("grace", "correct horse", algo)
+            .await
+            .unwrap();
+        assert!(phone
+            .login_with_password("grace", "wrong horse", algo)
+            .await
+            .is_err());
+    }
+    assert!(phone
+        .prove_membership("grace", "team", AuthAlgo::HybridEc)
+        .await
+        .is_err());
+
+    // A hybrid answer needs the SLH-DSA signature as well as s; a wrong
+    // answer uses the challenge up, so the real one comes too late
+    let auth_id = laptop
+        .create_authentication_challenge("grace", AuthAlgo::HybridEc)
+        .await
+        .unwrap();
+    let mut raw = AuthClient::connect(addr).await.unwrap();
+    let answer = AuthenticationAnswerRequest {
+        auth_id: auth_id.clone(),
+        s: vec![1],
+        auth_algo: AuthAlgo::HybridEc as i32,
+        ..Default::default()
+    };
+    let status = raw.verify_authentication(answer.clone()).await.unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+    assert_eq!(status.message(), "Invalid signature");
+    let status = raw
+        .verify_authentication(AuthenticationAnswerRequest {
+            signature: vec![0u8; slh_dsa::SIGNATURE_LEN],
+            ..answer
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::Unauthenticated);
+    let status = laptop
+        .verify_authentication(&auth_id, AuthAlgo::HybridEc)
+        .await
+        .unwrap_err()
+        .downcast::<Status>()
+        .unwrap();
+    assert_eq!(status.code(), Code::Unauthenticated);
+
+    // SLH-DSA registers only the post-quantum key, which the other algos refuse
+    let status = raw
+        .register(RegisterRequest {
+            user: "heidi".to_string(),
+            y1: vec![2],
+            auth_algo: AuthAlgo::SlhDsa as i32,
+            pq_public_key: vec![0u8; slh_dsa::PUBLIC_KEY_LEN],
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+    assert_eq!(status.message(), "Unexpected y1");
+    let status = raw
+        .register(RegisterRequest {
+            user: "heidi".to_string(),
+            auth_algo: AuthAlgo::SlhDsa as i32,
+            pq_public_key: vec![0u8; 31],
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+    assert_eq!(status.message(), "Invalid pq_public_key");
+    let status = raw
+        .register(RegisterRequest {
+            user: "heidi".to_string(),
+            auth_algo: AuthAlgo::Ristretto as i32,
+            pq_public_key: vec![0u8; slh_dsa::PUBLIC_KEY_LEN],
+            ..Default::default()
+        })
+        .await
+        .unwrap_err();
+    assert_eq!(status.code(), Code::InvalidArgument);
+    assert_eq!(status.message(), "Unexpected pq_public_key");
+}
@@ -1,2 +1,2 @@
-pub mod codec;
 pub mod params;
+pub mod transcript;
@@ -1,64 +1,122 @@
+#[cfg(feature = "openssl")]
 use openssl::bn::BigNumContext;
+#[cfg(feature = "openssl")]
 use openssl::ec::EcGroup;
-use openssl::error::ErrorStack;
-use openssl::nid::Nid;
-use openssl::{bn::BigNum, ec::EcPoint};
+#[cfg(feature = "openssl")]
+use openssl::ec::EcPoint;
 
+#[cfg(feature = "openssl")]
+use crypto::dl::groups::NamedGroup;
+#[cfg(feature = "openssl")]
 use crypto::dl::params::DlParams;
+#[cfg(feature = "openssl")]
+use crypto::ec::curves::NamedCurve;
+#[cfg(feature = "openssl")]
 use crypto::ec::params::EcParams;
+use crypto::error::ParamsError;
+use crypto::ristretto::params::RistrettoParams;
 
-// Constants for DL parameters
-// q = 95323790354645866989878273881751216942630210959135343057135663681821136636963
-pub const DL_Q: &[u8] = &[
-    210, 191, 90, 118, 231, 36, 240, 249, 221, 135, 95, 42, 202, 116, 56, 43, 79, 165, 55, 51, 241,
-    56, 40, 60, 238, 76, 134, 128, 63, 23, 204, 35,
-];
-// p = 190647580709291733979756547763502433885260421918270686114271327363642273273927
-pub const DL_P: &[u8] = &[
-    1, 165, 126, 180, 237, 206, 73, 225, 243, 187, 14, 190, 85, 148, 232, 112, 86, 159, 74, 110,
-    103, 226, 112, 80, 121, 220, 153, 13, 0, 126, 47, 152, 71,
-];
-// g = 171670852572928472888139503175515038342604868353722897525558979317782763303223
-pub const DL_G: &[u8] = &[
-    1, 123, 138, 66, 94, 146, 149, 205, 114, 62, 193, 223, 114, 153, 3, 64, 62, 199, 104, 176, 23,
-    44, 61, 209, 90, 46, 76, 221, 199, 206, 236, 113, 55,
-];
-// h = 23879747473187410337148282237111274999995618673330608148166136263819518677657
-pub const DL_H: &[u8] = &[
-    52, 203, 117, 70, 11, 73, 148, 228, 72, 226, 87, 31, 54, 82, 19, 130, 1, 46, 51, 83, 156, 101,
-    25, 183, 194, 160, 120, 163, 189, 126, 18, 153,
-];
+// DL parameters: RFC 7919 ffdhe2048, q = (p - 1) / 2, g = 2
+#[cfg(feature = "openssl")]
+pub const DL_GROUP: NamedGroup = NamedGroup::Ffdhe2048;
+// h is derived from DL_H_SEED with hash_to_subgroup, re-check with DlParams::verify_generation
+pub const DL_H_SEED: &[u8] = b"zkp_auth DL_H v1";
 
 // Constants for EC parameters
+// Default curve; EC_G and EC_H are points on it. The other curves use their
+// standard generator as g and derive h from EC_H_SEED.
+#[cfg(feature = "openssl")]
+pub const EC_CURVE: NamedCurve = NamedCurve::Secp256k1;
 // (x, y) = (87254753980364497552870028920037307725331490527579155562257602382389969860720, 5680688582317100251061343659837920885585461670565723910007566337080519753216)
 pub const EC_G: &[u8] = &[
     2, 192, 232, 112, 205, 104, 39, 209, 104, 239, 95, 247, 140, 78, 240, 157, 167, 251, 165, 208,
     111, 44, 60, 153, 143, 118, 211, 80, 132, 78, 190, 184, 112,
 ];
-// (x, y) = (84393524528455714661586904515318407170919209766852059800669914116931836361041, 87870688185902817226144150486770308118957474588889599130244928736264613435325)
+// (x, y) = (27679642402219111510752709342269761558484324536954826969227129706264133930368, 37666886343797660581961289057839544769461745502176678550965672765290257817684)
+// Derived from EC_H_SEED with hash_to_curve, re-check with EcParams::verify_generation
 pub const EC_H: &[u8] = &[
-    3, 186, 149, 10, 202, 151, 100, 123, 187, 240, 64, 106, 197, 30, 24, 93, 77, 13, 255, 228, 204,
-    21, 240, 176, 54, 127, 46, 1, 83, 124, 154, 185, 81,
+    2, 61, 50, 31, 86, 120, 173, 84, 106, 23, 50, 225, 10, 153, 217, 60, 220, 255, 93, 226, 54, 92,
+    230, 114, 26, 94, 140, 48, 126, 26, 36, 141, 128,
 ];
-pub const EC_CURVE: Nid = Nid::SECP256K1;
+pub const EC_H_SEED: &[u8] = b"zkp_auth EC_H v1";
 
-pub fn ec_params() -> Result<EcParams, ErrorStack> {
-    let mut ctx = BigNumContext::new().unwrap();
-    let group = EcGroup::from_curve_name(EC_CURVE)?;
+// Ristretto255 parameters: g is the standard base point, h is derived from
+// RISTRETTO_H_SEED with hash_to_group, re-check with RistrettoParams::verify_generation
+pub const RISTRETTO_H_SEED: &[u8] = b"zkp_auth RISTRETTO_H v1";
+
+// These come without fixed-base tables. The server, which runs every
+// verification, builds them with `precompute`; a client would only pay for them.
+#[cfg(feature = "openssl")]
+pub fn ec_params(curve: NamedCurve) -> Result<EcParams, ParamsError> {
+    let params = if curve == EC_CURVE {
+        let mut ctx = BigNumContext::new()?;
+        let group = EcGroup::from_curve_name(EC_CURVE.nid())?;
+
+        let g = EcPoint::from_bytes(&group, EC_G, &mut ctx)?;
+        EcParams::from_seed(EC_CURVE.nid(), g, EC_H_SEED)?
+    } else {
+        EcParams::from_named_curve(curve, EC_H_SEED)?
+    };
+    params.validate()?;
+
+    Ok(params)
+}
 
-    let g = EcPoint::from_bytes(&group, EC_G, &mut ctx)?;
-    let h = EcPoint::from_bytes(&group, EC_H, &mut ctx)?;
-    let params = EcParams::with_params(EC_CURVE, g, h)?;
+#[cfg(feature = "openssl")]
+pub fn dl_params() -> Result<DlParams, ParamsError> {
+    let params = DlParams::from_named_group(DL_GROUP, DL_H_SEED)?;
+    params.validate()?;
 
     Ok(params)
 }
 
-pub fn dl_params() -> Result<DlParams, ErrorStack> {
-    let q = BigNum::from_slice(DL_Q)?;
-    let p = BigNum::from_slice(DL_P)?;
-    let g = BigNum::from_slice(DL_G)?;
-    let h = BigNum::from_slice(DL_H)?;
-    let params = DlParams::with_params(q, p, g, h)?;
+pub fn ristretto_params() -> Result<RistrettoParams, ParamsError> {
+    let params = RistrettoParams::from_seed(RISTRETTO_H_SEED);
+    params.validate()?;
 
     Ok(params)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_published_h_matches_seed() -> Result<(), ParamsError> {
+        #[cfg(feature = "openssl")]
+        {
+            let mut ctx = BigNumContext::new()?;
+            let group = EcGroup::from_curve_name(EC_CURVE.nid())?;
+            let g = EcPoint::from_bytes(&group, EC_G, &mut ctx)?;
+            let h = EcPoint::from_bytes(&group, EC_H, &mut ctx)?;
+            let ec = EcParams::with_params(EC_CURVE.nid(), g, h)?;
+            assert!(
+                ec.verify_generation(EC_H_SEED)?,
+                "EC_H is not derived from EC_H_SEED"
+            );
+
+            for curve in NamedCurve::ALL {
+                let ec = ec_params(curve)?;
+                assert!(
+                    ec.verify_generation(EC_H_SEED)?,
+                    "h on {} is not derived from EC_H_SEED",
+                    curve.name()
+                );
+            }
+
+            let dl = dl_params()?;
+            assert!(
+                dl.verify_generation(DL_H_SEED)?,
+                "DL h is not derived from DL_H_SEED"
+            );
+        }
+
+        let ristretto = ristretto_params()?;
+        assert!(
+            ristretto.verify_generation(RISTRETTO_H_SEED),
+            "Ristretto h is not derived from RISTRETTO_H_SEED"
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,38 @@
+use crypto::transcript::Transcript;
+
+// Application labels; bump the version when the contents of a transcript change
+const LOGIN: &[u8] = b"zkp_auth/login/v2";
+const MEMBERSHIP_LOGIN: &[u8] = b"zkp_auth/membership-login/v2";
+const SIGNED_ACTION: &[u8] = b"zkp_auth/signed-action/v1";
+
+/// Transcript of a login by `user` in the session `server` opened as
+/// `auth_id`. `server` is the identity the server is configured with, and
+/// the `host:port` the client dialed; binding it keeps a proof made for one
+/// server from being relayed to another.
+pub fn login_transcript(server: &str, user: &str, auth_id: &str) -> Transcript {
+    let mut transcript = Transcript::new(LOGIN);
+    transcript.append_message(b"server", server.as_bytes());
+    transcript.append_message(b"user", user.as_bytes());
+    transcript.append_message(b"auth_id", auth_id.as_bytes());
+    transcript
+}
+
+/// Transcript of an anonymous login in the session `server` opened as
+/// `auth_id`; there is no user to bind.
+pub fn membership_transcript(server: &str, auth_id: &str) -> Transcript {
+    let mut transcript = Transcript::new(MEMBERSHIP_LOGIN);
+    transcript.append_message(b"server", server.as_bytes());
+    transcript.append_message(b"auth_id", auth_id.as_bytes());
+    transcript
+}
+
+/// Transcript `user` signs to approve `action` on `server`, bound to the
+/// `action_id` the server challenged them with so it is accepted only once.
+pub fn action_transcript(server: &str, user: &str, action_id: &str, action: &[u8]) -> Transcript {
+    let mut transcript = Transcript::new(SIGNED_ACTION);
+    transcript.append_message(b"server", server.as_bytes());
+    transcript.append_message(b"user", user.as_bytes());
+    transcript.append_message(b"action_id", action_id.as_bytes());
+    transcript.append_message(b"action", action);
+    transcript
+}